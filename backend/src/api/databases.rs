@@ -1,23 +1,26 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
 use crate::error::AppError;
 use crate::models::database::DatabaseConnection;
 use crate::models::request::CreateDatabaseRequest;
-use crate::models::schema::SchemaMetadata;
+use crate::models::schema::{SchemaMetadata, TableRowCount};
+use crate::models::schema_diff::{ConnectionDiff, ConnectionDiffRequest, SchemaSnapshot, SnapshotDiff, SnapshotDiffRequest};
 use crate::services::database_service::DatabaseService;
-use crate::types::{SharedDatabaseService, SharedSchemaService, SharedLLMService, SharedConnectionPoolCache};
+use crate::services::query_executor::query_id;
+use crate::types::{SharedDatabaseService, SharedSchemaService, SharedLLMService, SharedConnectionPoolCache, SharedQueryCursorStore};
 
 /// GET /api/v1/dbs
 /// List all database connections
 pub async fn list_databases(
-    State((service, _, _, _)): State<(
+    State((service, _, _, _, _)): State<(
         SharedDatabaseService,
         SharedSchemaService,
         SharedLLMService,
         SharedConnectionPoolCache,
+        SharedQueryCursorStore,
     )>,
 ) -> Result<Json<Vec<DatabaseConnection>>, AppError> {
     let connections = service.list_connections()?;
@@ -27,11 +30,12 @@ pub async fn list_databases(
 /// GET /api/v1/dbs/{name}
 /// Get database metadata (schema information)
 pub async fn get_database_metadata(
-    State((_, schema_service, _, _)): State<(
+    State((_, schema_service, _, _, _)): State<(
         SharedDatabaseService,
         SharedSchemaService,
         SharedLLMService,
         SharedConnectionPoolCache,
+        SharedQueryCursorStore,
     )>,
     Path(name): Path<String>,
 ) -> Result<Json<SchemaMetadata>, AppError> {
@@ -39,14 +43,109 @@ pub async fn get_database_metadata(
     Ok(Json(metadata))
 }
 
+/// GET /api/v1/dbs/{name}/tables/{schema}/{table}/row-count
+/// Count the rows of a table exactly, for connections that introspect estimated row counts
+pub async fn count_table_rows(
+    State((db_service, _, _, pool_cache, _)): State<(
+        SharedDatabaseService,
+        SharedSchemaService,
+        SharedLLMService,
+        SharedConnectionPoolCache,
+        SharedQueryCursorStore,
+    )>,
+    Path((name, schema, table)): Path<(String, String, String)>,
+) -> Result<Json<TableRowCount>, AppError> {
+    let connection = db_service.get_connection(&name)?;
+    let driver = pool_cache.get_or_create(&name, &connection.url).await?;
+
+    // The count runs like a query, bounded by the connection's statement timeout
+    let row_count = driver
+        .count_rows(&schema, &table, &query_id(None)?, &connection.settings)
+        .await?;
+
+    Ok(Json(TableRowCount { schema, name: table, row_count }))
+}
+
+/// POST /api/v1/dbs/{name}/schema/refresh
+/// Read the schema again, e.g. after a migration, replacing the cached metadata
+pub async fn refresh_database_schema(
+    State((_, schema_service, _, _, _)): State<(
+        SharedDatabaseService,
+        SharedSchemaService,
+        SharedLLMService,
+        SharedConnectionPoolCache,
+        SharedQueryCursorStore,
+    )>,
+    Path(name): Path<String>,
+) -> Result<Json<SchemaMetadata>, AppError> {
+    let metadata = schema_service.refresh_schema_metadata(&name).await?;
+    Ok(Json(metadata))
+}
+
+/// GET /api/v1/dbs/{name}/schema/snapshots
+/// List the schema snapshots taken by refreshes, oldest first
+pub async fn list_schema_snapshots(
+    State((_, schema_service, _, _, _)): State<(
+        SharedDatabaseService,
+        SharedSchemaService,
+        SharedLLMService,
+        SharedConnectionPoolCache,
+        SharedQueryCursorStore,
+    )>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<SchemaSnapshot>>, AppError> {
+    // Reading snapshots blocks on SQLite, so keep it off the async workers
+    let snapshots = tokio::task::spawn_blocking(move || schema_service.list_snapshots(&name))
+        .await
+        .map_err(|e| AppError::InternalError(format!("Snapshot listing task failed: {e}")))??;
+    Ok(Json(snapshots))
+}
+
+/// GET /api/v1/dbs/{name}/schema/diff
+/// Compare two schema snapshots, e.g. to see what changed since a date
+pub async fn diff_schema_snapshots(
+    State((_, schema_service, _, _, _)): State<(
+        SharedDatabaseService,
+        SharedSchemaService,
+        SharedLLMService,
+        SharedConnectionPoolCache,
+        SharedQueryCursorStore,
+    )>,
+    Path(name): Path<String>,
+    Query(request): Query<SnapshotDiffRequest>,
+) -> Result<Json<SnapshotDiff>, AppError> {
+    // Loading and comparing two snapshots blocks on SQLite and can take a while for large schemas
+    let diff = tokio::task::spawn_blocking(move || schema_service.diff_snapshots(&name, &request))
+        .await
+        .map_err(|e| AppError::InternalError(format!("Snapshot diff task failed: {e}")))??;
+    Ok(Json(diff))
+}
+
+/// GET /api/v1/schema-diff?left={name}&right={name}
+/// Compare the schemas of two connections, optionally with a migration script for one side
+pub async fn diff_database_schemas(
+    State((_, schema_service, _, _, _)): State<(
+        SharedDatabaseService,
+        SharedSchemaService,
+        SharedLLMService,
+        SharedConnectionPoolCache,
+        SharedQueryCursorStore,
+    )>,
+    Query(request): Query<ConnectionDiffRequest>,
+) -> Result<Json<ConnectionDiff>, AppError> {
+    let diff = schema_service.diff_connections(&request).await?;
+    Ok(Json(diff))
+}
+
 /// PUT /api/v1/dbs/{name}
 /// Create or update a database connection
 pub async fn upsert_database(
-    State((service, _, _, _)): State<(
+    State((service, schema_service, _, _, _)): State<(
         SharedDatabaseService,
         SharedSchemaService,
         SharedLLMService,
         SharedConnectionPoolCache,
+        SharedQueryCursorStore,
     )>,
     Path(name): Path<String>,
     Json(request): Json<CreateDatabaseRequest>,
@@ -65,6 +164,12 @@ pub async fn upsert_database(
         }
     }
 
+    let previous = match service.get_connection(&name) {
+        Ok(previous) => Some(previous),
+        Err(AppError::ConnectionNotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+
     // Store connection
     tracing::info!("[upsert_database] Storing database connection...");
     let connection = match service.store_connection(&name, &request) {
@@ -78,6 +183,11 @@ pub async fn upsert_database(
         }
     };
 
+    // A changed URL or settings make the cached schema, and with a new URL the pooled driver, out of date
+    if let Some(previous) = previous {
+        schema_service.connection_updated(&previous, &connection).await?;
+    }
+
     tracing::info!("[upsert_database] Database upsert completed successfully - name: {}", name);
     Ok(Json(connection))
 }
@@ -85,16 +195,20 @@ pub async fn upsert_database(
 /// DELETE /api/v1/dbs/{name}
 /// Delete a database connection
 pub async fn delete_database(
-    State((service, _, _, pool_cache)): State<(
+    State((service, _, _, pool_cache, cursors)): State<(
         SharedDatabaseService,
         SharedSchemaService,
         SharedLLMService,
         SharedConnectionPoolCache,
+        SharedQueryCursorStore,
     )>,
     Path(name): Path<String>,
 ) -> Result<StatusCode, AppError> {
     service.delete_connection(&name)?;
 
+    // Close open query cursors before the pool they read from
+    cursors.close_database(&name)?;
+
     // Remove connection pool from cache
     pool_cache.remove(&name).await;
 
@@ -1,22 +1,28 @@
 use axum::{
-    extract::{Path, State},
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use futures_util::StreamExt;
 use crate::error::AppError;
-use crate::models::query::{QueryRequest, QueryResponse};
+use crate::models::query::{PageRequest, QueryRequest, QueryResponse};
 use crate::models::natural_language::NaturalLanguageQueryRequest;
-use crate::types::{SharedDatabaseService, SharedSchemaService, SharedLLMService, SharedConnectionPoolCache};
-use crate::services::query_executor::QueryExecutor;
-use crate::services::sql_validator::validate_sql;
+use crate::types::{SharedDatabaseService, SharedSchemaService, SharedLLMService, SharedConnectionPoolCache, SharedQueryCursorStore};
+use crate::services::query_executor::{query_id, QueryExecutor};
+use crate::services::query_cursor::page_size;
+use crate::services::sql_validator::{validate_sql, validate_sql_for_paging};
 
 /// POST /api/v1/dbs/{name}/query
-/// Execute a SQL query against the specified database
+/// Execute a SQL query against the specified database, returning the first page of rows
 pub async fn execute_query(
-    State((db_service, _, _, pool_cache)): State<(
+    State((db_service, _, _, pool_cache, cursors)): State<(
         SharedDatabaseService,
         SharedSchemaService,
         SharedLLMService,
         SharedConnectionPoolCache,
+        SharedQueryCursorStore,
     )>,
     Path(name): Path<String>,
     Json(request): Json<QueryRequest>,
@@ -28,29 +34,109 @@ pub async fn execute_query(
         ));
     }
 
+    let query_id = query_id(request.query_id)?;
+
     // Get database connection
     let connection = db_service.get_connection(&name)?;
 
-    // Validate SQL syntax and ensure it's SELECT only
-    let validated_sql = validate_sql(&request.sql)?;
-
     // Get or create connection pool
-    let pool = pool_cache.get_or_create(&name, &connection.url).await?;
+    let driver = pool_cache.get_or_create(&name, &connection.url).await?;
 
-    // Execute query
-    let response = QueryExecutor::execute_query(&pool, &validated_sql).await?;
+    // Validate SQL syntax and ensure it's SELECT only. The result is bounded by the
+    // connection's maximum rows, which the cursor then reads page by page
+    let validated_sql = validate_sql_for_paging(&request.sql, driver.dialect(), &connection.settings)?;
+
+    // Execute query, keeping a cursor open if there are more pages.
+    // If the client disconnects, this future is dropped and the query cancelled.
+    let response = QueryExecutor::execute_paged(
+        driver.as_ref(),
+        &validated_sql,
+        &query_id,
+        &connection.settings,
+        &cursors,
+        &name,
+        page_size(request.page_size),
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// GET /api/v1/dbs/{name}/query/{cursor}
+/// Fetch the next page of rows of a query cursor
+pub async fn fetch_query_page(
+    State((_, _, _, _, cursors)): State<(
+        SharedDatabaseService,
+        SharedSchemaService,
+        SharedLLMService,
+        SharedConnectionPoolCache,
+        SharedQueryCursorStore,
+    )>,
+    Path((name, cursor)): Path<(String, String)>,
+    Query(request): Query<PageRequest>,
+) -> Result<Json<QueryResponse>, AppError> {
+    let response = cursors
+        .next_page(&name, &cursor, page_size(request.page_size))
+        .await?;
 
     Ok(Json(response))
 }
 
+/// POST /api/v1/dbs/{name}/query/stream
+/// Execute a SQL query and stream the result as newline delimited JSON
+pub async fn execute_query_stream(
+    State((db_service, _, _, pool_cache, _)): State<(
+        SharedDatabaseService,
+        SharedSchemaService,
+        SharedLLMService,
+        SharedConnectionPoolCache,
+        SharedQueryCursorStore,
+    )>,
+    Path(name): Path<String>,
+    Json(request): Json<QueryRequest>,
+) -> Result<Response, AppError> {
+    // Validate SQL is not empty
+    if request.sql.trim().is_empty() {
+        return Err(AppError::ValidationError(
+            "SQL query cannot be empty".to_string(),
+        ));
+    }
+
+    let query_id = query_id(request.query_id)?;
+
+    // Get database connection
+    let connection = db_service.get_connection(&name)?;
+
+    // Get or create connection pool
+    let driver = pool_cache.get_or_create(&name, &connection.url).await?;
+
+    // Validate SQL syntax and ensure it's SELECT only
+    let validated_sql = validate_sql(&request.sql, driver.dialect(), &connection.settings)?;
+
+    // Errors before the first row are returned as a regular error response.
+    // The query is cancelled if the client disconnects and the body is dropped.
+    let frames = QueryExecutor::stream_query(driver.as_ref(), &validated_sql, &query_id, &connection.settings).await?;
+
+    // One JSON frame per line, written as soon as it is produced
+    let body = Body::from_stream(frames.map(|frame| {
+        serde_json::to_vec(&frame).map(|mut line| {
+            line.push(b'\n');
+            line
+        })
+    }));
+
+    Ok(([(header::CONTENT_TYPE, "application/x-ndjson")], body).into_response())
+}
+
 /// POST /api/v1/dbs/{name}/query/natural
 /// Execute a natural language query (generates SQL and executes it)
 pub async fn execute_natural_language_query(
-    State((db_service, schema_service, llm_service, pool_cache)): State<(
+    State((db_service, schema_service, llm_service, pool_cache, _)): State<(
         SharedDatabaseService,
         SharedSchemaService,
         SharedLLMService,
         SharedConnectionPoolCache,
+        SharedQueryCursorStore,
     )>,
     Path(name): Path<String>,
     Json(request): Json<NaturalLanguageQueryRequest>,
@@ -62,26 +148,53 @@ pub async fn execute_natural_language_query(
         ));
     }
 
+    let query_id = query_id(request.query_id)?;
+
     // Get database connection
     let connection = db_service.get_connection(&name)?;
 
     // Get schema metadata for context
     let schema = schema_service.get_schema_metadata(&name).await?;
 
+    // Get or create connection pool
+    let driver = pool_cache.get_or_create(&name, &connection.url).await?;
+
     // Convert natural language to SQL using LLM service from state
     let sql = llm_service
-        .natural_language_to_sql(&request.prompt, &schema)
+        .natural_language_to_sql(&request.prompt, &schema, driver.kind())
         .await?;
 
     // Validate the generated SQL
-    let validated_sql = validate_sql(&sql)?;
-
-    // Get or create connection pool
-    let pool = pool_cache.get_or_create(&name, &connection.url).await?;
+    let validated_sql = validate_sql(&sql, driver.dialect(), &connection.settings)?;
 
     // Execute query
-    let response = QueryExecutor::execute_query(&pool, &validated_sql).await?;
+    let response = QueryExecutor::execute_query(driver.as_ref(), &validated_sql, &query_id, &connection.settings).await?;
 
     Ok(Json(response))
 }
 
+/// DELETE /api/v1/dbs/{name}/queries/{id}
+/// Cancel a running query
+pub async fn cancel_query(
+    State((_, _, _, pool_cache, _)): State<(
+        SharedDatabaseService,
+        SharedSchemaService,
+        SharedLLMService,
+        SharedConnectionPoolCache,
+        SharedQueryCursorStore,
+    )>,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    // A database without a connected driver has no running queries
+    let cancelled = match pool_cache.get(&name).await {
+        Some(driver) => driver.cancel(&id).await?,
+        None => false,
+    };
+
+    if cancelled {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!("Query '{id}' is not running on database '{name}'")))
+    }
+}
+
@@ -3,6 +3,7 @@ use dotenv::dotenv;
 
 pub struct Config {
     pub sqlite_db_path: String,
+    pub file_data_root: String,
     pub llm_api_key: String,
     pub llm_api_url: String,
     pub port: u16,
@@ -13,20 +14,18 @@ impl Config {
         dotenv().ok();
 
         // Resolve SQLite path (handle ~ expansion)
-        let sqlite_path = env::var("SQLITE_DB_PATH")
-            .unwrap_or_else(|_| "~/.db_query/db_query.db".to_string());
+        let sqlite_db_path = expand_home(
+            env::var("SQLITE_DB_PATH").unwrap_or_else(|_| "~/.db_query/db_query.db".to_string()),
+        );
 
-        let sqlite_db_path = if sqlite_path.starts_with("~/") {
-            let home = env::var("HOME")
-                .or_else(|_| env::var("USERPROFILE"))
-                .unwrap_or_else(|_| ".".to_string());
-            sqlite_path.replace("~/", &format!("{home}/"))
-        } else {
-            sqlite_path
-        };
+        // Directory that file:// and sqlite:// connections may read from
+        let file_data_root = expand_home(
+            env::var("FILE_DATA_ROOT").unwrap_or_else(|_| "~/.db_query/data".to_string()),
+        );
 
         Ok(Config {
             sqlite_db_path,
+            file_data_root,
             llm_api_key: env::var("LLM_API_KEY")
                 .unwrap_or_default(),
             llm_api_url: env::var("LLM_API_URL")
@@ -39,3 +38,15 @@ impl Config {
     }
 }
 
+
+/// Replace a leading `~/` with the user's home directory
+fn expand_home(path: String) -> String {
+    if path.starts_with("~/") {
+        let home = env::var("HOME")
+            .or_else(|_| env::var("USERPROFILE"))
+            .unwrap_or_else(|_| ".".to_string());
+        path.replace("~/", &format!("{home}/"))
+    } else {
+        path
+    }
+}
@@ -15,12 +15,21 @@ pub fn init_db(db_path: &str) -> SqliteResult<Connection> {
         "CREATE TABLE IF NOT EXISTS databases (
             name TEXT PRIMARY KEY NOT NULL,
             url TEXT NOT NULL,
+            settings TEXT NOT NULL DEFAULT '{}',
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL
         )",
         [],
     )?;
 
+    // Databases created before connection settings existed lack the column
+    let has_settings = conn
+        .prepare("SELECT 1 FROM pragma_table_info('databases') WHERE name = 'settings'")?
+        .exists([])?;
+    if !has_settings {
+        conn.execute("ALTER TABLE databases ADD COLUMN settings TEXT NOT NULL DEFAULT '{}'", [])?;
+    }
+
     // Create schema_metadata table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS schema_metadata (
@@ -42,6 +51,20 @@ pub fn init_db(db_path: &str) -> SqliteResult<Connection> {
         [],
     )?;
 
+    // Create schema_snapshots table, keeping the schema read by every refresh
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            db_name TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            metadata_json TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (db_name) REFERENCES databases(name),
+            UNIQUE(db_name, version)
+        )",
+        [],
+    )?;
+
     Ok(conn)
 }
 
@@ -59,6 +82,29 @@ mod tests {
             .collect();
         assert!(tables.contains(&"databases".to_string()));
         assert!(tables.contains(&"schema_metadata".to_string()));
+        assert!(tables.contains(&"schema_snapshots".to_string()));
+    }
+
+    #[test]
+    fn test_init_db_adds_settings_column() {
+        let path = std::env::temp_dir().join("db_query_settings_migration.db");
+        let _ = std::fs::remove_file(&path);
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute(
+                "CREATE TABLE databases (name TEXT PRIMARY KEY NOT NULL, url TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL)",
+                [],
+            ).unwrap();
+            conn.execute("INSERT INTO databases VALUES ('old', 'postgres://localhost/db', 'now', 'now')", []).unwrap();
+        }
+
+        let conn = init_db(path.to_str().unwrap()).unwrap();
+        let settings: String = conn
+            .query_row("SELECT settings FROM databases WHERE name = 'old'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(settings, "{}");
+
+        let _ = std::fs::remove_file(&path);
     }
 }
 
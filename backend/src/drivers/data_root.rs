@@ -0,0 +1,69 @@
+// Directory that connections to local files may read from
+
+use crate::error::AppError;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directory `file://` and `sqlite://` URLs must point inside, set once at startup
+static DATA_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Restrict `file://` and `sqlite://` connections to files and directories under `root`
+///
+/// Without a root, e.g. in tests, any readable path can be opened.
+pub fn set_data_root(root: PathBuf) {
+    if DATA_ROOT.set(root).is_err() {
+        tracing::warn!("data root for local connections already set");
+    }
+}
+
+/// Path a connection URL may open, resolved within the data root if one is set
+pub(crate) fn resolve_data_path(path: PathBuf) -> Result<PathBuf, AppError> {
+    match DATA_ROOT.get() {
+        Some(root) => path_within_root(&path, root),
+        None => Ok(path),
+    }
+}
+
+/// Resolve `path`, following `..` and symbolic links, and check it lies under `root`
+fn path_within_root(path: &Path, root: &Path) -> Result<PathBuf, AppError> {
+    let resolve = |path: &Path| {
+        path.canonicalize()
+            .map_err(|e| AppError::ConnectionError(format!("Failed to read '{}': {e}", path.display())))
+    };
+    let root = resolve(root)?;
+    let resolved = resolve(path)?;
+
+    if !resolved.starts_with(&root) {
+        tracing::warn!(path = %path.display(), root = %root.display(), "connection URL outside the data root");
+        return Err(AppError::PermissionDenied(format!(
+            "File and SQLite URLs must point inside the data directory '{}'",
+            root.display()
+        )));
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_within_data_root() {
+        let dir = std::env::temp_dir().join("db_query_data_root");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("app.db");
+        std::fs::write(&file, "").unwrap();
+
+        assert_eq!(path_within_root(&file, &dir).unwrap(), file.canonicalize().unwrap());
+        assert!(path_within_root(&dir, &dir).is_ok());
+
+        // Paths leaving the root, directly or through `..`, are refused
+        assert!(matches!(path_within_root(Path::new("/etc/passwd"), &dir), Err(AppError::PermissionDenied(_))));
+        let escape = dir.join("..").join(dir.file_name().unwrap()).join("..");
+        assert!(matches!(path_within_root(&escape, &dir), Err(AppError::PermissionDenied(_))));
+        assert!(matches!(path_within_root(&dir.join("missing"), &dir), Err(AppError::ConnectionError(_))));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
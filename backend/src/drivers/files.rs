@@ -0,0 +1,627 @@
+use crate::drivers::sqlite::quote_identifier;
+use crate::drivers::{
+    resolve_data_path, DatabaseDriver, DatabaseKind, IntrospectedSchema, QueryRows, RunningQueries, SharedDriver, SqliteDriver,
+};
+use crate::error::AppError;
+use crate::models::database::ConnectionSettings;
+use async_trait::async_trait;
+use parquet::file::reader::SerializedFileReader;
+use rusqlite::Connection;
+use serde_json::{Map, Value};
+use sqlparser::dialect::{Dialect, SQLiteDialect};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Driver exposing local CSV, JSON and Parquet files as tables
+///
+/// A `file://` URL points at a directory (every supported file becomes a table)
+/// or at a single file. The files are loaded into an in-memory `SQLite` database
+/// when the connection is opened, and queries run on that embedded engine.
+pub struct FileDriver {
+    inner: SqliteDriver,
+    /// File or directory the data was loaded from
+    path: PathBuf,
+}
+
+impl FileDriver {
+    /// Load the files referenced by a `file://` URL
+    pub async fn connect(url: &str) -> Result<Self, AppError> {
+        let path = resolve_data_path(file_path_from_url(url)?)?;
+        Self::load(path, RunningQueries::default()).await
+    }
+
+    /// Load the files under `path`, tracking queries in `running`
+    async fn load(path: PathBuf, running: RunningQueries) -> Result<Self, AppError> {
+        tracing::info!(path = %path.display(), "loading data files");
+
+        let files = path.clone();
+        let conn = tokio::task::spawn_blocking(move || load_files(&files))
+            .await
+            .map_err(|e| AppError::InternalError(format!("File loading task failed: {e}")))??;
+
+        Ok(Self {
+            inner: SqliteDriver::with_running_queries(conn, running),
+            path,
+        })
+    }
+}
+
+#[async_trait]
+impl DatabaseDriver for FileDriver {
+    fn kind(&self) -> DatabaseKind {
+        DatabaseKind::Files
+    }
+
+    fn dialect(&self) -> &'static dyn Dialect {
+        &SQLiteDialect {}
+    }
+
+    async fn test_connection(&self) -> Result<(), AppError> {
+        self.inner.test_connection().await
+    }
+
+    async fn introspect(&self, settings: &ConnectionSettings) -> Result<IntrospectedSchema, AppError> {
+        self.inner.introspect(settings).await
+    }
+
+    async fn execute(&self, sql: &str, query_id: &str, settings: &ConnectionSettings) -> Result<QueryRows, AppError> {
+        self.inner.execute(sql, query_id, settings).await
+    }
+
+    async fn cancel(&self, query_id: &str) -> Result<bool, AppError> {
+        self.inner.cancel(query_id).await
+    }
+
+    async fn reload(&self) -> Result<Option<SharedDriver>, AppError> {
+        let reloaded = Self::load(self.path.clone(), self.inner.running_queries()).await?;
+        Ok(Some(Arc::new(reloaded)))
+    }
+
+    async fn close(&self) {
+        self.inner.close().await;
+    }
+}
+
+/// Supported data file formats, detected from the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileFormat {
+    Csv,
+    Json,
+    NdJson,
+    Parquet,
+}
+
+impl FileFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "ndjson" | "jsonl" => Some(Self::NdJson),
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// `SQLite` column type inferred from the file contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Integer,
+    Real,
+    Boolean,
+    Text,
+    Json,
+}
+
+impl ColumnType {
+    fn sql_name(self) -> &'static str {
+        match self {
+            Self::Integer => "INTEGER",
+            Self::Real => "REAL",
+            Self::Boolean => "BOOLEAN",
+            Self::Text => "TEXT",
+            Self::Json => "JSON",
+        }
+    }
+
+    /// Combine the types of two values seen in the same column
+    fn merge(current: Option<Self>, next: Self) -> Self {
+        match (current, next) {
+            (None, next) => next,
+            (Some(current), next) if current == next => current,
+            (Some(Self::Integer), Self::Real) | (Some(Self::Real), Self::Integer) => Self::Real,
+            _ => Self::Text,
+        }
+    }
+
+    fn of_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) => Some(Self::Boolean),
+            Value::Number(n) if n.is_i64() => Some(Self::Integer),
+            Value::Number(_) => Some(Self::Real),
+            Value::String(_) => Some(Self::Text),
+            Value::Array(_) | Value::Object(_) => Some(Self::Json),
+        }
+    }
+
+    /// Numbers written with leading zeros, e.g. ZIP codes like `00501`, stay text to keep their zeros,
+    /// as do `nan` and `inf`, which JSON numbers cannot hold
+    fn of_csv(cell: &str) -> Option<Self> {
+        if cell.is_empty() {
+            None
+        } else if has_leading_zeros(cell) {
+            Some(Self::Text)
+        } else if cell.parse::<i64>().is_ok() {
+            Some(Self::Integer)
+        } else if cell.parse::<f64>().is_ok_and(f64::is_finite) {
+            Some(Self::Real)
+        } else if cell.eq_ignore_ascii_case("true") || cell.eq_ignore_ascii_case("false") {
+            Some(Self::Boolean)
+        } else {
+            Some(Self::Text)
+        }
+    }
+}
+
+/// Whether a cell starts with a zero followed by another digit, after an optional sign
+fn has_leading_zeros(cell: &str) -> bool {
+    let digits = cell.strip_prefix(['+', '-']).unwrap_or(cell).as_bytes();
+    digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
+}
+
+/// Rows read from a single data file
+struct FileTable {
+    name: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+/// Extract the path from a `file://` URL
+fn file_path_from_url(url: &str) -> Result<PathBuf, AppError> {
+    let path = url.strip_prefix("file://").unwrap_or(url);
+
+    if path.is_empty() {
+        return Err(AppError::ValidationError(
+            "File URL must include a file or directory path".to_string(),
+        ));
+    }
+
+    Ok(PathBuf::from(path))
+}
+
+/// Load every supported file under `path` into a new in-memory database
+fn load_files(path: &Path) -> Result<Connection, AppError> {
+    let files = collect_files(path)?;
+    if files.is_empty() {
+        return Err(AppError::ConnectionError(format!(
+            "No CSV, JSON or Parquet files found in '{}'",
+            path.display()
+        )));
+    }
+
+    let mut conn = Connection::open_in_memory()?;
+    let mut table_names = HashSet::new();
+
+    for (file, format) in files {
+        let mut table = read_file(&file, format).map_err(|e| {
+            tracing::error!(file = %file.display(), error = %e, "failed to load data file");
+            AppError::ConnectionError(format!("Failed to load '{}': {e}", file.display()))
+        })?;
+
+        // Two files with the same stem (e.g. `a.csv` and `a.json`) get distinct tables
+        if !table_names.insert(table.name.clone()) {
+            table.name = unique_name(&format!("{}_{format:?}", table.name).to_lowercase(), &table_names);
+            table_names.insert(table.name.clone());
+        }
+
+        tracing::info!(file = %file.display(), table = %table.name, rows = table.rows.len(), "loaded data file");
+        insert_table(&mut conn, &table)?;
+    }
+
+    // The data is a snapshot of the files, so reject any attempt to modify it
+    conn.execute_batch("PRAGMA query_only = ON")?;
+    Ok(conn)
+}
+
+/// List supported files in a directory (sorted by name), or the single file `path` points at
+fn collect_files(path: &Path) -> Result<Vec<(PathBuf, FileFormat)>, AppError> {
+    if path.is_file() {
+        return FileFormat::from_path(path)
+            .map(|format| vec![(path.to_path_buf(), format)])
+            .ok_or_else(|| AppError::ConnectionError(format!(
+                "Unsupported file type '{}'. Expected .csv, .json, .ndjson, .jsonl or .parquet",
+                path.display()
+            )));
+    }
+
+    let entries = std::fs::read_dir(path).map_err(|e| {
+        AppError::ConnectionError(format!("Failed to read directory '{}': {e}", path.display()))
+    })?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let file = entry
+            .map_err(|e| AppError::ConnectionError(format!("Failed to read directory entry: {e}")))?
+            .path();
+        if file.is_file() && let Some(format) = FileFormat::from_path(&file) {
+            files.push((file, format));
+        }
+    }
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(files)
+}
+
+/// Read a data file into memory as JSON values
+fn read_file(path: &Path, format: FileFormat) -> Result<FileTable, String> {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("data");
+    let name = sanitize_name(stem);
+
+    match format {
+        FileFormat::Csv => read_csv(path, name),
+        FileFormat::Json => {
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let records: Vec<Map<String, Value>> = serde_json::from_str(&text)
+                .map_err(|e| format!("expected a JSON array of objects: {e}"))?;
+            Ok(table_from_records(name, records))
+        }
+        FileFormat::NdJson => {
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let records = text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(index, line)| {
+                    serde_json::from_str(line).map_err(|e| format!("line {}: {e}", index + 1))
+                })
+                .collect::<Result<Vec<Map<String, Value>>, _>>()?;
+            Ok(table_from_records(name, records))
+        }
+        FileFormat::Parquet => {
+            let reader = SerializedFileReader::try_from(path).map_err(|e| e.to_string())?;
+            let records = reader
+                .into_iter()
+                .map(|row| match row.map_err(|e| e.to_string())?.to_json_value() {
+                    Value::Object(record) => Ok(record),
+                    _ => Err("unexpected Parquet row shape".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(table_from_records(name, records))
+        }
+    }
+}
+
+/// Read a CSV file with a header row, converting cells to the inferred column types
+fn read_csv(path: &Path, name: String) -> Result<FileTable, String> {
+    let mut reader = csv::Reader::from_path(path).map_err(|e| e.to_string())?;
+
+    let mut seen = HashSet::new();
+    let columns: Vec<String> = reader
+        .headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            let header = if header.trim().is_empty() {
+                format!("column_{}", index + 1)
+            } else {
+                header.trim().to_string()
+            };
+            let header = unique_name(&header, &seen);
+            seen.insert(header.clone());
+            header
+        })
+        .collect();
+
+    let records = reader
+        .records()
+        .map(|record| record.map(|r| r.iter().map(str::to_string).collect::<Vec<_>>()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Infer each column's type from all of its non-empty cells
+    let types: Vec<ColumnType> = (0..columns.len())
+        .map(|index| {
+            records
+                .iter()
+                .filter_map(|record| record.get(index).and_then(|cell| ColumnType::of_csv(cell)))
+                .fold(None, |current, next| Some(ColumnType::merge(current, next)))
+                .unwrap_or(ColumnType::Text)
+        })
+        .collect();
+
+    let rows = records
+        .into_iter()
+        .map(|record| {
+            types
+                .iter()
+                .enumerate()
+                .map(|(index, column_type)| {
+                    csv_cell_to_json(record.get(index).map_or("", String::as_str), *column_type)
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(FileTable { name, columns, rows })
+}
+
+/// Convert a CSV cell to a JSON value of the column's type (empty cells become NULL)
+fn csv_cell_to_json(cell: &str, column_type: ColumnType) -> Value {
+    if cell.is_empty() && column_type != ColumnType::Text {
+        return Value::Null;
+    }
+
+    match column_type {
+        ColumnType::Integer => cell.parse::<i64>().map_or(Value::Null, Value::from),
+        ColumnType::Real => cell
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map_or(Value::Null, Value::Number),
+        ColumnType::Boolean => Value::Bool(cell.eq_ignore_ascii_case("true")),
+        ColumnType::Text | ColumnType::Json => Value::String(cell.to_string()),
+    }
+}
+
+/// Build a table from JSON objects, using the keys in first-seen order as columns
+fn table_from_records(name: String, records: Vec<Map<String, Value>>) -> FileTable {
+    let mut columns: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    for record in &records {
+        for key in record.keys() {
+            if seen.insert(key.clone()) {
+                columns.push(key.clone());
+            }
+        }
+    }
+
+    let rows = records
+        .into_iter()
+        .map(|mut record| {
+            columns
+                .iter()
+                .map(|column| record.remove(column).unwrap_or(Value::Null))
+                .collect()
+        })
+        .collect();
+
+    FileTable { name, columns, rows }
+}
+
+/// Create a table for the file and insert all of its rows
+fn insert_table(conn: &mut Connection, table: &FileTable) -> Result<(), AppError> {
+    let types: Vec<ColumnType> = (0..table.columns.len())
+        .map(|index| {
+            table
+                .rows
+                .iter()
+                .filter_map(|row| row.get(index).and_then(ColumnType::of_json))
+                .fold(None, |current, next| Some(ColumnType::merge(current, next)))
+                .unwrap_or(ColumnType::Text)
+        })
+        .collect();
+
+    let column_defs: Vec<String> = table
+        .columns
+        .iter()
+        .zip(&types)
+        .map(|(column, column_type)| format!("{} {}", quote_identifier(column), column_type.sql_name()))
+        .collect();
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        &format!("CREATE TABLE {} ({})", quote_identifier(&table.name), column_defs.join(", ")),
+        [],
+    )?;
+
+    {
+        let placeholders = vec!["?"; table.columns.len()].join(", ");
+        let mut stmt = tx.prepare(&format!(
+            "INSERT INTO {} VALUES ({placeholders})",
+            quote_identifier(&table.name)
+        ))?;
+
+        for row in &table.rows {
+            let values = row
+                .iter()
+                .zip(&types)
+                .map(|(value, column_type)| json_to_sqlite(value, *column_type));
+            stmt.execute(rusqlite::params_from_iter(values))?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Convert a JSON value to a `SQLite` value for a column of the given type
+fn json_to_sqlite(value: &Value, column_type: ColumnType) -> rusqlite::types::Value {
+    use rusqlite::types::Value as SqliteValue;
+
+    match value {
+        Value::Null => SqliteValue::Null,
+        Value::Bool(b) if column_type == ColumnType::Boolean => SqliteValue::Integer(i64::from(*b)),
+        Value::Number(n) if column_type == ColumnType::Integer => {
+            n.as_i64().map_or(SqliteValue::Null, SqliteValue::Integer)
+        }
+        Value::Number(n) if column_type == ColumnType::Real => {
+            n.as_f64().map_or(SqliteValue::Null, SqliteValue::Real)
+        }
+        Value::String(s) => SqliteValue::Text(s.clone()),
+        other => SqliteValue::Text(other.to_string()),
+    }
+}
+
+/// Turn a file stem into a table or column name
+fn sanitize_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+
+    if sanitized.is_empty() { "data".to_string() } else { sanitized }
+}
+
+/// Append a numeric suffix until `name` does not collide with `taken`
+fn unique_name(name: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+
+    let mut suffix = 2;
+    loop {
+        let candidate = format!("{name}_{suffix}");
+        if !taken.contains(&candidate) {
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::query::LogicalType;
+    use crate::services::query_executor::QueryExecutor;
+    use crate::services::sql_validator::validate_sql;
+    use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    /// Create a directory with one file of each supported format
+    fn setup_test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("db_query_files_driver_{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(
+            dir.join("users.csv"),
+            "id,name,score,active\n1,alice,9.5,true\n2,bob,,false\n",
+        ).unwrap();
+        std::fs::write(
+            dir.join("orders.json"),
+            r#"[{"id": 10, "user_id": 1, "total": 12.5, "tags": ["a"]}, {"id": 11, "user_id": 2, "total": 3}]"#,
+        ).unwrap();
+        std::fs::write(
+            dir.join("events.ndjson"),
+            "{\"user_id\": 1, \"kind\": \"login\"}\n\n{\"user_id\": 1, \"kind\": \"logout\"}\n",
+        ).unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        write_parquet(&dir.join("products.parquet"));
+
+        dir
+    }
+
+    fn write_parquet(path: &Path) {
+        let schema = Arc::new(parse_message_type(
+            "message schema { REQUIRED INT64 id; REQUIRED BYTE_ARRAY name (UTF8); }"
+        ).unwrap());
+        let file = std::fs::File::create(path).unwrap();
+        let mut writer = SerializedFileWriter::new(file, schema, Arc::default()).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column.typed::<Int64Type>().write_batch(&[100, 200], None, None).unwrap();
+        column.close().unwrap();
+
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&[ByteArray::from("pen"), ByteArray::from("ink")], None, None)
+            .unwrap();
+        column.close().unwrap();
+
+        row_group.close().unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_column_type_inference() {
+        assert_eq!(ColumnType::of_csv("42"), Some(ColumnType::Integer));
+        assert_eq!(ColumnType::of_csv("4.2"), Some(ColumnType::Real));
+        assert_eq!(ColumnType::of_csv("TRUE"), Some(ColumnType::Boolean));
+        assert_eq!(ColumnType::of_csv("hello"), Some(ColumnType::Text));
+        assert_eq!(ColumnType::of_csv(""), None);
+        assert_eq!(ColumnType::of_csv("00501"), Some(ColumnType::Text));
+        assert_eq!(ColumnType::of_csv("-007"), Some(ColumnType::Text));
+        assert_eq!(ColumnType::of_csv("0"), Some(ColumnType::Integer));
+        assert_eq!(ColumnType::of_csv("0.5"), Some(ColumnType::Real));
+        assert_eq!(ColumnType::of_csv("nan"), Some(ColumnType::Text));
+        assert_eq!(ColumnType::of_csv("-inf"), Some(ColumnType::Text));
+        // A column with one of them keeps every value as text
+        assert_eq!(ColumnType::merge(Some(ColumnType::Real), ColumnType::Text), ColumnType::Text);
+        assert_eq!(ColumnType::merge(Some(ColumnType::Integer), ColumnType::Real), ColumnType::Real);
+        assert_eq!(ColumnType::merge(Some(ColumnType::Integer), ColumnType::Text), ColumnType::Text);
+    }
+
+    #[tokio::test]
+    async fn test_introspect_directory() {
+        let dir = setup_test_dir("introspect");
+        let driver = FileDriver::connect(&format!("file://{}", dir.display())).await.unwrap();
+
+        driver.test_connection().await.unwrap();
+        let schema = driver.introspect(&ConnectionSettings::default()).await.unwrap();
+
+        let names: Vec<&str> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["events", "orders", "products", "users"]);
+
+        let users = schema.tables.iter().find(|t| t.name == "users").unwrap();
+        let types: Vec<&str> = users.columns.iter().map(|c| c.data_type.as_str()).collect();
+        assert_eq!(types, vec!["INTEGER", "TEXT", "REAL", "BOOLEAN"]);
+        assert_eq!(users.row_count, Some(2));
+
+        let orders = schema.tables.iter().find(|t| t.name == "orders").unwrap();
+        let types: Vec<&str> = orders.columns.iter().map(|c| c.data_type.as_str()).collect();
+        assert_eq!(types, vec!["INTEGER", "INTEGER", "REAL", "JSON"]);
+
+        let products = schema.tables.iter().find(|t| t.name == "products").unwrap();
+        assert_eq!(products.columns[0].data_type, "INTEGER");
+        assert_eq!(products.row_count, Some(2));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_query_across_files() {
+        let dir = setup_test_dir("query");
+        let driver = FileDriver::connect(&format!("file://{}", dir.display())).await.unwrap();
+
+        let sql = validate_sql(
+            "SELECT u.name, SUM(o.total) AS total FROM users u JOIN orders o ON o.user_id = u.id GROUP BY u.name ORDER BY u.name",
+            driver.dialect(),
+            &ConnectionSettings::default(),
+        ).unwrap();
+        let response = QueryExecutor::execute_query(&driver, &sql, "test", &ConnectionSettings::default()).await.unwrap();
+
+        let names: Vec<&str> = response.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["name", "total"]);
+        assert_eq!(response.columns[0].logical_type, LogicalType::Text);
+        assert_eq!(response.columns[1].logical_type, LogicalType::Decimal);
+        assert_eq!(response.rows[0], vec![serde_json::json!("alice"), serde_json::json!(12.5)]);
+        assert_eq!(response.rows[1], vec![serde_json::json!("bob"), serde_json::json!(3.0)]);
+
+        // The loaded data is read-only
+        assert!(driver.execute("DELETE FROM users", "test", &ConnectionSettings::default()).await.is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_single_file_and_missing_path() {
+        let dir = setup_test_dir("single");
+        let driver = FileDriver::connect(&format!("file://{}", dir.join("events.ndjson").display())).await.unwrap();
+        let schema = driver.introspect(&ConnectionSettings::default()).await.unwrap();
+        assert_eq!(schema.tables.len(), 1);
+        assert_eq!(schema.tables[0].row_count, Some(2));
+
+        let missing = FileDriver::connect(&format!("file://{}", dir.join("missing").display())).await;
+        assert!(matches!(missing, Err(AppError::ConnectionError(_))));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
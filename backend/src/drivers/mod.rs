@@ -0,0 +1,358 @@
+// Database drivers module
+// Each supported URL scheme is backed by a `DatabaseDriver` implementation
+
+mod data_root;
+pub mod files;
+pub mod mysql;
+pub mod postgres;
+mod postgres_values;
+mod running;
+pub mod sqlite;
+
+use crate::error::AppError;
+use crate::models::database::ConnectionSettings;
+use crate::models::query::QueryColumn;
+use crate::models::schema::{FunctionInfo, SequenceInfo, TableInfo, ViewInfo};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use sqlparser::dialect::Dialect;
+use sqlx::pool::{Pool, PoolOptions};
+use sqlx::{Database, Executor, IntoArguments};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+pub use files::FileDriver;
+pub use mysql::MySqlDriver;
+pub use postgres::PostgresDriver;
+pub use sqlite::SqliteDriver;
+
+pub use data_root::set_data_root;
+pub(crate) use data_root::resolve_data_path;
+pub(crate) use running::{CancelQuery, RunningQueries, TrackedConnection};
+
+/// Number of rows buffered between a streaming query and its consumer
+const STREAM_BUFFER_ROWS: usize = 256;
+
+/// Per-table introspection queries run at once, leaving a pooled connection free
+const INTROSPECTION_CONCURRENCY: usize = 4;
+
+/// Kind of target database, derived from the connection URL scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseKind {
+    Postgres,
+    MySql,
+    Sqlite,
+    Files,
+}
+
+impl DatabaseKind {
+    /// Detect the database kind from a connection URL
+    #[must_use]
+    pub fn from_url(url: &str) -> Option<Self> {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            Some(Self::Postgres)
+        } else if url.starts_with("mysql://") {
+            Some(Self::MySql)
+        } else if url.starts_with("sqlite://") {
+            Some(Self::Sqlite)
+        } else if url.starts_with("file://") {
+            Some(Self::Files)
+        } else {
+            None
+        }
+    }
+
+    /// Human readable name used in logs and error messages
+    #[must_use]
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Postgres => "PostgreSQL",
+            Self::MySql => "MySQL",
+            Self::Sqlite => "SQLite",
+            Self::Files => "CSV/JSON/Parquet files",
+        }
+    }
+
+    /// Name of the SQL dialect queries must be written in
+    #[must_use]
+    pub fn sql_dialect_name(self) -> &'static str {
+        match self {
+            Self::Postgres => "PostgreSQL",
+            Self::MySql => "MySQL",
+            // Data files are queried through an embedded SQLite database
+            Self::Sqlite | Self::Files => "SQLite",
+        }
+    }
+}
+
+/// Rows returned by a driver, converted to JSON values, with their column metadata
+#[derive(Debug, Default)]
+pub struct QueryRows {
+    pub columns: Vec<QueryColumn>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+/// Rows sent by a driver as they are read from the database
+pub type RowReceiver = mpsc::Receiver<Result<Vec<serde_json::Value>, AppError>>;
+
+/// Streamed query result: column metadata up front, rows as they arrive
+#[derive(Debug)]
+pub struct QueryStream {
+    pub columns: Vec<QueryColumn>,
+    pub rows: RowReceiver,
+}
+
+/// Objects discovered by schema introspection
+#[derive(Debug, Default)]
+pub struct IntrospectedSchema {
+    pub tables: Vec<TableInfo>,
+    pub views: Vec<ViewInfo>,
+    pub materialized_views: Vec<ViewInfo>,
+    pub functions: Vec<FunctionInfo>,
+    pub sequences: Vec<SequenceInfo>,
+}
+
+/// Connection to a target database
+///
+/// A driver owns its connection pool, so one instance is created per stored
+/// connection and shared between requests.
+#[async_trait]
+pub trait DatabaseDriver: Send + Sync {
+    /// Kind of database this driver talks to
+    fn kind(&self) -> DatabaseKind;
+
+    /// SQL dialect used to parse and validate queries for this database
+    fn dialect(&self) -> &'static dyn Dialect;
+
+    /// Run a trivial query to verify the connection works
+    async fn test_connection(&self) -> Result<(), AppError>;
+
+    /// Read the tables, views, functions and sequences of the schemas selected by `settings` from the database catalog
+    async fn introspect(&self, settings: &ConnectionSettings) -> Result<IntrospectedSchema, AppError>;
+
+    /// Execute an already validated SQL query
+    ///
+    /// The query runs in a read-only transaction that is rolled back afterwards,
+    /// as the connection's role when one is configured. The query can be cancelled through `query_id` while it runs, and is
+    /// cancelled if the returned future is dropped before it completes. The
+    /// database stops it once it runs longer than the connection's statement timeout.
+    async fn execute(&self, sql: &str, query_id: &str, settings: &ConnectionSettings) -> Result<QueryRows, AppError>;
+
+    /// Execute an already validated SQL query, sending rows as they are read
+    ///
+    /// Drivers without native streaming buffer the result, up to one row past the
+    /// connection's `max_rows`, and replay it.
+    /// Streaming drivers cancel the query when the row receiver is dropped.
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        query_id: &str,
+        settings: &ConnectionSettings,
+    ) -> Result<QueryStream, AppError> {
+        let result = self.execute(sql, query_id, settings).await?;
+
+        let (sender, rows) = mpsc::channel(result.rows.len().max(1));
+        for row in result.rows {
+            // Cannot fail: the channel has room for every row
+            let _ = sender.try_send(Ok(row));
+        }
+
+        Ok(QueryStream { columns: result.columns, rows })
+    }
+
+    /// Count the rows of a table exactly, as query `query_id` run with the connection's settings
+    async fn count_rows(
+        &self,
+        schema: &str,
+        table: &str,
+        query_id: &str,
+        settings: &ConnectionSettings,
+    ) -> Result<u64, AppError> {
+        let quote = |name: &str| match self.kind() {
+            DatabaseKind::MySql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        };
+        let sql = format!("SELECT COUNT(*) FROM {}.{}", quote(schema), quote(table));
+
+        let result = self.execute(&sql, query_id, settings).await?;
+        result
+            .rows
+            .first()
+            .and_then(|row| row.first())
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| AppError::InternalError(format!("COUNT(*) of {schema}.{table} returned no count")))
+    }
+
+    /// Cancel the running query `query_id`, returning whether it was found
+    async fn cancel(&self, query_id: &str) -> Result<bool, AppError>;
+
+    /// New driver with the data read again, for drivers that keep their own copy of it
+    ///
+    /// The new driver can cancel the queries still running on this one. Drivers
+    /// querying the database directly have nothing to reload and return `None`.
+    async fn reload(&self) -> Result<Option<SharedDriver>, AppError> {
+        Ok(None)
+    }
+
+    /// Close the underlying connection pool
+    async fn close(&self);
+}
+
+/// Shared handle to a connected driver
+pub type SharedDriver = Arc<dyn DatabaseDriver>;
+
+/// Connect to the database behind `url` using the matching driver
+pub async fn connect(url: &str) -> Result<SharedDriver, AppError> {
+    match DatabaseKind::from_url(url) {
+        Some(DatabaseKind::Postgres) => Ok(Arc::new(PostgresDriver::connect(url).await?)),
+        Some(DatabaseKind::MySql) => Ok(Arc::new(MySqlDriver::connect(url).await?)),
+        Some(DatabaseKind::Sqlite) => Ok(Arc::new(SqliteDriver::connect(url).await?)),
+        Some(DatabaseKind::Files) => Ok(Arc::new(FileDriver::connect(url).await?)),
+        None => Err(AppError::UnsupportedDriver(
+            "Unsupported database URL scheme".to_string(),
+        )),
+    }
+}
+
+/// Pool settings shared by the sqlx based drivers
+fn pool_options<DB: Database>() -> PoolOptions<DB> {
+    PoolOptions::new()
+        .max_connections(5) // Limit per database
+        .acquire_timeout(Duration::from_secs(30))
+        .idle_timeout(Duration::from_secs(600)) // 10 minutes
+        .max_lifetime(Duration::from_secs(3600)) // 1 hour
+}
+
+/// Open a sqlx connection pool, failing if it cannot connect within 15 seconds
+async fn connect_pool<DB: Database>(
+    kind: DatabaseKind,
+    options: <DB::Connection as sqlx::Connection>::Options,
+) -> Result<Pool<DB>, AppError> {
+    let start_time = Instant::now();
+
+    let pool = timeout(Duration::from_secs(15), pool_options::<DB>().connect_with(options))
+        .await
+        .map_err(|_| {
+            let elapsed = start_time.elapsed();
+            tracing::error!(timeout_secs = elapsed.as_secs(), "connection timeout");
+            AppError::ConnectionError(format!(
+                "Connection timeout after {} seconds. Please check if {} is running and accessible.",
+                elapsed.as_secs(),
+                kind.display_name()
+            ))
+        })?
+        .map_err(|e| {
+            let elapsed = start_time.elapsed();
+            tracing::error!(error = ?e, elapsed_secs = elapsed.as_secs_f64(), "failed to connect");
+            AppError::ConnectionError(format!(
+                "Failed to connect to {}: {}. Connection took {:.2} seconds",
+                kind.display_name(), e, elapsed.as_secs_f64()
+            ))
+        })?;
+
+    let elapsed = start_time.elapsed();
+    tracing::info!(elapsed_secs = elapsed.as_secs_f64(), "connection established");
+    Ok(pool)
+}
+
+/// Read the rows of `sql` from a pooled connection on a background task
+///
+/// The task stops as soon as the receiver is dropped, e.g. when the client disconnects,
+/// which also cancels the query.
+fn spawn_row_stream<DB>(
+    mut conn: TrackedConnection<DB>,
+    sql: String,
+    column_count: usize,
+    to_json: fn(&DB::Row, usize) -> serde_json::Value,
+) -> RowReceiver
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let (sender, receiver) = mpsc::channel(STREAM_BUFFER_ROWS);
+
+    tokio::spawn(async move {
+        let completed = {
+            let mut rows = sqlx::query(&sql).fetch(&mut *conn);
+            loop {
+                // Waiting for the next row also watches the receiver, so a query that has not
+                // produced a row yet is still cancelled when the consumer goes away
+                let row = tokio::select! {
+                    row = rows.next() => row,
+                    () = sender.closed() => {
+                        tracing::info!("query stream closed by the receiver");
+                        break false;
+                    }
+                };
+                let Some(row) = row else {
+                    break true;
+                };
+                let row = row
+                    .map(|row| (0..column_count).map(|index| to_json(&row, index)).collect())
+                    .map_err(AppError::from);
+                let failed = row.is_err();
+
+                if sender.send(row).await.is_err() {
+                    tracing::info!("query stream closed by the receiver");
+                    break false;
+                }
+                if failed {
+                    break true;
+                }
+            }
+        };
+
+        if completed {
+            conn.finish().await;
+        }
+    });
+
+    receiver
+}
+
+/// Await a driver's test query, failing if it takes longer than 5 seconds
+async fn run_test_query<T, E>(query: impl Future<Output = Result<T, E>>) -> Result<(), AppError>
+where
+    AppError: From<E>,
+    E: std::fmt::Debug,
+{
+    tracing::debug!("executing test query: SELECT 1");
+    let start_time = Instant::now();
+
+    timeout(Duration::from_secs(5), query)
+        .await
+        .map_err(|_| {
+            let elapsed = start_time.elapsed();
+            tracing::error!(timeout_secs = elapsed.as_secs(), "test query timeout");
+            AppError::ConnectionError(
+                "Test query timeout. Database may be slow or unresponsive.".to_string()
+            )
+        })?
+        .map_err(|e| {
+            tracing::error!(error = ?e, "test query failed");
+            AppError::from(e)
+        })?;
+
+    let elapsed = start_time.elapsed();
+    tracing::info!(elapsed_secs = elapsed.as_secs_f64(), "test query executed successfully");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_kind_from_url() {
+        assert_eq!(DatabaseKind::from_url("postgres://u:p@localhost/db"), Some(DatabaseKind::Postgres));
+        assert_eq!(DatabaseKind::from_url("postgresql://u:p@localhost/db"), Some(DatabaseKind::Postgres));
+        assert_eq!(DatabaseKind::from_url("mysql://u:p@localhost/db"), Some(DatabaseKind::MySql));
+        assert_eq!(DatabaseKind::from_url("sqlite:///tmp/app.db"), Some(DatabaseKind::Sqlite));
+        assert_eq!(DatabaseKind::from_url("file:///data/exports"), Some(DatabaseKind::Files));
+        assert_eq!(DatabaseKind::from_url("invalid://url"), None);
+    }
+}
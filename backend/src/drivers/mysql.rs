@@ -0,0 +1,708 @@
+use crate::drivers::{
+    connect_pool, run_test_query, spawn_row_stream, CancelQuery, DatabaseDriver, DatabaseKind, IntrospectedSchema,
+    QueryRows, QueryStream, RunningQueries, TrackedConnection, INTROSPECTION_CONCURRENCY,
+};
+use crate::error::AppError;
+use crate::models::database::{ConnectionSettings, RowCountMode};
+use crate::models::query::{LogicalType, QueryColumn};
+use crate::models::schema::{
+    CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, FunctionInfo, IndexInfo, TableInfo, TableKind, UniqueConstraintInfo,
+    ViewInfo,
+};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use sqlparser::dialect::{Dialect, MySqlDialect};
+use sqlx::mysql::{MySql, MySqlConnectOptions, MySqlConnection, MySqlPool, MySqlRow};
+use sqlx::{Column, Executor, Row, TypeInfo};
+use std::sync::Arc;
+
+/// `MySQL` / `MariaDB` driver backed by a sqlx connection pool
+pub struct MySqlDriver {
+    pool: MySqlPool,
+    running: RunningQueries,
+}
+
+impl MySqlDriver {
+    /// Create a connection pool for `url`
+    pub async fn connect(url: &str) -> Result<Self, AppError> {
+        let options = url.parse::<MySqlConnectOptions>()
+            .map_err(|e| {
+                tracing::error!(error = ?e, "failed to parse connection options");
+                AppError::from(e)
+            })?;
+
+        let pool = connect_pool(DatabaseKind::MySql, options).await?;
+        Ok(Self {
+            pool,
+            running: RunningQueries::default(),
+        })
+    }
+
+    /// Acquire a connection for query `query_id`, registering its connection id for cancellation
+    async fn acquire_tracked(
+        &self,
+        query_id: &str,
+        settings: &ConnectionSettings,
+    ) -> Result<TrackedConnection<MySql>, AppError> {
+        let mut conn = self.pool.acquire().await?;
+        let connection_id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+            .fetch_one(&mut *conn)
+            .await?;
+
+        // Cancel requests go through another pooled connection, as the query's own one is busy
+        let pool = self.pool.clone();
+        let cancel: CancelQuery = Arc::new(move || {
+            let pool = pool.clone();
+            Box::pin(async move {
+                tracing::debug!(connection_id = connection_id, "killing MySQL query");
+                // KILL does not accept placeholders; the id is a number read from the server
+                sqlx::query(&format!("KILL QUERY {connection_id}"))
+                    .execute(&pool)
+                    .await?;
+                Ok(())
+            })
+        });
+
+        let guard = self.running.register(query_id, cancel)?;
+        let mut conn = TrackedConnection::new(conn, guard);
+
+        // The query runs in a read-only transaction that is always rolled back, so a
+        // statement that slips past validation still cannot modify data
+        conn.prepare_session("START TRANSACTION READ ONLY", "ROLLBACK").await?;
+        if let Some(role) = &settings.role {
+            conn.prepare_session(&format!("SET ROLE {}", quote_identifier(role)), "SET ROLE DEFAULT")
+                .await?;
+        }
+
+        // MySQL limits SELECTs with max_execution_time (ms), MariaDB with max_statement_time (s)
+        let timeout_ms = settings.statement_timeout_ms;
+        let mysql_timeout = conn
+            .prepare_session(
+                &format!("SET SESSION max_execution_time = {timeout_ms}"),
+                "SET SESSION max_execution_time = DEFAULT",
+            )
+            .await;
+        if let Err(e) = mysql_timeout {
+            tracing::debug!(error = ?e, "max_execution_time not supported, using max_statement_time");
+            conn.prepare_session(
+                &format!("SET SESSION max_statement_time = {}.{:03}", timeout_ms / 1000, timeout_ms % 1000),
+                "SET SESSION max_statement_time = DEFAULT",
+            )
+            .await?;
+        }
+
+        Ok(conn)
+    }
+
+    /// List the tables and views of the schemas selected by `settings`
+    async fn fetch_objects(&self, settings: &ConnectionSettings) -> Result<Vec<SchemaObject>, AppError> {
+        // information_schema columns are binary strings on MySQL 8, so cast them to text
+        let sql = format!(
+            "SELECT CAST(t.table_schema AS CHAR), CAST(t.table_name AS CHAR), CAST(t.table_type AS CHAR),
+                    NULLIF(CAST(t.table_comment AS CHAR), ''), CAST(v.view_definition AS CHAR), t.table_rows
+             FROM information_schema.tables t
+             LEFT JOIN information_schema.views v
+                 ON v.table_schema = t.table_schema AND v.table_name = t.table_name
+             WHERE t.table_type IN ('BASE TABLE', 'VIEW'){}
+             ORDER BY t.table_schema, t.table_name",
+            schema_filter("t.table_schema", settings)
+        );
+
+        let mut query = sqlx::query(&sql);
+        for schema in settings.include_schemas.iter().chain(&settings.exclude_schemas) {
+            query = query.bind(schema);
+        }
+
+        let objects = query
+            .map(|row: MySqlRow| SchemaObject {
+                schema: row.get(0),
+                name: row.get(1),
+                table_type: row.get(2),
+                comment: row.get(3),
+                definition: row.get(4),
+                estimated_rows: row.get(5),
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(objects)
+    }
+
+    /// Fetch the stored functions of the schemas selected by `settings`
+    async fn fetch_functions(&self, settings: &ConnectionSettings) -> Result<Vec<FunctionInfo>, AppError> {
+        let sql = format!(
+            "SELECT CAST(r.routine_schema AS CHAR), CAST(r.routine_name AS CHAR),
+                    CAST(COALESCE((
+                        SELECT GROUP_CONCAT(CONCAT(p.parameter_name, ' ', p.dtd_identifier)
+                                            ORDER BY p.ordinal_position SEPARATOR ', ')
+                        FROM information_schema.parameters p
+                        WHERE p.specific_schema = r.routine_schema
+                            AND p.specific_name = r.specific_name
+                            AND p.ordinal_position > 0
+                    ), '') AS CHAR),
+                    CAST(r.dtd_identifier AS CHAR), NULLIF(CAST(r.routine_comment AS CHAR), '')
+             FROM information_schema.routines r
+             WHERE r.routine_type = 'FUNCTION'{}
+             ORDER BY r.routine_schema, r.routine_name",
+            schema_filter("r.routine_schema", settings)
+        );
+
+        let mut query = sqlx::query(&sql);
+        for schema in settings.include_schemas.iter().chain(&settings.exclude_schemas) {
+            query = query.bind(schema);
+        }
+
+        let functions = query
+            .map(|row: MySqlRow| FunctionInfo {
+                schema: row.get(0),
+                name: row.get(1),
+                arguments: row.get(2),
+                return_type: row.get(3),
+                // MySQL functions return a single value
+                returns_set: false,
+                description: row.get(4),
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(functions)
+    }
+
+    /// Fetch information for a single table
+    ///
+    /// Rows are counted unless `estimated_rows` is given.
+    async fn fetch_table_info(
+        &self,
+        schema: &str,
+        table_name: &str,
+        description: Option<String>,
+        estimated_rows: Option<u64>,
+    ) -> Result<TableInfo, AppError> {
+        let columns = self.fetch_columns(schema, table_name).await?;
+
+        // Get primary key columns
+        let primary_key: Vec<String> = sqlx::query_scalar(
+            "SELECT CAST(column_name AS CHAR)
+             FROM information_schema.key_column_usage
+             WHERE table_schema = ?
+                 AND table_name = ?
+                 AND constraint_name = 'PRIMARY'
+             ORDER BY ordinal_position"
+        )
+        .bind(schema)
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Get row count for this table
+        let row_count_estimated = estimated_rows.is_some();
+        let row_count = match estimated_rows {
+            Some(rows) => Some(rows),
+            None => self.fetch_table_row_count(schema, table_name).await,
+        };
+
+        Ok(TableInfo {
+            schema: schema.to_string(),
+            name: table_name.to_string(),
+            // Partitions of MySQL tables are not tables of their own
+            kind: TableKind::Table,
+            description,
+            columns,
+            primary_key: if primary_key.is_empty() { None } else { Some(primary_key) },
+            row_count,
+            row_count_estimated,
+            statistics: None,
+            foreign_keys: self.fetch_foreign_keys(schema, table_name).await?,
+            indexes: self.fetch_indexes(schema, table_name).await?,
+            unique_constraints: self.fetch_unique_constraints(schema, table_name).await?,
+            check_constraints: self.fetch_check_constraints(schema, table_name).await,
+            partition_key: None,
+            partition_bound: None,
+            partitions: Vec::new(),
+        })
+    }
+
+    /// Fetch the foreign keys of a table, with their columns in key order
+    async fn fetch_foreign_keys(&self, schema: &str, table_name: &str) -> Result<Vec<ForeignKeyInfo>, AppError> {
+        // One row per column of each key
+        let rows: Vec<(String, String, String, String, String, String)> = sqlx::query_as(
+            "SELECT CAST(k.constraint_name AS CHAR), CAST(k.column_name AS CHAR),
+                    CAST(k.referenced_table_schema AS CHAR), CAST(k.referenced_table_name AS CHAR),
+                    CAST(k.referenced_column_name AS CHAR), CAST(r.delete_rule AS CHAR)
+             FROM information_schema.key_column_usage k
+             JOIN information_schema.referential_constraints r
+                 ON r.constraint_schema = k.constraint_schema
+                 AND r.constraint_name = k.constraint_name
+             WHERE k.table_schema = ? AND k.table_name = ?
+             ORDER BY k.constraint_name, k.ordinal_position"
+        )
+        .bind(schema)
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut foreign_keys: Vec<ForeignKeyInfo> = Vec::new();
+        for (name, column, referenced_schema, referenced_table, referenced_column, on_delete) in rows {
+            match foreign_keys.last_mut() {
+                Some(foreign_key) if foreign_key.name == name => {
+                    foreign_key.columns.push(column);
+                    foreign_key.referenced_columns.push(referenced_column);
+                }
+                _ => foreign_keys.push(ForeignKeyInfo {
+                    name,
+                    columns: vec![column],
+                    referenced_schema,
+                    referenced_table,
+                    referenced_columns: vec![referenced_column],
+                    on_delete,
+                }),
+            }
+        }
+
+        Ok(foreign_keys)
+    }
+
+    /// Fetch the indexes of a table, including its primary key
+    async fn fetch_indexes(&self, schema: &str, table_name: &str) -> Result<Vec<IndexInfo>, AppError> {
+        // One row per column of each index; functional key parts have no column name
+        let rows: Vec<(String, Option<String>, i64, String)> = sqlx::query_as(
+            "SELECT CAST(index_name AS CHAR), CAST(column_name AS CHAR),
+                    CAST(non_unique AS SIGNED), CAST(index_type AS CHAR)
+             FROM information_schema.statistics
+             WHERE table_schema = ? AND table_name = ?
+             ORDER BY index_name, seq_in_index"
+        )
+        .bind(schema)
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut indexes: Vec<IndexInfo> = Vec::new();
+        for (name, column, non_unique, method) in rows {
+            let column = column.unwrap_or_else(|| "(expression)".to_string());
+            match indexes.last_mut() {
+                Some(index) if index.name == name => index.columns.push(column),
+                _ => indexes.push(IndexInfo {
+                    name,
+                    columns: vec![column],
+                    unique: non_unique == 0,
+                    method: method.to_lowercase(),
+                    // MySQL has no partial indexes
+                    predicate: None,
+                }),
+            }
+        }
+
+        Ok(indexes)
+    }
+
+    /// Fetch the unique constraints of a table
+    async fn fetch_unique_constraints(
+        &self,
+        schema: &str,
+        table_name: &str,
+    ) -> Result<Vec<UniqueConstraintInfo>, AppError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT CAST(tc.constraint_name AS CHAR), CAST(k.column_name AS CHAR)
+             FROM information_schema.table_constraints tc
+             JOIN information_schema.key_column_usage k
+                 ON k.constraint_schema = tc.constraint_schema
+                 AND k.constraint_name = tc.constraint_name
+                 AND k.table_name = tc.table_name
+             WHERE tc.table_schema = ? AND tc.table_name = ? AND tc.constraint_type = 'UNIQUE'
+             ORDER BY tc.constraint_name, k.ordinal_position"
+        )
+        .bind(schema)
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut constraints: Vec<UniqueConstraintInfo> = Vec::new();
+        for (name, column) in rows {
+            match constraints.last_mut() {
+                Some(constraint) if constraint.name == name => constraint.columns.push(column),
+                _ => constraints.push(UniqueConstraintInfo { name, columns: vec![column] }),
+            }
+        }
+
+        Ok(constraints)
+    }
+
+    /// Fetch the check constraints of a table
+    ///
+    /// Servers without `information_schema.check_constraints` (before `MySQL` 8.0.16) have none.
+    async fn fetch_check_constraints(&self, schema: &str, table_name: &str) -> Vec<CheckConstraintInfo> {
+        let result = sqlx::query_as::<_, (String, String)>(
+            "SELECT CAST(tc.constraint_name AS CHAR), CAST(cc.check_clause AS CHAR)
+             FROM information_schema.table_constraints tc
+             JOIN information_schema.check_constraints cc
+                 ON cc.constraint_schema = tc.constraint_schema
+                 AND cc.constraint_name = tc.constraint_name
+             WHERE tc.table_schema = ? AND tc.table_name = ? AND tc.constraint_type = 'CHECK'
+             ORDER BY tc.constraint_name"
+        )
+        .bind(schema)
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await;
+
+        match result {
+            Ok(rows) => rows
+                .into_iter()
+                .map(|(name, expression)| CheckConstraintInfo { name, expression })
+                .collect(),
+            Err(e) => {
+                tracing::warn!(
+                    table = %table_name,
+                    error = ?e,
+                    "failed to get check constraints"
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    /// Fetch row count for a table
+    async fn fetch_table_row_count(&self, schema: &str, table_name: &str) -> Option<u64> {
+        match sqlx::query_scalar::<_, i64>(
+            &format!("SELECT COUNT(*) FROM {}.{}", quote_identifier(schema), quote_identifier(table_name))
+        )
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(count) => u64::try_from(count).ok(),
+            Err(e) => {
+                tracing::warn!(
+                    table = %table_name,
+                    error = ?e,
+                    "failed to get row count"
+                );
+                None
+            }
+        }
+    }
+
+    /// Fetch column definitions for a table or view
+    async fn fetch_columns(&self, schema: &str, table_name: &str) -> Result<Vec<ColumnInfo>, AppError> {
+        let columns = sqlx::query(
+            "SELECT CAST(column_name AS CHAR), CAST(data_type AS CHAR),
+                    CAST(is_nullable AS CHAR), CAST(column_default AS CHAR),
+                    NULLIF(CAST(column_comment AS CHAR), ''), CAST(column_type AS CHAR)
+             FROM information_schema.columns
+             WHERE table_schema = ? AND table_name = ?
+             ORDER BY ordinal_position"
+        )
+        .bind(schema)
+        .bind(table_name)
+        .map(|row: MySqlRow| {
+            let is_nullable: String = row.get(2);
+            let column_type: String = row.get(5);
+            ColumnInfo {
+                name: row.get(0),
+                data_type: row.get(1),
+                nullable: is_nullable == "YES",
+                default_value: row.get(3),
+                description: row.get(4),
+                // MySQL has no domains
+                base_type: None,
+                enum_values: enum_values(&column_type),
+            }
+        })
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(columns)
+    }
+}
+
+#[async_trait]
+impl DatabaseDriver for MySqlDriver {
+    fn kind(&self) -> DatabaseKind {
+        DatabaseKind::MySql
+    }
+
+    fn dialect(&self) -> &'static dyn Dialect {
+        &MySqlDialect {}
+    }
+
+    async fn test_connection(&self) -> Result<(), AppError> {
+        run_test_query(sqlx::query("SELECT 1").execute(&self.pool)).await
+    }
+
+    async fn introspect(&self, settings: &ConnectionSettings) -> Result<IntrospectedSchema, AppError> {
+        let (views, tables): (Vec<SchemaObject>, Vec<SchemaObject>) = self
+            .fetch_objects(settings)
+            .await?
+            .into_iter()
+            .partition(|object| object.table_type == "VIEW");
+
+        // Tables are read a few at a time, each takes several queries
+        let tables = stream::iter(tables)
+            .map(|object| async move {
+                // table_rows is InnoDB's estimate, refreshed by ANALYZE TABLE
+                let estimated_rows = object.estimated_rows.filter(|_| settings.row_counts == RowCountMode::Estimated);
+                self.fetch_table_info(&object.schema, &object.name, object.comment, estimated_rows).await
+            })
+            .buffered(INTROSPECTION_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        // Views cannot have comments; their comment column always reads VIEW
+        let views = stream::iter(views)
+            .map(|object| async move {
+                Ok::<_, AppError>(ViewInfo {
+                    columns: self.fetch_columns(&object.schema, &object.name).await?,
+                    schema: object.schema,
+                    name: object.name,
+                    description: None,
+                    definition: object.definition,
+                })
+            })
+            .buffered(INTROSPECTION_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        // MySQL has no materialized views or sequences
+        Ok(IntrospectedSchema {
+            tables,
+            views,
+            functions: self.fetch_functions(settings).await?,
+            ..IntrospectedSchema::default()
+        })
+    }
+
+    async fn execute(&self, sql: &str, query_id: &str, settings: &ConnectionSettings) -> Result<QueryRows, AppError> {
+        // Describe and run the statement on one connection, so it is only prepared once
+        let mut conn = self.acquire_tracked(query_id, settings).await?;
+        let result = async {
+            let columns = describe_columns(&mut conn, sql).await?;
+            let rows = sqlx::query(sql).fetch_all(&mut *conn).await?;
+            Ok::<_, AppError>((columns, rows))
+        }
+        .await;
+        conn.finish().await;
+        let (columns, rows) = result?;
+
+        // Convert rows to JSON values
+        let rows = rows
+            .iter()
+            .map(|row| (0..columns.len()).map(|index| get_value_as_json(row, index)).collect())
+            .collect();
+
+        Ok(QueryRows { columns, rows })
+    }
+
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        query_id: &str,
+        settings: &ConnectionSettings,
+    ) -> Result<QueryStream, AppError> {
+        let mut conn = self.acquire_tracked(query_id, settings).await?;
+        let columns = match describe_columns(&mut conn, sql).await {
+            Ok(columns) => columns,
+            Err(e) => {
+                conn.finish().await;
+                return Err(e);
+            }
+        };
+        let rows = spawn_row_stream(conn, sql.to_string(), columns.len(), get_value_as_json);
+
+        Ok(QueryStream { columns, rows })
+    }
+
+    async fn cancel(&self, query_id: &str) -> Result<bool, AppError> {
+        self.running.cancel(query_id).await
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+/// Describe the result columns of a statement
+///
+/// Column metadata comes from the prepared statement, so it is known even without rows.
+async fn describe_columns(conn: &mut MySqlConnection, sql: &str) -> Result<Vec<QueryColumn>, AppError> {
+    let describe = conn.describe(sql).await?;
+
+    Ok(describe
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let db_type = column.type_info().name();
+            QueryColumn {
+                name: column.name().to_string(),
+                db_type: db_type.to_string(),
+                logical_type: logical_type(db_type),
+                nullable: describe.nullable(index),
+            }
+        })
+        .collect())
+}
+
+/// Convert a column value to JSON based on the column's `MySQL` type
+fn get_value_as_json(row: &MySqlRow, index: usize) -> serde_json::Value {
+    use serde_json::Value;
+
+    let type_name = row.column(index).type_info().name();
+    let value = match type_name {
+        "BOOLEAN" => row.try_get::<Option<bool>, _>(index).map(|v| v.map(Value::Bool)),
+        "TINYINT" | "SMALLINT" | "INT" | "MEDIUMINT" | "BIGINT" => {
+            row.try_get::<Option<i64>, _>(index).map(|v| v.map(Value::from))
+        }
+        "TINYINT UNSIGNED" | "SMALLINT UNSIGNED" | "INT UNSIGNED" | "MEDIUMINT UNSIGNED"
+        | "BIGINT UNSIGNED" | "YEAR" | "BIT" => {
+            // YEAR and BIT are not always flagged UNSIGNED, so skip the compatibility check
+            row.try_get_unchecked::<Option<u64>, _>(index).map(|v| v.map(Value::from))
+        }
+        "FLOAT" => row.try_get::<Option<f32>, _>(index).map(|v| v.map(|f| Value::from(f64::from(f)))),
+        "DOUBLE" => row.try_get::<Option<f64>, _>(index).map(|v| v.map(Value::from)),
+        // DECIMAL is transferred as text, keep it as a string to avoid losing precision
+        "DECIMAL" => row.try_get_unchecked::<Option<String>, _>(index).map(|v| v.map(Value::String)),
+        "DATE" => row
+            .try_get::<Option<chrono::NaiveDate>, _>(index)
+            .map(|v| v.map(|d| Value::String(d.to_string()))),
+        "TIME" => row
+            .try_get::<Option<chrono::NaiveTime>, _>(index)
+            .map(|v| v.map(|t| Value::String(t.to_string()))),
+        "DATETIME" => row
+            .try_get::<Option<chrono::NaiveDateTime>, _>(index)
+            .map(|v| v.map(|dt| Value::String(dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string()))),
+        "TIMESTAMP" => row
+            .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(index)
+            .map(|v| v.map(|dt| Value::String(dt.to_rfc3339()))),
+        "JSON" => row.try_get::<Option<Value>, _>(index),
+        "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" => row
+            .try_get::<Option<Vec<u8>>, _>(index)
+            .map(|v| v.map(|bytes| Value::String(BASE64.encode(bytes)))),
+        _ => row.try_get::<Option<String>, _>(index).map(|v| v.map(Value::String)),
+    };
+
+    value.unwrap_or_else(|e| {
+        tracing::warn!(column_type = %type_name, error = ?e, "failed to decode MySQL value");
+        None
+    })
+    .unwrap_or(Value::Null)
+}
+
+/// Logical type of a `MySQL` type name, as reported by sqlx
+fn logical_type(type_name: &str) -> LogicalType {
+    match type_name {
+        "BOOLEAN" => LogicalType::Boolean,
+        "TINYINT" | "SMALLINT" | "INT" | "MEDIUMINT" | "BIGINT" | "TINYINT UNSIGNED"
+        | "SMALLINT UNSIGNED" | "INT UNSIGNED" | "MEDIUMINT UNSIGNED" | "BIGINT UNSIGNED"
+        | "YEAR" | "BIT" => LogicalType::Integer,
+        "FLOAT" | "DOUBLE" | "DECIMAL" => LogicalType::Decimal,
+        "DATE" | "TIME" | "DATETIME" | "TIMESTAMP" => LogicalType::Timestamp,
+        "JSON" => LogicalType::Json,
+        "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" => LogicalType::Binary,
+        _ => LogicalType::Text,
+    }
+}
+
+/// Quote a `MySQL` identifier with backticks, escaping embedded backticks
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// Table or view listed by introspection
+struct SchemaObject {
+    schema: String,
+    name: String,
+    table_type: String,
+    comment: Option<String>,
+    definition: Option<String>,
+    /// Row count from the table statistics
+    estimated_rows: Option<u64>,
+}
+
+/// SQL conditions on `column` selecting the schemas of `settings`, or the connection's database
+///
+/// The included schemas are bound first, then the excluded ones.
+fn schema_filter(column: &str, settings: &ConnectionSettings) -> String {
+    let placeholders = |count: usize| vec!["?"; count].join(", ");
+
+    let mut filter = if settings.include_schemas.is_empty() {
+        format!(" AND {column} = DATABASE()")
+    } else {
+        format!(" AND {column} IN ({})", placeholders(settings.include_schemas.len()))
+    };
+    if !settings.exclude_schemas.is_empty() {
+        filter.push_str(&format!(" AND {column} NOT IN ({})", placeholders(settings.exclude_schemas.len())));
+    }
+    filter
+}
+
+/// Labels of an `enum('a','b')` column type, with their quotes unescaped
+fn enum_values(column_type: &str) -> Option<Vec<String>> {
+    let list = column_type.strip_prefix("enum(")?.strip_suffix(')')?;
+    let mut chars = list.chars().peekable();
+    let mut values = Vec::new();
+    loop {
+        if chars.next()? != '\'' {
+            return None;
+        }
+        let mut value = String::new();
+        loop {
+            match chars.next()? {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    value.push('\'');
+                }
+                '\'' => break,
+                c => value.push(c),
+            }
+        }
+        values.push(value);
+
+        match chars.next() {
+            Some(',') => {}
+            None => return Some(values),
+            Some(_) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_filter() {
+        let mut settings = ConnectionSettings::default();
+        assert_eq!(schema_filter("t.table_schema", &settings), " AND t.table_schema = DATABASE()");
+
+        settings.include_schemas = vec!["sales".to_string(), "billing".to_string()];
+        settings.exclude_schemas = vec!["billing".to_string()];
+        assert_eq!(
+            schema_filter("t.table_schema", &settings),
+            " AND t.table_schema IN (?, ?) AND t.table_schema NOT IN (?)"
+        );
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("users"), "`users`");
+        assert_eq!(quote_identifier("odd`name"), "`odd``name`");
+    }
+
+    #[test]
+    fn test_enum_values() {
+        assert_eq!(enum_values("enum('open','paid')"), Some(vec!["open".to_string(), "paid".to_string()]));
+        assert_eq!(enum_values("enum('it''s','a,b')"), Some(vec!["it's".to_string(), "a,b".to_string()]));
+        assert_eq!(enum_values("varchar(20)"), None);
+    }
+
+    #[test]
+    fn test_logical_type() {
+        assert_eq!(logical_type("BIGINT UNSIGNED"), LogicalType::Integer);
+        assert_eq!(logical_type("DECIMAL"), LogicalType::Decimal);
+        assert_eq!(logical_type("DATETIME"), LogicalType::Timestamp);
+        assert_eq!(logical_type("VARBINARY"), LogicalType::Binary);
+        assert_eq!(logical_type("VARCHAR"), LogicalType::Text);
+    }
+}
@@ -0,0 +1,863 @@
+use crate::drivers::postgres_values;
+use crate::drivers::{
+    connect_pool, run_test_query, spawn_row_stream, CancelQuery, DatabaseDriver, DatabaseKind, IntrospectedSchema,
+    QueryRows, QueryStream, RunningQueries, TrackedConnection, INTROSPECTION_CONCURRENCY,
+};
+use crate::error::AppError;
+use crate::models::database::{ConnectionSettings, RowCountMode};
+use crate::models::query::{LogicalType, QueryColumn};
+use crate::models::schema::{
+    CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, FunctionInfo, IndexInfo, SequenceInfo, TableInfo, TableKind,
+    TableStatistics, UniqueConstraintInfo, ViewInfo,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use sqlparser::dialect::{Dialect, PostgreSqlDialect};
+use sqlx::postgres::types::Oid;
+use sqlx::postgres::{
+    PgConnectOptions, PgConnection, PgPool, PgRow, PgTypeInfo, PgTypeKind, PgValueFormat, Postgres,
+};
+use sqlx::{Column, Executor, Row, TypeInfo, ValueRef};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// `PostgreSQL` driver backed by a sqlx connection pool
+pub struct PostgresDriver {
+    pool: PgPool,
+    running: RunningQueries,
+}
+
+impl PostgresDriver {
+    /// Create a connection pool for `url`
+    pub async fn connect(url: &str) -> Result<Self, AppError> {
+        let options = url.parse::<PgConnectOptions>()
+            .map_err(|e| {
+                tracing::error!(error = ?e, "failed to parse connection options");
+                AppError::from(e)
+            })?;
+
+        let pool = connect_pool(DatabaseKind::Postgres, options).await?;
+        Ok(Self {
+            pool,
+            running: RunningQueries::default(),
+        })
+    }
+
+    /// Acquire a connection for query `query_id`, registering its backend PID for cancellation
+    ///
+    /// The query runs in a transaction, so settings made with `SET LOCAL` end with it.
+    async fn acquire_tracked(
+        &self,
+        query_id: &str,
+        settings: &ConnectionSettings,
+    ) -> Result<TrackedConnection<Postgres>, AppError> {
+        let mut conn = self.pool.acquire().await?;
+        let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut *conn)
+            .await?;
+
+        // Cancel requests go through another pooled connection, as the query's own one is busy
+        let pool = self.pool.clone();
+        let cancel: CancelQuery = Arc::new(move || {
+            let pool = pool.clone();
+            Box::pin(async move {
+                tracing::debug!(pid = pid, "cancelling PostgreSQL backend query");
+                sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(pid)
+                    .execute(&pool)
+                    .await?;
+                Ok(())
+            })
+        });
+
+        let guard = self.running.register(query_id, cancel)?;
+        let mut conn = TrackedConnection::new(conn, guard);
+
+        // The query runs in a read-only transaction that is always rolled back, so a
+        // statement that slips past validation still cannot modify data
+        conn.prepare_session("BEGIN READ ONLY", "ROLLBACK").await?;
+        sqlx::query(&format!("SET LOCAL statement_timeout = {}", settings.statement_timeout_ms))
+            .execute(&mut *conn)
+            .await?;
+        if let Some(role) = &settings.role {
+            sqlx::query(&format!("SET LOCAL ROLE \"{}\"", role.replace('"', "\"\"")))
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(conn)
+    }
+
+    /// List the tables, views and materialized views of the schemas selected by `settings`
+    async fn fetch_relations(&self, settings: &ConnectionSettings) -> Result<Vec<Relation>, AppError> {
+        // Relations the user has no privilege on are left out, like in information_schema.
+        // reltuples is -1 until a table is first vacuumed or analyzed, the statistics
+        // collector's live row count stands in for it until then.
+        let sql = format!(
+            "SELECT n.nspname::text, c.relname::text, c.relkind::text,
+                    obj_description(c.oid, 'pg_class'),
+                    CASE WHEN c.relkind IN ('v', 'm') THEN pg_get_viewdef(c.oid, true) END,
+                    CASE WHEN c.relkind = 'p' THEN pg_get_partkeydef(c.oid) END,
+                    CASE WHEN c.relispartition THEN pg_get_expr(c.relpartbound, c.oid) END,
+                    pn.nspname || '.' || p.relname,
+                    CASE WHEN c.reltuples >= 0 THEN c.reltuples::bigint ELSE COALESCE(s.n_live_tup, 0) END,
+                    pg_total_relation_size(c.oid), pg_relation_size(c.oid), pg_indexes_size(c.oid),
+                    COALESCE(pg_total_relation_size(NULLIF(c.reltoastrelid, 0)), 0),
+                    s.n_dead_tup, s.last_vacuum, s.last_autovacuum, s.last_analyze, s.last_autoanalyze,
+                    c.oid
+             FROM pg_class c
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             LEFT JOIN pg_inherits i ON c.relispartition AND i.inhrelid = c.oid
+             LEFT JOIN pg_class p ON p.oid = i.inhparent
+             LEFT JOIN pg_namespace pn ON pn.oid = p.relnamespace
+             LEFT JOIN pg_stat_user_tables s ON s.relid = c.oid
+             WHERE c.relkind IN ('r', 'p', 'f', 'v', 'm')
+                 AND {}
+                 AND (pg_has_role(c.relowner, 'USAGE')
+                     OR has_table_privilege(c.oid, 'SELECT, INSERT, UPDATE, DELETE, TRUNCATE, REFERENCES, TRIGGER')
+                     OR has_any_column_privilege(c.oid, 'SELECT, INSERT, UPDATE, REFERENCES'))
+             ORDER BY n.nspname, c.relname",
+            schema_filter("n.nspname")
+        );
+
+        let relations = sqlx::query(&sql)
+            .bind(&settings.include_schemas)
+            .bind(&settings.exclude_schemas)
+            .map(|row: PgRow| Relation {
+                oid: row.get(18),
+                schema: row.get(0),
+                name: row.get(1),
+                kind: row.get(2),
+                description: row.get(3),
+                definition: row.get(4),
+                partition_key: row.get(5),
+                partition_bound: row.get(6),
+                parent: row.get(7),
+                estimated_rows: row.get(8),
+                statistics: TableStatistics {
+                    total_bytes: to_bytes(row.get(9)),
+                    table_bytes: to_bytes(row.get(10)),
+                    index_bytes: to_bytes(row.get(11)),
+                    toast_bytes: to_bytes(row.get(12)),
+                    dead_tuples: row.get::<Option<i64>, _>(13).and_then(|count| u64::try_from(count).ok()),
+                    last_vacuum: to_timestamp(row.get(14)),
+                    last_autovacuum: to_timestamp(row.get(15)),
+                    last_analyze: to_timestamp(row.get(16)),
+                    last_autoanalyze: to_timestamp(row.get(17)),
+                },
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(relations)
+    }
+
+    /// Build the information of a table from its relation and the catalog rows read for it
+    fn table_info(
+        relation: Relation,
+        columns: Vec<ColumnInfo>,
+        constraints: TableConstraints,
+        indexes: Vec<IndexInfo>,
+        row_count: Option<u64>,
+        row_counts: RowCountMode,
+    ) -> TableInfo {
+        let kind = match relation.kind.as_str() {
+            "p" => TableKind::Partitioned,
+            "f" => TableKind::Foreign,
+            _ => TableKind::Table,
+        };
+
+        // Foreign tables are not counted, counting them would read them from their server.
+        // Partitioned tables have no rows of their own, their estimate is summed from their partitions.
+        let row_count_estimated = row_counts == RowCountMode::Estimated && kind != TableKind::Foreign;
+        let row_count = if row_count_estimated && kind == TableKind::Table {
+            u64::try_from(relation.estimated_rows).ok()
+        } else {
+            row_count
+        };
+
+        TableInfo {
+            schema: relation.schema,
+            name: relation.name,
+            kind,
+            columns,
+            primary_key: if constraints.primary_key.is_empty() { None } else { Some(constraints.primary_key) },
+            row_count,
+            row_count_estimated,
+            // Partitioned and foreign tables store no data of their own
+            statistics: (kind == TableKind::Table).then_some(relation.statistics),
+            foreign_keys: constraints.foreign_keys,
+            indexes,
+            unique_constraints: constraints.unique_constraints,
+            check_constraints: constraints.check_constraints,
+            description: relation.description,
+            partition_key: relation.partition_key,
+            partition_bound: relation.partition_bound,
+            partitions: Vec::new(),
+        }
+    }
+
+    /// Fetch the primary keys, foreign keys, unique and check constraints of the relations `oids`
+    ///
+    /// Key columns are listed in key order.
+    async fn fetch_constraints(&self, oids: &[Oid]) -> Result<HashMap<Oid, TableConstraints>, AppError> {
+        let rows = sqlx::query(
+            "SELECT c.conrelid, c.contype::text, c.conname::text,
+                    ARRAY(SELECT a.attname::text
+                          FROM unnest(c.conkey) WITH ORDINALITY AS k(attnum, ord)
+                          JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+                          ORDER BY k.ord),
+                    rn.nspname::text,
+                    r.relname::text,
+                    ARRAY(SELECT a.attname::text
+                          FROM unnest(c.confkey) WITH ORDINALITY AS k(attnum, ord)
+                          JOIN pg_attribute a ON a.attrelid = c.confrelid AND a.attnum = k.attnum
+                          ORDER BY k.ord),
+                    CASE c.confdeltype
+                        WHEN 'r' THEN 'RESTRICT'
+                        WHEN 'c' THEN 'CASCADE'
+                        WHEN 'n' THEN 'SET NULL'
+                        WHEN 'd' THEN 'SET DEFAULT'
+                        ELSE 'NO ACTION'
+                    END,
+                    pg_get_expr(c.conbin, c.conrelid, true)
+             FROM pg_constraint c
+             LEFT JOIN pg_class r ON r.oid = c.confrelid
+             LEFT JOIN pg_namespace rn ON rn.oid = r.relnamespace
+             WHERE c.conrelid = ANY($1) AND c.contype IN ('p', 'f', 'u', 'c')
+             ORDER BY c.conrelid, c.conname"
+        )
+        .bind(oids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut constraints: HashMap<Oid, TableConstraints> = HashMap::new();
+        for row in rows {
+            let table = constraints.entry(row.get(0)).or_default();
+            let name: String = row.get(2);
+            let columns: Vec<String> = row.get(3);
+            match row.get::<&str, _>(1) {
+                "p" => table.primary_key = columns,
+                "f" => table.foreign_keys.push(ForeignKeyInfo {
+                    name,
+                    columns,
+                    referenced_schema: row.get(4),
+                    referenced_table: row.get(5),
+                    referenced_columns: row.get(6),
+                    on_delete: row.get(7),
+                }),
+                "u" => table.unique_constraints.push(UniqueConstraintInfo { name, columns }),
+                _ => table.check_constraints.push(CheckConstraintInfo { name, expression: row.get(8) }),
+            }
+        }
+
+        Ok(constraints)
+    }
+
+    /// Fetch the indexes of the relations `oids`, including those backing their constraints
+    async fn fetch_indexes(&self, oids: &[Oid]) -> Result<HashMap<Oid, Vec<IndexInfo>>, AppError> {
+        let rows = sqlx::query(
+            "SELECT x.indrelid,
+                    i.relname::text,
+                    ARRAY(SELECT pg_get_indexdef(x.indexrelid, k, true)
+                          FROM generate_series(1, x.indnkeyatts) AS k
+                          ORDER BY k),
+                    x.indisunique,
+                    am.amname::text,
+                    pg_get_expr(x.indpred, x.indrelid, true)
+             FROM pg_index x
+             JOIN pg_class i ON i.oid = x.indexrelid
+             JOIN pg_am am ON am.oid = i.relam
+             WHERE x.indrelid = ANY($1)
+             ORDER BY x.indrelid, i.relname"
+        )
+        .bind(oids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut indexes: HashMap<Oid, Vec<IndexInfo>> = HashMap::new();
+        for row in rows {
+            indexes.entry(row.get(0)).or_default().push(IndexInfo {
+                name: row.get(1),
+                columns: row.get(2),
+                unique: row.get(3),
+                method: row.get(4),
+                predicate: row.get(5),
+            });
+        }
+
+        Ok(indexes)
+    }
+
+    /// Count the rows of the tables and partitioned tables among `relations`, a few at a time
+    async fn fetch_row_counts(&self, relations: &[Relation]) -> HashMap<Oid, u64> {
+        let tables: Vec<(Oid, String, String)> = relations
+            .iter()
+            .filter(|relation| relation.kind == "r" || relation.kind == "p")
+            .map(|relation| (relation.oid, relation.schema.clone(), relation.name.clone()))
+            .collect();
+
+        let counts: Vec<(Oid, Option<u64>)> = stream::iter(tables)
+            .map(|(oid, schema, name)| async move { (oid, self.fetch_table_row_count(&schema, &name).await) })
+            .buffer_unordered(INTROSPECTION_CONCURRENCY)
+            .collect()
+            .await;
+
+        counts
+            .into_iter()
+            .filter_map(|(oid, count)| count.map(|count| (oid, count)))
+            .collect()
+    }
+
+    /// Fetch row count for a table (with validation and error handling)
+    async fn fetch_table_row_count(&self, schema: &str, table_name: &str) -> Option<u64> {
+        // Validate table name to prevent SQL injection
+        if !is_valid_identifier(schema) || !is_valid_identifier(table_name) {
+            tracing::warn!(schema = %schema, table = %table_name, "invalid table name, skipping row count");
+            return None;
+        }
+
+        match sqlx::query_scalar::<_, i64>(
+            &format!("SELECT COUNT(*) FROM \"{schema}\".\"{table_name}\"")
+        )
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(count) => {
+                #[allow(clippy::cast_sign_loss)]
+                if count >= 0 {
+                    Some(count as u64)
+                } else {
+                    tracing::warn!(table = %table_name, count = count, "negative row count");
+                    None
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    table = %table_name,
+                    error = ?e,
+                    "failed to get row count"
+                );
+                None
+            }
+        }
+    }
+
+    /// Fetch the column definitions of the relations `oids`
+    ///
+    /// Enum and domain columns report the name of their type; domains over enums get its labels too.
+    async fn fetch_columns(&self, oids: &[Oid]) -> Result<HashMap<Oid, Vec<ColumnInfo>>, AppError> {
+        let rows = sqlx::query(
+            "SELECT a.attname::text,
+                    format_type(a.atttypid, a.atttypmod),
+                    NOT a.attnotnull,
+                    pg_get_expr(d.adbin, d.adrelid),
+                    col_description(a.attrelid, a.attnum),
+                    CASE WHEN t.typtype = 'd' THEN format_type(t.typbasetype, t.typtypmod) END,
+                    ARRAY(SELECT e.enumlabel::text
+                          FROM pg_enum e
+                          WHERE e.enumtypid = CASE WHEN t.typtype = 'd' THEN t.typbasetype ELSE t.oid END
+                          ORDER BY e.enumsortorder),
+                    a.attrelid
+             FROM pg_attribute a
+             JOIN pg_type t ON t.oid = a.atttypid
+             LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+             WHERE a.attrelid = ANY($1)
+                 AND a.attnum > 0
+                 AND NOT a.attisdropped
+             ORDER BY a.attrelid, a.attnum"
+        )
+        .bind(oids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut columns: HashMap<Oid, Vec<ColumnInfo>> = HashMap::new();
+        for row in rows {
+            let enum_values: Vec<String> = row.get(6);
+            columns.entry(row.get(7)).or_default().push(ColumnInfo {
+                name: row.get(0),
+                data_type: row.get(1),
+                nullable: row.get(2),
+                default_value: row.get(3),
+                description: row.get(4),
+                base_type: row.get(5),
+                enum_values: if enum_values.is_empty() { None } else { Some(enum_values) },
+            });
+        }
+
+        Ok(columns)
+    }
+
+    /// Fetch the functions of the schemas selected by `settings`
+    ///
+    /// Functions created by extensions and trigger functions are left out.
+    async fn fetch_functions(&self, settings: &ConnectionSettings) -> Result<Vec<FunctionInfo>, AppError> {
+        let sql = format!(
+            "SELECT n.nspname::text, p.proname::text,
+                    pg_get_function_identity_arguments(p.oid), pg_get_function_result(p.oid),
+                    p.proretset, obj_description(p.oid, 'pg_proc')
+             FROM pg_proc p
+             JOIN pg_namespace n ON n.oid = p.pronamespace
+             WHERE p.prokind = 'f'
+                 AND p.prorettype NOT IN ('trigger'::regtype, 'event_trigger'::regtype)
+                 AND NOT EXISTS (
+                     SELECT 1 FROM pg_depend d
+                     WHERE d.classid = 'pg_proc'::regclass AND d.objid = p.oid AND d.deptype = 'e'
+                 )
+                 AND {}
+                 AND has_function_privilege(p.oid, 'EXECUTE')
+             ORDER BY n.nspname, p.proname, 3",
+            schema_filter("n.nspname")
+        );
+
+        let functions = sqlx::query(&sql)
+            .bind(&settings.include_schemas)
+            .bind(&settings.exclude_schemas)
+            .map(|row: PgRow| FunctionInfo {
+                schema: row.get(0),
+                name: row.get(1),
+                arguments: row.get(2),
+                return_type: row.get(3),
+                returns_set: row.get(4),
+                description: row.get(5),
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(functions)
+    }
+
+    /// Fetch the sequences of the schemas selected by `settings`
+    async fn fetch_sequences(&self, settings: &ConnectionSettings) -> Result<Vec<SequenceInfo>, AppError> {
+        let sql = format!(
+            "SELECT n.nspname::text, c.relname::text, format_type(s.seqtypid, NULL), s.seqstart, s.seqincrement
+             FROM pg_sequence s
+             JOIN pg_class c ON c.oid = s.seqrelid
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE {}
+                 AND (pg_has_role(c.relowner, 'USAGE') OR has_sequence_privilege(c.oid, 'SELECT, UPDATE, USAGE'))
+             ORDER BY n.nspname, c.relname",
+            schema_filter("n.nspname")
+        );
+
+        let sequences = sqlx::query(&sql)
+            .bind(&settings.include_schemas)
+            .bind(&settings.exclude_schemas)
+            .map(|row: PgRow| SequenceInfo {
+                schema: row.get(0),
+                name: row.get(1),
+                data_type: row.get(2),
+                start_value: row.get(3),
+                increment: row.get(4),
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(sequences)
+    }
+}
+
+/// Table, view or materialized view listed by introspection
+struct Relation {
+    oid: Oid,
+    schema: String,
+    name: String,
+    /// `relkind` of the relation in `pg_class`
+    kind: String,
+    description: Option<String>,
+    definition: Option<String>,
+    partition_key: Option<String>,
+    partition_bound: Option<String>,
+    /// Qualified name of the table this is a partition of
+    parent: Option<String>,
+    /// Row count from the planner statistics
+    estimated_rows: i64,
+    statistics: TableStatistics,
+}
+
+/// Constraints of one table
+#[derive(Default)]
+struct TableConstraints {
+    primary_key: Vec<String>,
+    foreign_keys: Vec<ForeignKeyInfo>,
+    unique_constraints: Vec<UniqueConstraintInfo>,
+    check_constraints: Vec<CheckConstraintInfo>,
+}
+
+#[async_trait]
+impl DatabaseDriver for PostgresDriver {
+    fn kind(&self) -> DatabaseKind {
+        DatabaseKind::Postgres
+    }
+
+    fn dialect(&self) -> &'static dyn Dialect {
+        &PostgreSqlDialect {}
+    }
+
+    async fn test_connection(&self) -> Result<(), AppError> {
+        run_test_query(sqlx::query("SELECT 1").execute(&self.pool)).await
+    }
+
+    async fn introspect(&self, settings: &ConnectionSettings) -> Result<IntrospectedSchema, AppError> {
+        // The catalog is read with one query per kind of object for all relations at once,
+        // only exact row counts need a query per table
+        let relations = self.fetch_relations(settings).await?;
+        let oids: Vec<Oid> = relations.iter().map(|relation| relation.oid).collect();
+        let mut columns = self.fetch_columns(&oids).await?;
+        let mut constraints = self.fetch_constraints(&oids).await?;
+        let mut indexes = self.fetch_indexes(&oids).await?;
+        let mut row_counts = match settings.row_counts {
+            RowCountMode::Exact => self.fetch_row_counts(&relations).await,
+            RowCountMode::Estimated => HashMap::new(),
+        };
+
+        let mut tables = Vec::new();
+        let mut views = Vec::new();
+        let mut materialized_views = Vec::new();
+        for relation in relations {
+            let oid = relation.oid;
+            let relation_columns = columns.remove(&oid).unwrap_or_default();
+            if relation.kind == "v" || relation.kind == "m" {
+                let view = ViewInfo {
+                    columns: relation_columns,
+                    schema: relation.schema,
+                    name: relation.name,
+                    description: relation.description,
+                    definition: relation.definition,
+                };
+                if relation.kind == "m" {
+                    materialized_views.push(view);
+                } else {
+                    views.push(view);
+                }
+            } else {
+                let parent = relation.parent.clone();
+                let table = Self::table_info(
+                    relation,
+                    relation_columns,
+                    constraints.remove(&oid).unwrap_or_default(),
+                    indexes.remove(&oid).unwrap_or_default(),
+                    row_counts.remove(&oid),
+                    settings.row_counts,
+                );
+                tables.push((table, parent));
+            }
+        }
+
+        Ok(IntrospectedSchema {
+            tables: nest_partitions(tables),
+            views,
+            materialized_views,
+            functions: self.fetch_functions(settings).await?,
+            sequences: self.fetch_sequences(settings).await?,
+        })
+    }
+
+    async fn execute(&self, sql: &str, query_id: &str, settings: &ConnectionSettings) -> Result<QueryRows, AppError> {
+        // Describe and run the statement on one connection, so it is only prepared once
+        let mut conn = self.acquire_tracked(query_id, settings).await?;
+        let result = async {
+            let columns = describe_columns(&mut conn, sql).await?;
+            let rows = sqlx::query(sql).fetch_all(&mut *conn).await?;
+            Ok::<_, AppError>((columns, rows))
+        }
+        .await;
+        conn.finish().await;
+        let (columns, rows) = result?;
+
+        // Convert rows to JSON values
+        let rows = rows
+            .iter()
+            .map(|row| (0..columns.len()).map(|index| get_value_as_json(row, index)).collect())
+            .collect();
+
+        Ok(QueryRows { columns, rows })
+    }
+
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        query_id: &str,
+        settings: &ConnectionSettings,
+    ) -> Result<QueryStream, AppError> {
+        let mut conn = self.acquire_tracked(query_id, settings).await?;
+        let columns = match describe_columns(&mut conn, sql).await {
+            Ok(columns) => columns,
+            Err(e) => {
+                conn.finish().await;
+                return Err(e);
+            }
+        };
+        let rows = spawn_row_stream(conn, sql.to_string(), columns.len(), get_value_as_json);
+
+        Ok(QueryStream { columns, rows })
+    }
+
+    async fn cancel(&self, query_id: &str) -> Result<bool, AppError> {
+        self.running.cancel(query_id).await
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+/// Describe the result columns of a statement
+///
+/// Column metadata comes from the row description, so it is known even without rows.
+async fn describe_columns(conn: &mut PgConnection, sql: &str) -> Result<Vec<QueryColumn>, AppError> {
+    let describe = conn.describe(sql).await?;
+
+    Ok(describe
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(index, column)| QueryColumn {
+            name: column.name().to_string(),
+            db_type: column.type_info().name().to_string(),
+            logical_type: logical_type(column.type_info()),
+            nullable: describe.nullable(index),
+        })
+        .collect())
+}
+
+/// Convert a column value to JSON based on the column's `PostgreSQL` type
+fn get_value_as_json(row: &PgRow, index: usize) -> serde_json::Value {
+    use serde_json::Value;
+
+    let value = match row.try_get_raw(index) {
+        Ok(value) => value,
+        Err(e) => {
+            tracing::warn!(column = index, error = ?e, "failed to read PostgreSQL value");
+            return Value::Null;
+        }
+    };
+    if value.is_null() {
+        return Value::Null;
+    }
+
+    let type_info = value.type_info().into_owned();
+    let result = match value.format() {
+        PgValueFormat::Binary => value
+            .as_bytes()
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| binary_value_to_json(&type_info, bytes)),
+        PgValueFormat::Text => value
+            .as_str()
+            .map(|text| postgres_values::text_to_json(decoder_oid(&type_info), text))
+            .map_err(|e| e.to_string()),
+    };
+
+    result.unwrap_or_else(|e| {
+        tracing::warn!(column_type = %type_info.name(), error = %e, "failed to decode PostgreSQL value");
+        Value::Null
+    })
+}
+
+/// Decode a binary value, dispatching on the kind of its type
+fn binary_value_to_json(type_info: &PgTypeInfo, bytes: &[u8]) -> Result<serde_json::Value, String> {
+    match type_info.kind() {
+        PgTypeKind::Array(_) => postgres_values::array_to_json(bytes),
+        PgTypeKind::Composite(fields) => {
+            let names: Vec<String> = fields.iter().map(|(name, _)| name.clone()).collect();
+            postgres_values::record_to_json(bytes, Some(&names))
+        }
+        _ => postgres_values::binary_to_json(decoder_oid(type_info), bytes),
+    }
+}
+
+/// Logical type of a column, looking through arrays, composites, enums and domains
+fn logical_type(type_info: &PgTypeInfo) -> LogicalType {
+    match type_info.kind() {
+        PgTypeKind::Array(_) => LogicalType::Array,
+        PgTypeKind::Composite(_) => LogicalType::Json,
+        _ => postgres_values::logical_type(decoder_oid(type_info)),
+    }
+}
+
+/// OID whose decoder applies to a type: enums are sent as their label, domains as their base type
+fn decoder_oid(type_info: &PgTypeInfo) -> u32 {
+    match type_info.kind() {
+        PgTypeKind::Enum(_) => postgres_values::oid::TEXT,
+        PgTypeKind::Domain(base) => decoder_oid(base),
+        _ => type_info.oid().map_or(0, |oid| oid.0),
+    }
+}
+
+/// SQL condition selecting the schemas in `$1`, or every non-system schema when it is empty,
+/// except those in `$2`
+///
+/// Schemas starting with pg_ are reserved for the system.
+fn schema_filter(column: &str) -> String {
+    format!(
+        "({column} = ANY($1)
+             OR (cardinality($1) = 0 AND {column} <> 'information_schema' AND {column} NOT LIKE 'pg\\_%'))
+         AND {column} <> ALL($2)"
+    )
+}
+
+/// Move partitions under the table they are a partition of, at any depth
+///
+/// Partitions whose parent was not introspected, e.g. because its schema is excluded, stay at the top.
+fn nest_partitions(tables: Vec<(TableInfo, Option<String>)>) -> Vec<TableInfo> {
+    let names: HashSet<String> = tables.iter().map(|(table, _)| table.qualified_name()).collect();
+
+    let mut partitions: HashMap<String, Vec<TableInfo>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (table, parent) in tables {
+        match parent {
+            Some(parent) if names.contains(&parent) => partitions.entry(parent).or_default().push(table),
+            _ => roots.push(table),
+        }
+    }
+
+    for table in &mut roots {
+        attach_partitions(table, &mut partitions);
+    }
+    roots
+}
+
+fn attach_partitions(table: &mut TableInfo, partitions: &mut HashMap<String, Vec<TableInfo>>) {
+    if let Some(mut children) = partitions.remove(&table.qualified_name()) {
+        for child in &mut children {
+            attach_partitions(child, partitions);
+        }
+        table.partitions = children;
+    }
+
+    if table.row_count_estimated && table.kind == TableKind::Partitioned {
+        table.row_count = Some(table.partitions.iter().filter_map(|partition| partition.row_count).sum());
+    }
+}
+
+/// Size in bytes reported by a `pg_*_size` function
+fn to_bytes(size: i64) -> u64 {
+    u64::try_from(size).unwrap_or_default()
+}
+
+fn to_timestamp(time: Option<DateTime<Utc>>) -> Option<String> {
+    time.map(|time| time.to_rfc3339())
+}
+
+/// Validate that a string is a valid `PostgreSQL` identifier
+/// This prevents SQL injection when using identifiers in format! macros
+fn is_valid_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 63 // PostgreSQL identifier length limit
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && name.chars().next().is_some_and(|c| !c.is_ascii_digit()) // Can't start with digit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schema::builders;
+    use std::time::Instant;
+
+    /// Tables created by `introspection_benchmark`
+    const BENCH_TABLES: usize = 400;
+
+    fn table(name: &str) -> TableInfo {
+        builders::table(name, Vec::new())
+    }
+
+    #[test]
+    fn test_nest_partitions() {
+        let parent = |name: &str| Some(format!("public.{name}"));
+        let tables = nest_partitions(vec![
+            (table("events"), None),
+            (table("events_2024"), parent("events")),
+            (table("events_2024_q1"), parent("events_2024")),
+            (table("events_2025"), parent("events")),
+            (table("orphan"), Some("archive.events".to_string())),
+        ]);
+
+        let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["events", "orphan"]);
+        let partitions: Vec<&str> = tables[0].partitions.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(partitions, vec!["events_2024", "events_2025"]);
+        assert_eq!(tables[0].partitions[0].partitions[0].name, "events_2024_q1");
+    }
+
+    #[test]
+    fn test_nest_partitions_sums_estimates() {
+        let estimated = |name: &str, kind: TableKind, row_count: Option<u64>| TableInfo {
+            kind,
+            row_count,
+            row_count_estimated: true,
+            ..table(name)
+        };
+        let parent = |name: &str| Some(format!("public.{name}"));
+        let tables = nest_partitions(vec![
+            (estimated("events", TableKind::Partitioned, None), None),
+            (estimated("events_2024", TableKind::Partitioned, None), parent("events")),
+            (estimated("events_2024_q1", TableKind::Table, Some(100)), parent("events_2024")),
+            (estimated("events_2024_q2", TableKind::Table, Some(20)), parent("events_2024")),
+            (estimated("events_2025", TableKind::Table, Some(3)), parent("events")),
+            (estimated("archive", TableKind::Partitioned, None), None),
+        ]);
+
+        assert_eq!(tables[0].row_count, Some(123));
+        assert_eq!(tables[0].partitions[0].row_count, Some(120));
+        // A partitioned table without partitions has no rows
+        assert_eq!(tables[1].row_count, Some(0));
+    }
+
+    /// Compare introspection with catalog-wide queries to reading the catalog table by table
+    ///
+    /// Run against a scratch database with
+    /// `POSTGRES_BENCH_URL=postgres://... cargo test --release introspection_benchmark -- --ignored --nocapture`
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL server in POSTGRES_BENCH_URL"]
+    async fn introspection_benchmark() {
+        let Ok(url) = std::env::var("POSTGRES_BENCH_URL") else {
+            return;
+        };
+        let driver = PostgresDriver::connect(&url).await.unwrap();
+        sqlx::query("DROP SCHEMA IF EXISTS introspection_bench CASCADE").execute(&driver.pool).await.unwrap();
+        sqlx::query("CREATE SCHEMA introspection_bench").execute(&driver.pool).await.unwrap();
+        sqlx::query(&format!(
+            "DO $$ BEGIN FOR i IN 1..{BENCH_TABLES} LOOP
+                 EXECUTE format('CREATE TABLE introspection_bench.t%s (
+                     id int PRIMARY KEY,
+                     name text UNIQUE,
+                     amount numeric CHECK (amount >= 0),
+                     parent int REFERENCES introspection_bench.t%s (id))', i, i);
+                 EXECUTE format('CREATE INDEX ON introspection_bench.t%s (amount)', i);
+             END LOOP; END $$"
+        ))
+        .execute(&driver.pool)
+        .await
+        .unwrap();
+
+        // Row counts are left out, they take a query per table either way
+        let settings = ConnectionSettings {
+            include_schemas: vec!["introspection_bench".to_string()],
+            row_counts: RowCountMode::Estimated,
+            ..ConnectionSettings::default()
+        };
+
+        let start = Instant::now();
+        let relations = driver.fetch_relations(&settings).await.unwrap();
+        for relation in &relations {
+            let oids = [relation.oid];
+            driver.fetch_columns(&oids).await.unwrap();
+            driver.fetch_constraints(&oids).await.unwrap();
+            driver.fetch_indexes(&oids).await.unwrap();
+        }
+        let per_table = start.elapsed();
+
+        let start = Instant::now();
+        let schema = driver.introspect(&settings).await.unwrap();
+        let catalog_wide = start.elapsed();
+
+        sqlx::query("DROP SCHEMA introspection_bench CASCADE").execute(&driver.pool).await.unwrap();
+        driver.close().await;
+
+        println!("{BENCH_TABLES} tables: table by table {per_table:?}, catalog-wide {catalog_wide:?}");
+        assert_eq!(schema.tables.len(), BENCH_TABLES);
+        assert!(schema.tables.iter().all(|table| table.foreign_keys.len() == 1 && table.indexes.len() == 3));
+        assert!(catalog_wide < per_table);
+    }
+}
@@ -0,0 +1,804 @@
+// Conversion of PostgreSQL wire values to JSON
+// Values are decoded from the binary protocol format, keyed by the column type OID,
+// so every type keeps its precision and canonical text form
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use crate::models::query::LogicalType;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat};
+use serde_json::Value;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Type OIDs from the default `pg_type` catalog
+pub(crate) mod oid {
+    pub const BOOL: u32 = 16;
+    pub const BYTEA: u32 = 17;
+    pub const CHAR: u32 = 18;
+    pub const NAME: u32 = 19;
+    pub const INT8: u32 = 20;
+    pub const INT2: u32 = 21;
+    pub const INT4: u32 = 23;
+    pub const TEXT: u32 = 25;
+    pub const OID: u32 = 26;
+    pub const JSON: u32 = 114;
+    pub const XML: u32 = 142;
+    pub const CIDR: u32 = 650;
+    pub const FLOAT4: u32 = 700;
+    pub const FLOAT8: u32 = 701;
+    pub const UNKNOWN: u32 = 705;
+    pub const MACADDR8: u32 = 774;
+    pub const MONEY: u32 = 790;
+    pub const MACADDR: u32 = 829;
+    pub const INET: u32 = 869;
+    pub const BPCHAR: u32 = 1042;
+    pub const VARCHAR: u32 = 1043;
+    pub const DATE: u32 = 1082;
+    pub const TIME: u32 = 1083;
+    pub const TIMESTAMP: u32 = 1114;
+    pub const TIMESTAMPTZ: u32 = 1184;
+    pub const INTERVAL: u32 = 1186;
+    pub const TIMETZ: u32 = 1266;
+    pub const BIT: u32 = 1560;
+    pub const VARBIT: u32 = 1562;
+    pub const NUMERIC: u32 = 1700;
+    pub const RECORD: u32 = 2249;
+    pub const UUID: u32 = 2950;
+    pub const JSONB: u32 = 3802;
+}
+
+/// Microseconds per second, minute and hour
+const USECS_PER_SEC: i64 = 1_000_000;
+const USECS_PER_MINUTE: i64 = 60 * USECS_PER_SEC;
+const USECS_PER_HOUR: i64 = 60 * USECS_PER_MINUTE;
+
+/// Decode a binary format value of the type identified by `type_oid`
+///
+/// Types without a dedicated decoder are returned as text when the bytes are
+/// valid UTF-8 (extension types such as `citext`), and as base64 otherwise.
+pub(crate) fn binary_to_json(type_oid: u32, bytes: &[u8]) -> Result<Value, String> {
+    match type_oid {
+        oid::BOOL => Ok(Value::Bool(fixed::<1>(bytes)?[0] != 0)),
+        oid::INT2 => Ok(Value::from(i16::from_be_bytes(fixed(bytes)?))),
+        oid::INT4 => Ok(Value::from(i32::from_be_bytes(fixed(bytes)?))),
+        oid::INT8 => Ok(Value::from(i64::from_be_bytes(fixed(bytes)?))),
+        oid::OID => Ok(Value::from(u32::from_be_bytes(fixed(bytes)?))),
+        oid::FLOAT4 => Ok(float_to_json(f64::from(f32::from_be_bytes(fixed(bytes)?)))),
+        oid::FLOAT8 => Ok(float_to_json(f64::from_be_bytes(fixed(bytes)?))),
+        oid::NUMERIC => numeric_to_string(bytes).map(Value::String),
+        oid::MONEY => Ok(Value::String(money_to_string(i64::from_be_bytes(fixed(bytes)?)))),
+        oid::TEXT | oid::VARCHAR | oid::BPCHAR | oid::NAME | oid::XML | oid::UNKNOWN => {
+            utf8(bytes).map(|text| Value::String(text.to_string()))
+        }
+        oid::CHAR => Ok(Value::String(char::from(fixed::<1>(bytes)?[0]).to_string())),
+        oid::JSON => parse_json(bytes),
+        oid::JSONB => match bytes.split_first() {
+            Some((1, json)) => parse_json(json),
+            _ => Err("unsupported jsonb format version".to_string()),
+        },
+        oid::BYTEA => Ok(Value::String(BASE64.encode(bytes))),
+        oid::UUID => Ok(Value::String(uuid_to_string(&fixed::<16>(bytes)?))),
+        oid::DATE => date_to_string(i32::from_be_bytes(fixed(bytes)?)).map(Value::String),
+        oid::TIME => time_to_string(i64::from_be_bytes(fixed(bytes)?)).map(Value::String),
+        oid::TIMETZ => timetz_to_string(bytes).map(Value::String),
+        oid::TIMESTAMP => timestamp_to_string(i64::from_be_bytes(fixed(bytes)?), false).map(Value::String),
+        oid::TIMESTAMPTZ => timestamp_to_string(i64::from_be_bytes(fixed(bytes)?), true).map(Value::String),
+        oid::INTERVAL => interval_to_string(bytes).map(Value::String),
+        oid::INET | oid::CIDR => inet_to_string(bytes).map(Value::String),
+        oid::MACADDR | oid::MACADDR8 => Ok(Value::String(macaddr_to_string(bytes))),
+        oid::BIT | oid::VARBIT => bits_to_string(bytes).map(Value::String),
+        oid::RECORD => record_to_json(bytes, None),
+        _ => Ok(match std::str::from_utf8(bytes) {
+            Ok(text) if !text.contains('\0') => Value::String(text.to_string()),
+            _ => Value::String(BASE64.encode(bytes)),
+        }),
+    }
+}
+
+/// Logical type of a scalar type, `Text` for anything without a closer match
+pub(crate) fn logical_type(type_oid: u32) -> LogicalType {
+    match type_oid {
+        oid::INT2 | oid::INT4 | oid::INT8 | oid::OID => LogicalType::Integer,
+        oid::FLOAT4 | oid::FLOAT8 | oid::NUMERIC | oid::MONEY => LogicalType::Decimal,
+        oid::BOOL => LogicalType::Boolean,
+        oid::DATE | oid::TIME | oid::TIMETZ | oid::TIMESTAMP | oid::TIMESTAMPTZ => LogicalType::Timestamp,
+        oid::JSON | oid::JSONB | oid::RECORD => LogicalType::Json,
+        oid::BYTEA => LogicalType::Binary,
+        _ => LogicalType::Text,
+    }
+}
+
+/// Convert a text format value, parsing the types that have a JSON equivalent
+pub(crate) fn text_to_json(type_oid: u32, text: &str) -> Value {
+    let parsed = match type_oid {
+        oid::BOOL => Some(Value::Bool(text == "t")),
+        oid::INT2 | oid::INT4 | oid::INT8 | oid::OID => text.parse::<i64>().ok().map(Value::from),
+        oid::JSON | oid::JSONB => serde_json::from_str(text).ok(),
+        _ => None,
+    };
+
+    parsed.unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+/// Decode a binary array into nested JSON arrays, one level per dimension
+///
+/// Elements are decoded with the element OID carried in the array header.
+pub(crate) fn array_to_json(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = Reader::new(bytes);
+    let ndim = usize::try_from(reader.i32()?).map_err(|_| "negative array dimension count")?;
+    let _has_nulls = reader.i32()?;
+    let element_oid = reader.u32()?;
+
+    let mut dims = Vec::with_capacity(ndim);
+    for _ in 0..ndim {
+        dims.push(usize::try_from(reader.i32()?).map_err(|_| "negative array dimension")?);
+        let _lower_bound = reader.i32()?;
+    }
+
+    if dims.is_empty() {
+        return Ok(Value::Array(Vec::new()));
+    }
+
+    let value = read_array_dimension(&mut reader, element_oid, &dims)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+/// Read the elements of one array dimension, recursing into inner dimensions
+fn read_array_dimension(reader: &mut Reader<'_>, element_oid: u32, dims: &[usize]) -> Result<Value, String> {
+    let Some((&len, inner)) = dims.split_first() else {
+        return Ok(Value::Null);
+    };
+
+    let mut values = Vec::with_capacity(len);
+    for _ in 0..len {
+        if inner.is_empty() {
+            values.push(match reader.field()? {
+                Some(bytes) => binary_to_json(element_oid, bytes)?,
+                None => Value::Null,
+            });
+        } else {
+            values.push(read_array_dimension(reader, element_oid, inner)?);
+        }
+    }
+
+    Ok(Value::Array(values))
+}
+
+/// Decode a binary composite value into an object, or an array when field names are unknown
+pub(crate) fn record_to_json(bytes: &[u8], field_names: Option<&[String]>) -> Result<Value, String> {
+    let mut reader = Reader::new(bytes);
+    let count = usize::try_from(reader.i32()?).map_err(|_| "negative record field count")?;
+
+    let mut values = Vec::with_capacity(count);
+    for _ in 0..count {
+        let field_oid = reader.u32()?;
+        values.push(match reader.field()? {
+            Some(bytes) => binary_to_json(field_oid, bytes)?,
+            None => Value::Null,
+        });
+    }
+    reader.finish()?;
+
+    match field_names {
+        Some(names) if names.len() == values.len() => Ok(Value::Object(
+            names.iter().cloned().zip(values).collect(),
+        )),
+        _ => Ok(Value::Array(values)),
+    }
+}
+
+/// Format a `numeric` value as an exact decimal string
+///
+/// The wire format stores base-10000 digits with a weight (the power of the
+/// first digit), a sign and the display scale.
+fn numeric_to_string(bytes: &[u8]) -> Result<String, String> {
+    const SIGN_NEGATIVE: u16 = 0x4000;
+    const SIGN_NAN: u16 = 0xC000;
+    const SIGN_POSITIVE_INFINITY: u16 = 0xD000;
+    const SIGN_NEGATIVE_INFINITY: u16 = 0xF000;
+
+    let mut reader = Reader::new(bytes);
+    let ndigits = usize::try_from(reader.i16()?).map_err(|_| "negative numeric digit count")?;
+    let weight = i64::from(reader.i16()?);
+    let sign = reader.u16()?;
+    let dscale = usize::from(reader.u16()?);
+
+    match sign {
+        SIGN_NAN => return Ok("NaN".to_string()),
+        SIGN_POSITIVE_INFINITY => return Ok("Infinity".to_string()),
+        SIGN_NEGATIVE_INFINITY => return Ok("-Infinity".to_string()),
+        _ => {}
+    }
+
+    let mut digits = Vec::with_capacity(ndigits);
+    for _ in 0..ndigits {
+        digits.push(reader.i16()?);
+    }
+    reader.finish()?;
+
+    // Digit `i` is multiplied by 10000^(weight - i)
+    let digit_at = |index: i64| -> i16 {
+        usize::try_from(index).ok().and_then(|i| digits.get(i).copied()).unwrap_or(0)
+    };
+
+    let mut result = String::new();
+    if sign == SIGN_NEGATIVE && digits.iter().any(|&d| d != 0) {
+        result.push('-');
+    }
+
+    if weight < 0 {
+        result.push('0');
+    } else {
+        result.push_str(&digit_at(0).to_string());
+        for index in 1..=weight {
+            result.push_str(&format!("{:04}", digit_at(index)));
+        }
+    }
+
+    if dscale > 0 {
+        let mut fraction = String::new();
+        let mut index = weight + 1;
+        while fraction.len() < dscale {
+            fraction.push_str(&format!("{:04}", digit_at(index)));
+            index += 1;
+        }
+        fraction.truncate(dscale);
+        result.push('.');
+        result.push_str(&fraction);
+    }
+
+    Ok(result)
+}
+
+/// Format a `money` value, stored as an integer number of cents
+fn money_to_string(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Floats that JSON cannot represent (NaN, infinities) are returned as strings
+fn float_to_json(value: f64) -> Value {
+    serde_json::Number::from_f64(value).map_or_else(
+        || {
+            let text = if value.is_nan() {
+                "NaN"
+            } else if value.is_sign_positive() {
+                "Infinity"
+            } else {
+                "-Infinity"
+            };
+            Value::String(text.to_string())
+        },
+        Value::Number,
+    )
+}
+
+/// Format a UUID in its canonical hyphenated form
+fn uuid_to_string(bytes: &[u8; 16]) -> String {
+    let hex = format!("{:032x}", u128::from_be_bytes(*bytes));
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+/// `PostgreSQL` dates and timestamps count from 2000-01-01
+fn postgres_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .unwrap_or_default()
+}
+
+/// Format a `date` (days since 2000-01-01) as `YYYY-MM-DD`
+fn date_to_string(days: i32) -> Result<String, String> {
+    match days {
+        i32::MAX => Ok("infinity".to_string()),
+        i32::MIN => Ok("-infinity".to_string()),
+        _ => postgres_epoch()
+            .date()
+            .checked_add_signed(Duration::days(i64::from(days)))
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .ok_or_else(|| format!("date out of range: {days}")),
+    }
+}
+
+/// Format a `timestamp` or `timestamptz` (microseconds since 2000-01-01)
+///
+/// `timestamptz` values are sent in UTC and formatted as RFC 3339 with a `Z` suffix.
+fn timestamp_to_string(micros: i64, with_time_zone: bool) -> Result<String, String> {
+    match micros {
+        i64::MAX => return Ok("infinity".to_string()),
+        i64::MIN => return Ok("-infinity".to_string()),
+        _ => {}
+    }
+
+    let timestamp = postgres_epoch()
+        .checked_add_signed(Duration::microseconds(micros))
+        .ok_or_else(|| format!("timestamp out of range: {micros}"))?;
+
+    Ok(if with_time_zone {
+        timestamp.and_utc().to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    } else {
+        timestamp.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+    })
+}
+
+/// Format a `time` (microseconds since midnight) as `HH:MM:SS[.ffffff]`
+fn time_to_string(micros: i64) -> Result<String, String> {
+    // 24:00:00 is a valid time of day in PostgreSQL but not in chrono
+    if micros == 24 * USECS_PER_HOUR {
+        return Ok("24:00:00".to_string());
+    }
+
+    u32::try_from(micros / USECS_PER_SEC)
+        .ok()
+        .zip(u32::try_from(micros % USECS_PER_SEC * 1000).ok())
+        .and_then(|(secs, nanos)| NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos))
+        .map(|time| time.format("%H:%M:%S%.f").to_string())
+        .ok_or_else(|| format!("time out of range: {micros}"))
+}
+
+/// Format a `timetz` as a time followed by its UTC offset
+fn timetz_to_string(bytes: &[u8]) -> Result<String, String> {
+    let mut reader = Reader::new(bytes);
+    let time = time_to_string(reader.i64()?)?;
+    // The zone is stored in seconds west of UTC
+    let offset = -reader.i32()?;
+    reader.finish()?;
+
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.unsigned_abs();
+    let (hours, minutes, seconds) = (offset / 3600, offset % 3600 / 60, offset % 60);
+
+    Ok(if seconds == 0 {
+        format!("{time}{sign}{hours:02}:{minutes:02}")
+    } else {
+        format!("{time}{sign}{hours:02}:{minutes:02}:{seconds:02}")
+    })
+}
+
+/// Format an `interval` as an ISO 8601 duration, e.g. `P1Y2M3DT4H5M6.5S`
+///
+/// Months, days and microseconds are stored separately and may have different
+/// signs, so each component carries its own sign like `IntervalStyle = iso_8601`.
+fn interval_to_string(bytes: &[u8]) -> Result<String, String> {
+    let mut reader = Reader::new(bytes);
+    let micros = reader.i64()?;
+    let days = reader.i32()?;
+    let months = reader.i32()?;
+    reader.finish()?;
+
+    let mut result = String::from("P");
+    let (years, months) = (months / 12, months % 12);
+    for (value, unit) in [(years, 'Y'), (months, 'M'), (days, 'D')] {
+        if value != 0 {
+            result.push_str(&format!("{value}{unit}"));
+        }
+    }
+
+    if micros != 0 {
+        result.push('T');
+        let hours = micros / USECS_PER_HOUR;
+        let minutes = micros % USECS_PER_HOUR / USECS_PER_MINUTE;
+        let seconds = micros % USECS_PER_MINUTE;
+        for (value, unit) in [(hours, 'H'), (minutes, 'M')] {
+            if value != 0 {
+                result.push_str(&format!("{value}{unit}"));
+            }
+        }
+        if seconds != 0 {
+            let sign = if seconds < 0 { "-" } else { "" };
+            let seconds = seconds.unsigned_abs();
+            let fraction = format!("{:06}", seconds % 1_000_000);
+            let fraction = fraction.trim_end_matches('0');
+            if fraction.is_empty() {
+                result.push_str(&format!("{sign}{}S", seconds / 1_000_000));
+            } else {
+                result.push_str(&format!("{sign}{}.{fraction}S", seconds / 1_000_000));
+            }
+        }
+    }
+
+    if result == "P" {
+        result.push_str("T0S");
+    }
+
+    Ok(result)
+}
+
+/// Format an `inet` or `cidr` address, with the prefix length unless it is a single host
+fn inet_to_string(bytes: &[u8]) -> Result<String, String> {
+    const FAMILY_INET: u8 = 2;
+    const FAMILY_INET6: u8 = 3;
+
+    let mut reader = Reader::new(bytes);
+    let header = reader.take(4)?;
+    let (family, bits, is_cidr, len) = (header[0], header[1], header[2] != 0, header[3]);
+    let address = reader.take(usize::from(len))?;
+    reader.finish()?;
+
+    let (address, max_bits) = match family {
+        FAMILY_INET => {
+            let octets: [u8; 4] = address.try_into().map_err(|_| "invalid IPv4 address length")?;
+            (Ipv4Addr::from(octets).to_string(), 32)
+        }
+        FAMILY_INET6 => {
+            let octets: [u8; 16] = address.try_into().map_err(|_| "invalid IPv6 address length")?;
+            (Ipv6Addr::from(octets).to_string(), 128)
+        }
+        _ => return Err(format!("unknown address family: {family}")),
+    };
+
+    Ok(if is_cidr || bits != max_bits {
+        format!("{address}/{bits}")
+    } else {
+        address
+    })
+}
+
+/// Format a `macaddr` or `macaddr8` as colon separated hex
+fn macaddr_to_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(":")
+}
+
+/// Format a `bit` or `bit varying` value as a string of `0` and `1`
+fn bits_to_string(bytes: &[u8]) -> Result<String, String> {
+    let mut reader = Reader::new(bytes);
+    let len = usize::try_from(reader.i32()?).map_err(|_| "negative bit string length")?;
+    let data = reader.take(len.div_ceil(8))?;
+    reader.finish()?;
+
+    Ok((0..len)
+        .map(|i| if data[i / 8] & (0x80 >> (i % 8)) == 0 { '0' } else { '1' })
+        .collect())
+}
+
+/// Parse a `json` value, which is sent as text
+fn parse_json(bytes: &[u8]) -> Result<Value, String> {
+    serde_json::from_slice(bytes).map_err(|e| format!("invalid JSON: {e}"))
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str, String> {
+    std::str::from_utf8(bytes).map_err(|e| format!("invalid UTF-8: {e}"))
+}
+
+/// Require a fixed size value, as sent for integers, floats and UUIDs
+fn fixed<const N: usize>(bytes: &[u8]) -> Result<[u8; N], String> {
+    bytes
+        .try_into()
+        .map_err(|_| format!("expected {N} bytes, got {}", bytes.len()))
+}
+
+/// Cursor over a binary value made of big-endian fields
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err(format!("unexpected end of value, needed {len} more bytes"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        fixed(self.take(N)?)
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.array().map(i16::from_be_bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        self.array().map(i32::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        self.array().map(i64::from_be_bytes)
+    }
+
+    /// Read a length prefixed field, where a length of -1 means NULL
+    fn field(&mut self) -> Result<Option<&'a [u8]>, String> {
+        let len = self.i32()?;
+        if len < 0 {
+            return Ok(None);
+        }
+        let len = usize::try_from(len).map_err(|e| e.to_string())?;
+        self.take(len).map(Some)
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} trailing bytes in value", self.bytes.len()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Build a binary `numeric` value from its header fields and base-10000 digits
+    fn numeric(weight: i16, sign: u16, dscale: u16, digits: &[i16]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&i16::try_from(digits.len()).unwrap().to_be_bytes());
+        bytes.extend_from_slice(&weight.to_be_bytes());
+        bytes.extend_from_slice(&sign.to_be_bytes());
+        bytes.extend_from_slice(&dscale.to_be_bytes());
+        for digit in digits {
+            bytes.extend_from_slice(&digit.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Build a binary array from its dimensions and already encoded elements
+    fn array(element_oid: u32, dims: &[i32], elements: &[Option<Vec<u8>>]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&i32::try_from(dims.len()).unwrap().to_be_bytes());
+        bytes.extend_from_slice(&i32::from(elements.iter().any(Option::is_none)).to_be_bytes());
+        bytes.extend_from_slice(&element_oid.to_be_bytes());
+        for dim in dims {
+            bytes.extend_from_slice(&dim.to_be_bytes());
+            bytes.extend_from_slice(&1i32.to_be_bytes());
+        }
+        for element in elements {
+            match element {
+                Some(data) => {
+                    bytes.extend_from_slice(&i32::try_from(data.len()).unwrap().to_be_bytes());
+                    bytes.extend_from_slice(data);
+                }
+                None => bytes.extend_from_slice(&(-1i32).to_be_bytes()),
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(binary_to_json(oid::INT2, &(-12i16).to_be_bytes()).unwrap(), json!(-12));
+        assert_eq!(binary_to_json(oid::INT4, &2_000_000_000i32.to_be_bytes()).unwrap(), json!(2_000_000_000));
+        assert_eq!(binary_to_json(oid::INT8, &i64::MAX.to_be_bytes()).unwrap(), json!(i64::MAX));
+        assert_eq!(binary_to_json(oid::OID, &4_000_000_000u32.to_be_bytes()).unwrap(), json!(4_000_000_000u32));
+        assert!(binary_to_json(oid::INT4, &[0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_bool_and_floats() {
+        assert_eq!(binary_to_json(oid::BOOL, &[1]).unwrap(), json!(true));
+        assert_eq!(binary_to_json(oid::BOOL, &[0]).unwrap(), json!(false));
+        assert_eq!(binary_to_json(oid::FLOAT4, &1.5f32.to_be_bytes()).unwrap(), json!(1.5));
+        assert_eq!(binary_to_json(oid::FLOAT8, &(-0.25f64).to_be_bytes()).unwrap(), json!(-0.25));
+        assert_eq!(binary_to_json(oid::FLOAT8, &f64::NAN.to_be_bytes()).unwrap(), json!("NaN"));
+        assert_eq!(binary_to_json(oid::FLOAT8, &f64::NEG_INFINITY.to_be_bytes()).unwrap(), json!("-Infinity"));
+    }
+
+    #[test]
+    fn test_numeric() {
+        // 12345.678
+        let value = numeric(1, 0, 3, &[1, 2345, 6780]);
+        assert_eq!(binary_to_json(oid::NUMERIC, &value).unwrap(), json!("12345.678"));
+        // -0.00042 keeps its leading zeros
+        let value = numeric(-1, 0x4000, 5, &[4, 2000]);
+        assert_eq!(binary_to_json(oid::NUMERIC, &value).unwrap(), json!("-0.00042"));
+        // 10^20 + 0.1 exceeds f64 precision and stays exact
+        let value = numeric(5, 0, 2, &[1, 0, 0, 0, 0, 0, 1000]);
+        assert_eq!(binary_to_json(oid::NUMERIC, &value).unwrap(), json!("100000000000000000000.10"));
+        // Trailing zero digits are omitted from the wire format
+        assert_eq!(binary_to_json(oid::NUMERIC, &numeric(1, 0, 0, &[12])).unwrap(), json!("120000"));
+        assert_eq!(binary_to_json(oid::NUMERIC, &numeric(0, 0, 2, &[])).unwrap(), json!("0.00"));
+        assert_eq!(binary_to_json(oid::NUMERIC, &numeric(0, 0xC000, 0, &[])).unwrap(), json!("NaN"));
+        assert_eq!(binary_to_json(oid::NUMERIC, &numeric(0, 0xF000, 0, &[])).unwrap(), json!("-Infinity"));
+    }
+
+    #[test]
+    fn test_money() {
+        assert_eq!(binary_to_json(oid::MONEY, &123_456i64.to_be_bytes()).unwrap(), json!("1234.56"));
+        assert_eq!(binary_to_json(oid::MONEY, &(-5i64).to_be_bytes()).unwrap(), json!("-0.05"));
+    }
+
+    #[test]
+    fn test_text_types() {
+        assert_eq!(binary_to_json(oid::TEXT, "héllo".as_bytes()).unwrap(), json!("héllo"));
+        assert_eq!(binary_to_json(oid::VARCHAR, b"abc").unwrap(), json!("abc"));
+        assert_eq!(binary_to_json(oid::BPCHAR, b"ab  ").unwrap(), json!("ab  "));
+        assert_eq!(binary_to_json(oid::CHAR, b"x").unwrap(), json!("x"));
+        assert!(binary_to_json(oid::TEXT, &[0xff, 0xfe]).is_err());
+    }
+
+    #[test]
+    fn test_json_and_jsonb() {
+        assert_eq!(binary_to_json(oid::JSON, br#"{"a": [1, 2]}"#).unwrap(), json!({"a": [1, 2]}));
+        assert_eq!(binary_to_json(oid::JSONB, b"\x01{\"b\": null}").unwrap(), json!({"b": null}));
+        assert!(binary_to_json(oid::JSONB, b"\x02{}").is_err());
+    }
+
+    #[test]
+    fn test_bytea_as_base64() {
+        assert_eq!(binary_to_json(oid::BYTEA, &[0xde, 0xad, 0xbe, 0xef]).unwrap(), json!("3q2+7w=="));
+        assert_eq!(binary_to_json(oid::BYTEA, &[]).unwrap(), json!(""));
+    }
+
+    #[test]
+    fn test_uuid() {
+        let bytes = [
+            0x55, 0x0e, 0x84, 0x00, 0xe2, 0x9b, 0x41, 0xd4,
+            0xa7, 0x16, 0x44, 0x66, 0x55, 0x44, 0x00, 0x00,
+        ];
+        assert_eq!(
+            binary_to_json(oid::UUID, &bytes).unwrap(),
+            json!("550e8400-e29b-41d4-a716-446655440000")
+        );
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(binary_to_json(oid::DATE, &0i32.to_be_bytes()).unwrap(), json!("2000-01-01"));
+        assert_eq!(binary_to_json(oid::DATE, &8_825i32.to_be_bytes()).unwrap(), json!("2024-02-29"));
+        assert_eq!(binary_to_json(oid::DATE, &(-1i32).to_be_bytes()).unwrap(), json!("1999-12-31"));
+        assert_eq!(binary_to_json(oid::DATE, &i32::MAX.to_be_bytes()).unwrap(), json!("infinity"));
+    }
+
+    #[test]
+    fn test_time_and_timetz() {
+        let micros = 13 * USECS_PER_HOUR + 5 * USECS_PER_MINUTE + 7 * USECS_PER_SEC + 250_000;
+        assert_eq!(binary_to_json(oid::TIME, &micros.to_be_bytes()).unwrap(), json!("13:05:07.250"));
+
+        // 13:05:07.25+05:30, sent as seconds west of UTC
+        let mut bytes = micros.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&(-19_800i32).to_be_bytes());
+        assert_eq!(binary_to_json(oid::TIMETZ, &bytes).unwrap(), json!("13:05:07.250+05:30"));
+    }
+
+    #[test]
+    fn test_timestamp() {
+        // 2024-02-29T12:30:45.123456
+        let micros = 762_525_045_123_456i64;
+        assert_eq!(
+            binary_to_json(oid::TIMESTAMP, &micros.to_be_bytes()).unwrap(),
+            json!("2024-02-29T12:30:45.123456")
+        );
+        assert_eq!(
+            binary_to_json(oid::TIMESTAMP, &(-USECS_PER_SEC).to_be_bytes()).unwrap(),
+            json!("1999-12-31T23:59:59")
+        );
+    }
+
+    #[test]
+    fn test_timestamptz() {
+        let micros = 762_525_045_123_456i64;
+        assert_eq!(
+            binary_to_json(oid::TIMESTAMPTZ, &micros.to_be_bytes()).unwrap(),
+            json!("2024-02-29T12:30:45.123456Z")
+        );
+        assert_eq!(
+            binary_to_json(oid::TIMESTAMPTZ, &0i64.to_be_bytes()).unwrap(),
+            json!("2000-01-01T00:00:00Z")
+        );
+        assert_eq!(binary_to_json(oid::TIMESTAMPTZ, &i64::MIN.to_be_bytes()).unwrap(), json!("-infinity"));
+    }
+
+    #[test]
+    fn test_interval() {
+        let interval = |micros: i64, days: i32, months: i32| {
+            let mut bytes = micros.to_be_bytes().to_vec();
+            bytes.extend_from_slice(&days.to_be_bytes());
+            bytes.extend_from_slice(&months.to_be_bytes());
+            binary_to_json(oid::INTERVAL, &bytes).unwrap()
+        };
+
+        let time = 4 * USECS_PER_HOUR + 5 * USECS_PER_MINUTE + 6 * USECS_PER_SEC + 500_000;
+        assert_eq!(interval(time, 3, 14), json!("P1Y2M3DT4H5M6.5S"));
+        assert_eq!(interval(0, 0, 0), json!("PT0S"));
+        assert_eq!(interval(-90 * USECS_PER_SEC, 0, 0), json!("PT-1M-30S"));
+        assert_eq!(interval(0, -7, 1), json!("P1M-7D"));
+    }
+
+    #[test]
+    fn test_inet_and_cidr() {
+        assert_eq!(binary_to_json(oid::INET, &[2, 32, 0, 4, 192, 168, 0, 1]).unwrap(), json!("192.168.0.1"));
+        assert_eq!(binary_to_json(oid::INET, &[2, 24, 0, 4, 10, 0, 0, 7]).unwrap(), json!("10.0.0.7/24"));
+        assert_eq!(binary_to_json(oid::CIDR, &[2, 32, 1, 4, 10, 0, 0, 0]).unwrap(), json!("10.0.0.0/32"));
+
+        let mut ipv6 = vec![3, 64, 1, 16, 0x20, 0x01, 0x0d, 0xb8];
+        ipv6.extend_from_slice(&[0; 12]);
+        assert_eq!(binary_to_json(oid::CIDR, &ipv6).unwrap(), json!("2001:db8::/64"));
+    }
+
+    #[test]
+    fn test_macaddr_and_bits() {
+        assert_eq!(
+            binary_to_json(oid::MACADDR, &[0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]).unwrap(),
+            json!("08:00:2b:01:02:03")
+        );
+
+        let mut bits = 10i32.to_be_bytes().to_vec();
+        bits.extend_from_slice(&[0b1010_0000, 0b1100_0000]);
+        assert_eq!(binary_to_json(oid::VARBIT, &bits).unwrap(), json!("1010000011"));
+    }
+
+    #[test]
+    fn test_arrays() {
+        // int4[] with a NULL element
+        let value = array(oid::INT4, &[3], &[
+            Some(1i32.to_be_bytes().to_vec()),
+            None,
+            Some(3i32.to_be_bytes().to_vec()),
+        ]);
+        assert_eq!(array_to_json(&value).unwrap(), json!([1, null, 3]));
+
+        // Two-dimensional text[][] becomes nested arrays
+        let value = array(oid::TEXT, &[2, 2], &[
+            Some(b"a".to_vec()),
+            Some(b"b".to_vec()),
+            Some(b"c".to_vec()),
+            Some(b"d".to_vec()),
+        ]);
+        assert_eq!(array_to_json(&value).unwrap(), json!([["a", "b"], ["c", "d"]]));
+
+        // Elements use their own decoder
+        let value = array(oid::NUMERIC, &[1], &[Some(numeric(0, 0, 1, &[3, 1000]))]);
+        assert_eq!(array_to_json(&value).unwrap(), json!(["3.1"]));
+
+        // Empty arrays have no dimensions
+        assert_eq!(array_to_json(&array(oid::INT4, &[], &[])).unwrap(), json!([]));
+    }
+
+    #[test]
+    fn test_enum_labels() {
+        // Enum types get dynamic OIDs; their labels are sent as UTF-8 text
+        assert_eq!(binary_to_json(16_385, b"happy").unwrap(), json!("happy"));
+        let value = array(16_385, &[2], &[Some(b"sad".to_vec()), Some(b"ok".to_vec())]);
+        assert_eq!(array_to_json(&value).unwrap(), json!(["sad", "ok"]));
+    }
+
+    #[test]
+    fn test_records() {
+        let mut value = 2i32.to_be_bytes().to_vec();
+        value.extend_from_slice(&oid::INT4.to_be_bytes());
+        value.extend_from_slice(&4i32.to_be_bytes());
+        value.extend_from_slice(&7i32.to_be_bytes());
+        value.extend_from_slice(&oid::TEXT.to_be_bytes());
+        value.extend_from_slice(&(-1i32).to_be_bytes());
+
+        assert_eq!(binary_to_json(oid::RECORD, &value).unwrap(), json!([7, null]));
+        let names = vec!["id".to_string(), "label".to_string()];
+        assert_eq!(record_to_json(&value, Some(&names)).unwrap(), json!({"id": 7, "label": null}));
+    }
+
+    #[test]
+    fn test_logical_type() {
+        assert_eq!(logical_type(oid::INT8), LogicalType::Integer);
+        assert_eq!(logical_type(oid::NUMERIC), LogicalType::Decimal);
+        assert_eq!(logical_type(oid::TIMESTAMPTZ), LogicalType::Timestamp);
+        assert_eq!(logical_type(oid::JSONB), LogicalType::Json);
+        assert_eq!(logical_type(oid::BYTEA), LogicalType::Binary);
+        assert_eq!(logical_type(oid::UUID), LogicalType::Text);
+    }
+
+    #[test]
+    fn test_unknown_types_and_text_format() {
+        assert_eq!(binary_to_json(99_999, b"citext value").unwrap(), json!("citext value"));
+        assert_eq!(binary_to_json(99_999, &[0, 1, 2]).unwrap(), json!("AAEC"));
+
+        assert_eq!(text_to_json(oid::INT4, "42"), json!(42));
+        assert_eq!(text_to_json(oid::BOOL, "t"), json!(true));
+        assert_eq!(text_to_json(oid::JSONB, "[1]"), json!([1]));
+        assert_eq!(text_to_json(oid::NUMERIC, "1.50"), json!("1.50"));
+    }
+}
@@ -0,0 +1,208 @@
+// Tracking of running queries, so they can be cancelled by id
+
+use crate::error::AppError;
+use futures_util::future::BoxFuture;
+use sqlx::pool::PoolConnection;
+use sqlx::{Database, Executor, IntoArguments};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Asks the database to stop one running query
+pub(crate) type CancelQuery = Arc<dyn Fn() -> BoxFuture<'static, Result<(), AppError>> + Send + Sync>;
+
+/// Queries currently running on one driver, keyed by query id
+#[derive(Clone, Default)]
+pub(crate) struct RunningQueries {
+    queries: Arc<Mutex<HashMap<String, CancelQuery>>>,
+}
+
+impl RunningQueries {
+    /// Record a query as running until the returned guard is dropped
+    pub(crate) fn register(&self, query_id: &str, cancel: CancelQuery) -> Result<RunningQueryGuard, AppError> {
+        let mut queries = self.lock()?;
+        if queries.contains_key(query_id) {
+            return Err(AppError::ValidationError(format!("Query '{query_id}' is already running")));
+        }
+        queries.insert(query_id.to_string(), Arc::clone(&cancel));
+
+        Ok(RunningQueryGuard {
+            running: self.clone(),
+            query_id: query_id.to_string(),
+            cancel: Some(cancel),
+        })
+    }
+
+    /// Cancel a running query, returning whether it was found
+    pub(crate) async fn cancel(&self, query_id: &str) -> Result<bool, AppError> {
+        let cancel = self.lock()?.get(query_id).cloned();
+        match cancel {
+            Some(cancel) => {
+                tracing::info!(query_id = %query_id, "cancelling query");
+                cancel().await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, HashMap<String, CancelQuery>>, AppError> {
+        self.queries.lock().map_err(|e| {
+            tracing::error!(error = ?e, "running queries mutex poisoned");
+            AppError::InternalError(format!("Failed to acquire lock: {e:?}"))
+        })
+    }
+}
+
+/// Registration of a running query
+///
+/// Dropping the guard before `finish` is called means the caller gave up on the
+/// query, e.g. because the client disconnected, so the query is cancelled.
+pub(crate) struct RunningQueryGuard {
+    running: RunningQueries,
+    query_id: String,
+    cancel: Option<CancelQuery>,
+}
+
+impl RunningQueryGuard {
+    /// Mark the query as completed, so dropping the guard does not cancel it
+    pub(crate) fn finish(mut self) {
+        self.cancel = None;
+    }
+}
+
+impl Drop for RunningQueryGuard {
+    fn drop(&mut self) {
+        if let Ok(mut queries) = self.running.queries.lock() {
+            queries.remove(&self.query_id);
+        }
+
+        let Some(cancel) = self.cancel.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let query_id = std::mem::take(&mut self.query_id);
+        tracing::info!(query_id = %query_id, "query abandoned, cancelling");
+        runtime.spawn(async move {
+            if let Err(e) = cancel().await {
+                tracing::warn!(query_id = %query_id, error = ?e, "failed to cancel abandoned query");
+            }
+        });
+    }
+}
+
+/// Pooled connection running a registered query
+///
+/// A connection whose query did not finish is closed instead of being returned
+/// to the pool, so a late cancel request cannot hit the next query on it.
+pub(crate) struct TrackedConnection<DB: Database> {
+    conn: PoolConnection<DB>,
+    guard: Option<RunningQueryGuard>,
+    /// Statements undoing the session state set up for the query, run by `finish`
+    cleanup: Vec<String>,
+}
+
+impl<DB: Database> TrackedConnection<DB>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    pub(crate) fn new(conn: PoolConnection<DB>, guard: RunningQueryGuard) -> Self {
+        Self { conn, guard: Some(guard), cleanup: Vec::new() }
+    }
+
+    /// Run `setup` before the query and `cleanup` once it completes
+    pub(crate) async fn prepare_session(&mut self, setup: &str, cleanup: &str) -> Result<(), AppError> {
+        sqlx::query(setup).execute(&mut *self.conn).await?;
+        self.cleanup.insert(0, cleanup.to_string());
+        Ok(())
+    }
+
+    /// Mark the query as completed and return the connection to the pool when dropped
+    ///
+    /// A connection whose session state cannot be cleaned up is closed instead.
+    pub(crate) async fn finish(&mut self) {
+        for sql in std::mem::take(&mut self.cleanup) {
+            if let Err(e) = sqlx::query(&sql).execute(&mut *self.conn).await {
+                tracing::warn!(error = ?e, statement = %sql, "failed to clean up connection after query, closing it");
+                self.conn.close_on_drop();
+                break;
+            }
+        }
+
+        if let Some(guard) = self.guard.take() {
+            guard.finish();
+        }
+    }
+}
+
+impl<DB: Database> Deref for TrackedConnection<DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl<DB: Database> DerefMut for TrackedConnection<DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+impl<DB: Database> Drop for TrackedConnection<DB> {
+    fn drop(&mut self) {
+        if self.guard.is_some() {
+            self.conn.close_on_drop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counting_cancel(count: &Arc<AtomicUsize>) -> CancelQuery {
+        let count = Arc::clone(count);
+        Arc::new(move || {
+            count.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(()) })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_query() {
+        let running = RunningQueries::default();
+        let cancelled = Arc::new(AtomicUsize::new(0));
+
+        let guard = running.register("q1", counting_cancel(&cancelled)).unwrap();
+        // Query ids are unique while the query runs
+        assert!(running.register("q1", counting_cancel(&cancelled)).is_err());
+
+        assert!(running.cancel("q1").await.unwrap());
+        assert!(!running.cancel("missing").await.unwrap());
+        assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+
+        // A finished query is forgotten and not cancelled again
+        guard.finish();
+        assert!(!running.cancel("q1").await.unwrap());
+        tokio::task::yield_now().await;
+        assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_query_is_cancelled() {
+        let running = RunningQueries::default();
+        let cancelled = Arc::new(AtomicUsize::new(0));
+
+        let guard = running.register("q1", counting_cancel(&cancelled)).unwrap();
+        drop(guard);
+        tokio::task::yield_now().await;
+
+        assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+        assert!(!running.cancel("q1").await.unwrap());
+    }
+}
@@ -0,0 +1,836 @@
+use crate::drivers::{
+    resolve_data_path, CancelQuery, DatabaseDriver, DatabaseKind, IntrospectedSchema, QueryRows, RunningQueries,
+};
+use crate::error::AppError;
+use crate::models::database::ConnectionSettings;
+use crate::models::query::{LogicalType, QueryColumn};
+use crate::models::schema::{
+    CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, IndexInfo, TableInfo, TableKind, UniqueConstraintInfo, ViewInfo,
+};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use rusqlite::types::{Type, ValueRef};
+use rusqlite::{Connection, OpenFlags};
+use sqlparser::ast::{ColumnOption, Statement, TableConstraint};
+use sqlparser::dialect::{Dialect, SQLiteDialect};
+use sqlparser::parser::Parser;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// `SQLite` driver for local database files, opened read-only through rusqlite
+pub struct SqliteDriver {
+    conn: Arc<Mutex<Connection>>,
+    running: RunningQueries,
+}
+
+/// Virtual machine instructions between checks for cancellation and timeout of a running query
+const CANCEL_CHECK_OPS: i32 = 1000;
+
+/// Schema of the database file itself; `SQLite` has no others unless databases are attached
+const MAIN_SCHEMA: &str = "main";
+
+impl SqliteDriver {
+    /// Open the database file referenced by a `sqlite://` URL
+    pub async fn connect(url: &str) -> Result<Self, AppError> {
+        let path = resolve_data_path(PathBuf::from(sqlite_path_from_url(url)?))?;
+        tracing::info!(path = %path.display(), "opening SQLite database (read-only)");
+
+        let conn = tokio::task::spawn_blocking(move || {
+            Connection::open_with_flags(
+                &path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .map_err(|e| {
+                tracing::error!(error = ?e, path = %path.display(), "failed to open SQLite database");
+                AppError::ConnectionError(format!("Failed to open SQLite database '{}': {e}", path.display()))
+            })
+        })
+        .await
+        .map_err(|e| AppError::InternalError(format!("SQLite task failed: {e}")))??;
+
+        Ok(Self::from_connection(conn))
+    }
+
+    /// Wrap an already opened connection
+    pub(crate) fn from_connection(conn: Connection) -> Self {
+        Self::with_running_queries(conn, RunningQueries::default())
+    }
+
+    /// Wrap an already opened connection, tracking its queries in `running`
+    pub(crate) fn with_running_queries(conn: Connection, running: RunningQueries) -> Self {
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+            running,
+        }
+    }
+
+    /// Run a blocking operation against the connection on the blocking thread pool
+    async fn with_connection<T, F>(&self, f: F) -> Result<T, AppError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, AppError> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock()
+                .map_err(|e| {
+                    tracing::error!(error = ?e, "SQLite mutex poisoned");
+                    AppError::DatabaseError(format!("Failed to acquire lock: {e:?}"))
+                })?;
+            f(&conn)
+        })
+        .await
+        .map_err(|e| AppError::InternalError(format!("SQLite task failed: {e}")))?
+    }
+}
+
+#[async_trait]
+impl DatabaseDriver for SqliteDriver {
+    fn kind(&self) -> DatabaseKind {
+        DatabaseKind::Sqlite
+    }
+
+    fn dialect(&self) -> &'static dyn Dialect {
+        &SQLiteDialect {}
+    }
+
+    async fn test_connection(&self) -> Result<(), AppError> {
+        self.with_connection(|conn| {
+            conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn introspect(&self, _settings: &ConnectionSettings) -> Result<IntrospectedSchema, AppError> {
+        self.with_connection(|conn| {
+            let mut tables = Vec::new();
+            for table_name in fetch_object_names(conn, "table")? {
+                let (columns, primary_key) = fetch_columns(conn, &table_name)?;
+                let row_count = fetch_table_row_count(conn, &table_name);
+                // SQLite has no comments, partitions or foreign tables
+                tables.push(TableInfo {
+                    schema: MAIN_SCHEMA.to_string(),
+                    kind: TableKind::Table,
+                    description: None,
+                    foreign_keys: fetch_foreign_keys(conn, &table_name)?,
+                    indexes: fetch_indexes(conn, &table_name)?,
+                    unique_constraints: fetch_unique_constraints(conn, &table_name)?,
+                    check_constraints: fetch_check_constraints(conn, &table_name)?,
+                    name: table_name,
+                    columns,
+                    primary_key: if primary_key.is_empty() { None } else { Some(primary_key) },
+                    row_count,
+                    row_count_estimated: false,
+                    statistics: None,
+                    partition_key: None,
+                    partition_bound: None,
+                    partitions: Vec::new(),
+                });
+            }
+
+            let mut views = Vec::new();
+            for view_name in fetch_object_names(conn, "view")? {
+                let (columns, _) = fetch_columns(conn, &view_name)?;
+                views.push(ViewInfo {
+                    schema: MAIN_SCHEMA.to_string(),
+                    description: None,
+                    definition: fetch_definition(conn, "view", &view_name)?,
+                    name: view_name,
+                    columns,
+                });
+            }
+
+            // SQLite has no materialized views, functions or sequences
+            Ok(IntrospectedSchema { tables, views, ..IntrospectedSchema::default() })
+        })
+        .await
+    }
+
+    async fn execute(&self, sql: &str, query_id: &str, settings: &ConnectionSettings) -> Result<QueryRows, AppError> {
+        // Cancelling only raises a flag, which the running statement checks periodically
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancelled);
+        let cancel: CancelQuery = Arc::new(move || {
+            flag.store(true, Ordering::SeqCst);
+            Box::pin(async { Ok(()) })
+        });
+        let guard = self.running.register(query_id, cancel)?;
+
+        let sql = sql.to_string();
+        let timeout_ms = settings.statement_timeout_ms;
+        // One row past the limit is enough for the caller to tell the result was cut short
+        let row_limit = settings.max_rows.saturating_add(1);
+        let result = self.with_connection(move |conn| {
+            let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+            let timed_out = Arc::new(AtomicBool::new(false));
+            let timeout_flag = Arc::clone(&timed_out);
+            let cancel_flag = Arc::clone(&cancelled);
+
+            // SQLite stops the statement with an "interrupted" error once the handler returns true
+            conn.progress_handler(
+                CANCEL_CHECK_OPS,
+                Some(move || {
+                    if Instant::now() >= deadline {
+                        timeout_flag.store(true, Ordering::SeqCst);
+                    }
+                    cancel_flag.load(Ordering::SeqCst) || timeout_flag.load(Ordering::SeqCst)
+                }),
+            );
+            // Like the server drivers, the query runs in a transaction that is rolled back
+            // when dropped; the connection itself is already read-only
+            let result = conn
+                .unchecked_transaction()
+                .map_err(AppError::from)
+                .and_then(|transaction| read_rows(&transaction, &sql, row_limit));
+            conn.progress_handler(0, None::<fn() -> bool>);
+
+            if result.is_err() && timed_out.load(Ordering::SeqCst) {
+                return Err(AppError::QueryTimeout(format!(
+                    "Query exceeded the statement timeout of {timeout_ms} ms"
+                )));
+            }
+            if result.is_err() && cancelled.load(Ordering::SeqCst) {
+                return Err(AppError::QueryCancelled("Query was cancelled".to_string()));
+            }
+            result
+        })
+        .await;
+
+        guard.finish();
+        result
+    }
+
+    async fn cancel(&self, query_id: &str) -> Result<bool, AppError> {
+        self.running.cancel(query_id).await
+    }
+
+    async fn close(&self) {
+        // The connection is closed when the last reference to the driver is dropped
+    }
+}
+
+impl SqliteDriver {
+    /// Queries running on this driver, to be taken over by a driver replacing it
+    pub(crate) fn running_queries(&self) -> RunningQueries {
+        self.running.clone()
+    }
+}
+
+/// Run a query and read up to `row_limit` of its rows with their column metadata
+///
+/// The whole result is held in memory, so rows past the limit are never read.
+fn read_rows(conn: &Connection, sql: &str, row_limit: usize) -> Result<QueryRows, AppError> {
+    let mut stmt = conn.prepare(sql)?;
+    // Column names and declared types are known from the prepared statement, even for empty results
+    let declared: Vec<(String, Option<String>)> = stmt
+        .columns()
+        .iter()
+        .map(|column| (column.name().to_string(), column.decl_type().map(str::to_string)))
+        .collect();
+    let column_count = declared.len();
+
+    // Expression columns have no declared type, so use the storage class of their first value
+    let mut storage_classes: Vec<Option<Type>> = vec![None; column_count];
+    let mut rows = Vec::new();
+    let mut result = stmt.query([])?;
+    while rows.len() < row_limit {
+        let Some(row) = result.next()? else {
+            break;
+        };
+        let mut values = Vec::with_capacity(column_count);
+        for (index, storage_class) in storage_classes.iter_mut().enumerate() {
+            let value = row.get_ref(index)?;
+            if storage_class.is_none() && !matches!(value, ValueRef::Null) {
+                *storage_class = Some(value.data_type());
+            }
+            values.push(value_to_json(value));
+        }
+        rows.push(values);
+    }
+
+    let columns = declared
+        .into_iter()
+        .zip(storage_classes)
+        .map(|((name, declared_type), storage_class)| {
+            let (db_type, logical_type) = match (declared_type, storage_class) {
+                (Some(declared_type), _) => {
+                    let logical_type = logical_type(&declared_type);
+                    (declared_type, logical_type)
+                }
+                (None, Some(storage_class)) => {
+                    (storage_class.to_string().to_uppercase(), storage_class_logical_type(storage_class))
+                }
+                (None, None) => (String::new(), LogicalType::Text),
+            };
+            QueryColumn { name, db_type, logical_type, nullable: None }
+        })
+        .collect();
+
+    Ok(QueryRows { columns, rows })
+}
+
+/// Extract the file path from a `sqlite://` URL, ignoring any query parameters
+fn sqlite_path_from_url(url: &str) -> Result<String, AppError> {
+    let path = url
+        .strip_prefix("sqlite://")
+        .unwrap_or(url)
+        .split('?')
+        .next()
+        .unwrap_or_default();
+
+    if path.is_empty() {
+        return Err(AppError::ValidationError(
+            "SQLite URL must include a database file path".to_string(),
+        ));
+    }
+
+    Ok(path.to_string())
+}
+
+/// List user tables or views from `sqlite_master`
+fn fetch_object_names(conn: &Connection, object_type: &str) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master
+         WHERE type = ?1 AND name NOT LIKE 'sqlite_%'
+         ORDER BY name"
+    )?;
+
+    let names = stmt
+        .query_map([object_type], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    Ok(names)
+}
+
+/// Fetch column definitions and primary key columns for a table or view
+fn fetch_columns(conn: &Connection, table_name: &str) -> Result<(Vec<ColumnInfo>, Vec<String>), AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT name, type, \"notnull\", dflt_value, pk
+         FROM pragma_table_info(?1)
+         ORDER BY cid"
+    )?;
+
+    let rows: Vec<(String, String, bool, Option<String>, i64)> = stmt
+        .query_map([table_name], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // pk holds the 1-based position of the column within the primary key
+    let mut primary_key: Vec<(i64, String)> = rows
+        .iter()
+        .filter(|(_, _, _, _, pk)| *pk > 0)
+        .map(|(name, _, _, _, pk)| (*pk, name.clone()))
+        .collect();
+    primary_key.sort();
+
+    let columns = rows
+        .into_iter()
+        .map(|(name, data_type, not_null, default_value, _)| {
+            ColumnInfo {
+                name,
+                data_type,
+                nullable: !not_null,
+                default_value,
+                description: None,
+                base_type: None,
+                enum_values: None,
+            }
+        })
+        .collect();
+
+    Ok((columns, primary_key.into_iter().map(|(_, name)| name).collect()))
+}
+
+/// Fetch the foreign keys of a table
+///
+/// `SQLite` does not name foreign keys, so they are named after the table and columns.
+fn fetch_foreign_keys(conn: &Connection, table_name: &str) -> Result<Vec<ForeignKeyInfo>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, \"table\", \"from\", \"to\", on_delete
+         FROM pragma_foreign_key_list(?1)
+         ORDER BY id, seq"
+    )?;
+
+    // One row per column of each key
+    let rows: Vec<(i64, String, String, Option<String>, String)> = stmt
+        .query_map([table_name], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut keys: Vec<(i64, ForeignKeyInfo)> = Vec::new();
+    for (id, referenced_table, column, referenced_column, on_delete) in rows {
+        match keys.last_mut() {
+            Some((key_id, foreign_key)) if *key_id == id => {
+                foreign_key.columns.push(column);
+                foreign_key.referenced_columns.extend(referenced_column);
+            }
+            _ => keys.push((id, ForeignKeyInfo {
+                name: String::new(),
+                columns: vec![column],
+                referenced_schema: MAIN_SCHEMA.to_string(),
+                referenced_table,
+                referenced_columns: referenced_column.into_iter().collect(),
+                on_delete,
+            })),
+        }
+    }
+
+    let mut foreign_keys = Vec::new();
+    for (_, mut foreign_key) in keys {
+        foreign_key.name = format!("{table_name}_{}_fkey", foreign_key.columns.join("_"));
+        // A key declared without referenced columns refers to the primary key
+        if foreign_key.referenced_columns.is_empty() {
+            foreign_key.referenced_columns = fetch_columns(conn, &foreign_key.referenced_table)?.1;
+        }
+        foreign_keys.push(foreign_key);
+    }
+
+    Ok(foreign_keys)
+}
+
+/// Fetch the indexes of a table, including those created for its constraints
+fn fetch_indexes(conn: &Connection, table_name: &str) -> Result<Vec<IndexInfo>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT l.name, l.\"unique\", m.sql
+         FROM pragma_index_list(?1) l
+         LEFT JOIN sqlite_master m ON m.type = 'index' AND m.name = l.name
+         ORDER BY l.name"
+    )?;
+
+    let rows: Vec<(String, bool, Option<String>)> = stmt
+        .query_map([table_name], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut indexes = Vec::new();
+    for (name, unique, sql) in rows {
+        // Indexes created with CREATE INDEX can index expressions and be partial;
+        // those created for constraints have no SQL and only index columns
+        let definition = sql.as_deref().and_then(parse_definition);
+        let (columns, predicate) = match definition {
+            Some(Statement::CreateIndex { columns, predicate, .. }) => (
+                columns.iter().map(|column| column.expr.to_string()).collect(),
+                predicate.map(|predicate| predicate.to_string()),
+            ),
+            _ => (fetch_index_columns(conn, &name)?, None),
+        };
+
+        indexes.push(IndexInfo {
+            name,
+            columns,
+            unique,
+            method: "btree".to_string(),
+            predicate,
+        });
+    }
+
+    Ok(indexes)
+}
+
+/// Fetch the unique constraints of a table from the indexes `SQLite` creates for them
+fn fetch_unique_constraints(conn: &Connection, table_name: &str) -> Result<Vec<UniqueConstraintInfo>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM pragma_index_list(?1) WHERE origin = 'u' ORDER BY name"
+    )?;
+
+    let names = stmt
+        .query_map([table_name], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    names
+        .into_iter()
+        .map(|name| {
+            let columns = fetch_index_columns(conn, &name)?;
+            Ok(UniqueConstraintInfo { name, columns })
+        })
+        .collect()
+}
+
+/// Fetch the check constraints declared in a table's `CREATE TABLE` statement
+///
+/// Unnamed constraints are named after the table and their position.
+fn fetch_check_constraints(conn: &Connection, table_name: &str) -> Result<Vec<CheckConstraintInfo>, AppError> {
+    let sql = fetch_definition(conn, "table", table_name)?;
+    let Some(Statement::CreateTable { columns, constraints, .. }) = sql.as_deref().and_then(parse_definition) else {
+        return Ok(Vec::new());
+    };
+
+    let column_checks = columns.iter().flat_map(|column| &column.options).filter_map(|option| {
+        match &option.option {
+            ColumnOption::Check(expr) => Some((option.name.as_ref(), expr)),
+            _ => None,
+        }
+    });
+    let table_checks = constraints.iter().filter_map(|constraint| match constraint {
+        TableConstraint::Check { name, expr } => Some((name.as_ref(), expr.as_ref())),
+        _ => None,
+    });
+
+    Ok(column_checks
+        .chain(table_checks)
+        .enumerate()
+        .map(|(index, (name, expr))| CheckConstraintInfo {
+            name: name.map_or_else(|| format!("{table_name}_check{}", index + 1), |name| name.value.clone()),
+            expression: expr.to_string(),
+        })
+        .collect())
+}
+
+/// Names of the columns covered by an index, in index order
+fn fetch_index_columns(conn: &Connection, index_name: &str) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM pragma_index_info(?1) ORDER BY seqno"
+    )?;
+
+    // Expression key parts have no column name
+    let columns = stmt
+        .query_map([index_name], |row| row.get::<_, Option<String>>(0))?
+        .map(|name| name.map(|name| name.unwrap_or_else(|| "(expression)".to_string())))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(columns)
+}
+
+/// `CREATE` statement of a table or view, as stored in `sqlite_master`
+fn fetch_definition(conn: &Connection, object_type: &str, name: &str) -> Result<Option<String>, AppError> {
+    let sql = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = ?1 AND name = ?2",
+        [object_type, name],
+        |row| row.get(0),
+    )?;
+
+    Ok(sql)
+}
+
+/// Parse a `CREATE` statement stored in `sqlite_master`
+fn parse_definition(sql: &str) -> Option<Statement> {
+    match Parser::parse_sql(&SQLiteDialect {}, sql) {
+        Ok(mut statements) if statements.len() == 1 => statements.pop(),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::debug!(error = %e, sql = %sql, "failed to parse schema definition");
+            None
+        }
+    }
+}
+
+/// Fetch row count for a table
+fn fetch_table_row_count(conn: &Connection, table_name: &str) -> Option<u64> {
+    let sql = format!("SELECT COUNT(*) FROM {}", quote_identifier(table_name));
+    match conn.query_row(&sql, [], |row| row.get::<_, i64>(0)) {
+        Ok(count) => u64::try_from(count).ok(),
+        Err(e) => {
+            tracing::warn!(
+                table = %table_name,
+                error = ?e,
+                "failed to get row count"
+            );
+            None
+        }
+    }
+}
+
+/// Quote a `SQLite` identifier with double quotes, escaping embedded quotes
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Logical type of a declared column type, following `SQLite` type affinity rules
+pub(crate) fn logical_type(declared_type: &str) -> LogicalType {
+    let declared_type = declared_type.to_uppercase();
+    let contains_any = |patterns: &[&str]| patterns.iter().any(|p| declared_type.contains(p));
+
+    if contains_any(&["BOOL"]) {
+        LogicalType::Boolean
+    } else if contains_any(&["INT"]) {
+        LogicalType::Integer
+    } else if contains_any(&["CHAR", "CLOB", "TEXT"]) {
+        LogicalType::Text
+    } else if contains_any(&["BLOB"]) {
+        LogicalType::Binary
+    } else if contains_any(&["JSON"]) {
+        LogicalType::Json
+    } else if contains_any(&["DATE", "TIME"]) {
+        LogicalType::Timestamp
+    } else if contains_any(&["REAL", "FLOA", "DOUB", "NUM", "DEC"]) {
+        LogicalType::Decimal
+    } else {
+        LogicalType::Text
+    }
+}
+
+/// Logical type of a value's storage class
+fn storage_class_logical_type(storage_class: Type) -> LogicalType {
+    match storage_class {
+        Type::Integer => LogicalType::Integer,
+        Type::Real => LogicalType::Decimal,
+        Type::Blob => LogicalType::Binary,
+        Type::Text | Type::Null => LogicalType::Text,
+    }
+}
+
+/// Convert a `SQLite` value to JSON using its storage class, with BLOBs as base64
+fn value_to_json(value: ValueRef<'_>) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number),
+        ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::String(BASE64.encode(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::query_executor::QueryExecutor;
+    use crate::services::sql_validator::{validate_sql, ValidatedSql};
+
+    /// Query that never finishes on its own
+    const ENDLESS_QUERY: &str = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT count(*) FROM n";
+
+    /// Create a small `SQLite` database file and return its path
+    fn setup_test_file(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("db_query_sqlite_driver_{name}.db"));
+        let _ = std::fs::remove_file(&path);
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, score REAL, avatar BLOB);
+             INSERT INTO users (name, score) VALUES ('alice', 9.5), ('bob', NULL);
+             CREATE VIEW user_names AS SELECT name FROM users;"
+        ).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn test_sqlite_path_from_url() {
+        assert_eq!(sqlite_path_from_url("sqlite:///tmp/app.db").unwrap(), "/tmp/app.db");
+        assert_eq!(sqlite_path_from_url("sqlite://data/app.db?mode=ro").unwrap(), "data/app.db");
+        assert!(sqlite_path_from_url("sqlite://").is_err());
+    }
+
+    #[test]
+    fn test_logical_type() {
+        assert_eq!(logical_type("BIGINT"), LogicalType::Integer);
+        assert_eq!(logical_type("varchar(20)"), LogicalType::Text);
+        assert_eq!(logical_type("DECIMAL(10,2)"), LogicalType::Decimal);
+        assert_eq!(logical_type("DATETIME"), LogicalType::Timestamp);
+        assert_eq!(logical_type("BOOLEAN"), LogicalType::Boolean);
+        assert_eq!(logical_type("JSON"), LogicalType::Json);
+        assert_eq!(logical_type("BLOB"), LogicalType::Binary);
+    }
+
+    #[test]
+    fn test_blob_as_base64() {
+        assert_eq!(value_to_json(ValueRef::Blob(&[0xff, 0x00, 0x41])), serde_json::json!("/wBB"));
+        assert_eq!(value_to_json(ValueRef::Text(b"alice")), serde_json::json!("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_is_connection_error() {
+        let path = std::env::temp_dir().join("db_query_sqlite_driver_missing.db");
+        let _ = std::fs::remove_file(&path);
+
+        let result = SqliteDriver::connect(&format!("sqlite://{}", path.display())).await;
+        assert!(matches!(result, Err(AppError::ConnectionError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_introspect() {
+        let path = setup_test_file("introspect");
+        let driver = SqliteDriver::connect(&format!("sqlite://{}", path.display())).await.unwrap();
+
+        driver.test_connection().await.unwrap();
+        let schema = driver.introspect(&ConnectionSettings::default()).await.unwrap();
+
+        assert_eq!(schema.tables.len(), 1);
+        let users = &schema.tables[0];
+        assert_eq!(users.name, "users");
+        assert_eq!(users.qualified_name(), "main.users");
+        assert_eq!(users.primary_key, Some(vec!["id".to_string()]));
+        assert_eq!(users.row_count, Some(2));
+        assert_eq!(users.columns[1].name, "name");
+        assert_eq!(users.columns[1].data_type, "TEXT");
+        assert!(!users.columns[1].nullable);
+
+        assert_eq!(schema.views.len(), 1);
+        assert_eq!(schema.views[0].name, "user_names");
+        assert_eq!(
+            schema.views[0].definition.as_deref(),
+            Some("CREATE VIEW user_names AS SELECT name FROM users")
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_introspect_constraints() {
+        let path = std::env::temp_dir().join("db_query_sqlite_driver_constraints.db");
+        let _ = std::fs::remove_file(&path);
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE customers (id INTEGER PRIMARY KEY, email TEXT UNIQUE);
+             CREATE TABLE orders (
+                 id INTEGER PRIMARY KEY,
+                 customer_id INTEGER REFERENCES customers ON DELETE CASCADE,
+                 total REAL CHECK (total >= 0),
+                 status TEXT,
+                 CONSTRAINT known_status CHECK (status IN ('open', 'paid'))
+             );
+             CREATE INDEX orders_open ON orders (customer_id, lower(status)) WHERE status = 'open';"
+        ).unwrap();
+        drop(conn);
+
+        let driver = SqliteDriver::connect(&format!("sqlite://{}", path.display())).await.unwrap();
+        let schema = driver.introspect(&ConnectionSettings::default()).await.unwrap();
+        let customers = schema.tables.iter().find(|t| t.name == "customers").unwrap();
+        let orders = schema.tables.iter().find(|t| t.name == "orders").unwrap();
+
+        // The key refers to the primary key of customers
+        assert_eq!(orders.foreign_keys.len(), 1);
+        let foreign_key = &orders.foreign_keys[0];
+        assert_eq!(foreign_key.columns, vec!["customer_id"]);
+        assert_eq!(foreign_key.referenced_table, "customers");
+        assert_eq!(foreign_key.referenced_columns, vec!["id"]);
+        assert_eq!(foreign_key.on_delete, "CASCADE");
+
+        assert_eq!(orders.indexes.len(), 1);
+        let index = &orders.indexes[0];
+        assert_eq!(index.name, "orders_open");
+        assert_eq!(index.columns, vec!["customer_id", "lower(status)"]);
+        assert!(!index.unique);
+        assert_eq!(index.predicate.as_deref(), Some("status = 'open'"));
+
+        let checks: Vec<(&str, &str)> = orders
+            .check_constraints
+            .iter()
+            .map(|c| (c.name.as_str(), c.expression.as_str()))
+            .collect();
+        assert_eq!(checks, vec![("orders_check1", "total >= 0"), ("known_status", "status IN ('open', 'paid')")]);
+
+        assert_eq!(customers.unique_constraints.len(), 1);
+        assert_eq!(customers.unique_constraints[0].columns, vec!["email"]);
+        assert!(customers.indexes[0].unique);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_execute_validated_query() {
+        let path = setup_test_file("execute");
+        let driver = SqliteDriver::connect(&format!("sqlite://{}", path.display())).await.unwrap();
+
+        let sql = validate_sql("SELECT id, name, score FROM users ORDER BY id", driver.dialect(), &ConnectionSettings::default()).unwrap();
+        let response = QueryExecutor::execute_query(&driver, &sql, "test", &ConnectionSettings::default()).await.unwrap();
+
+        let names: Vec<&str> = response.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "name", "score"]);
+        assert_eq!(response.columns[0].db_type, "INTEGER");
+        assert_eq!(response.columns[0].logical_type, LogicalType::Integer);
+        assert_eq!(response.columns[2].logical_type, LogicalType::Decimal);
+        assert_eq!(response.row_count, 2);
+        assert_eq!(response.rows[0], vec![serde_json::json!(1), serde_json::json!("alice"), serde_json::json!(9.5)]);
+        assert_eq!(response.rows[1][2], serde_json::Value::Null);
+
+        // Column names are returned even when no rows match
+        let empty = driver.execute("SELECT id, name FROM users WHERE id < 0", "test", &ConnectionSettings::default()).await.unwrap();
+        let names: Vec<&str> = empty.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "name"]);
+        assert_eq!(empty.columns[1].logical_type, LogicalType::Text);
+        assert!(empty.rows.is_empty());
+
+        // Expression columns take the type of their values
+        let computed = driver.execute("SELECT count(*) AS total, avg(score) AS average FROM users", "test", &ConnectionSettings::default()).await.unwrap();
+        assert_eq!(computed.columns[0].db_type, "INTEGER");
+        assert_eq!(computed.columns[0].logical_type, LogicalType::Integer);
+        assert_eq!(computed.columns[1].logical_type, LogicalType::Decimal);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_rejected() {
+        let path = setup_test_file("read_only");
+        let driver = SqliteDriver::connect(&format!("sqlite://{}", path.display())).await.unwrap();
+
+        // The file is opened read-only, so even unvalidated writes fail
+        assert!(driver.execute("DELETE FROM users", "test", &ConnectionSettings::default()).await.is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_rows_past_max_rows_are_not_read() {
+        let conn = Connection::open_in_memory().unwrap();
+        let driver = SqliteDriver::from_connection(conn);
+        let settings = ConnectionSettings { max_rows: 10, ..ConnectionSettings::default() };
+        let sql = ValidatedSql {
+            sql: "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) SELECT i FROM n".to_string(),
+            clamped: false,
+        };
+
+        // An endless result stops one row past the limit
+        let result = driver.execute(&sql.sql, "test", &settings).await.unwrap();
+        assert_eq!(result.rows.len(), 11);
+
+        let response = QueryExecutor::execute_query(&driver, &sql, "test", &settings).await.unwrap();
+        assert_eq!(response.row_count, 10);
+        assert!(response.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_query() {
+        let conn = Connection::open_in_memory().unwrap();
+        let driver = Arc::new(SqliteDriver::from_connection(conn));
+        assert!(!driver.cancel("endless").await.unwrap());
+
+        let running = Arc::clone(&driver);
+        let query = tokio::spawn(async move {
+            running
+                .execute(ENDLESS_QUERY, "endless", &ConnectionSettings::default())
+                .await
+        });
+
+        // Wait for the query to start, then interrupt it
+        while !driver.cancel("endless").await.unwrap() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let result = tokio::time::timeout(std::time::Duration::from_secs(10), query).await.unwrap().unwrap();
+        assert!(matches!(result, Err(AppError::QueryCancelled(_))));
+
+        // The connection remains usable for the next query
+        let next = driver.execute("SELECT 1", "next", &ConnectionSettings::default()).await.unwrap();
+        assert_eq!(next.rows, vec![vec![serde_json::json!(1)]]);
+    }
+
+    #[tokio::test]
+    async fn test_statement_timeout() {
+        let conn = Connection::open_in_memory().unwrap();
+        let driver = SqliteDriver::from_connection(conn);
+        let settings = ConnectionSettings { statement_timeout_ms: 50, ..ConnectionSettings::default() };
+
+        let result = driver.execute(ENDLESS_QUERY, "slow", &settings).await;
+        assert!(matches!(result, Err(AppError::QueryTimeout(message)) if message.contains("statement timeout")));
+    }
+}
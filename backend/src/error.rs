@@ -3,7 +3,10 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use serde::Serialize;
 use serde_json::json;
+use sqlx::mysql::MySqlDatabaseError;
+use sqlx::postgres::{PgDatabaseError, PgErrorPosition};
 
 #[derive(Debug)]
 pub enum AppError {
@@ -12,44 +15,173 @@ pub enum AppError {
     NotFound(String),
     InternalError(String),
     ConnectionError(String),
+    /// SQL that failed to parse, with the location of the offending token
+    InvalidSql(String, Box<SqlDiagnostics>),
+    /// Error reported by the target database for a query
+    QueryFailed(String, Box<SqlDiagnostics>),
+    /// No stored connection has the requested name
+    ConnectionNotFound(String),
+    /// The LLM API failed or returned an unusable answer
+    LlmError(String),
+    /// The query ran longer than the connection's statement timeout
+    QueryTimeout(String),
+    /// The query was stopped by a cancel request
+    QueryCancelled(String),
+    /// The database denied access, e.g. to a table the connection's role cannot read
+    PermissionDenied(String),
+    /// Too many queries or connections at once
+    LimitExceeded(String),
+    /// The connection URL is for a database this server has no driver for
+    UnsupportedDriver(String),
+}
+
+/// Where and why a SQL statement failed, for the editor to mark the problem
+///
+/// Lines and columns start at 1; `position` is the 1-based character offset
+/// into the SQL, as `PostgreSQL` reports it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SqlDiagnostics {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sqlstate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl SqlDiagnostics {
+    /// Diagnostics of a sqlparser error, whose message ends with "at Line: L, Column C"
+    #[must_use]
+    pub fn from_parser_message(sql: &str, message: &str) -> Self {
+        let location = message.rfind(" at Line: ").and_then(|start| {
+            let (line, column) = message[start + " at Line: ".len()..].split_once(", Column")?;
+            let column = column.trim_start_matches(':').trim();
+            Some((line.trim().parse().ok()?, column.parse().ok()?))
+        });
+
+        let mut diagnostics = Self::default();
+        if let Some((line, column)) = location {
+            diagnostics.line = Some(line);
+            diagnostics.column = Some(column);
+            diagnostics.position = char_position(sql, line, column);
+        }
+        diagnostics
+    }
+
+    /// Fill in the line and column of a known position in `sql`
+    fn locate_in(&mut self, sql: &str) {
+        let Some(position) = self.position else {
+            return;
+        };
+        if self.line.is_some() {
+            return;
+        }
+
+        let before = sql.chars().take(position.saturating_sub(1));
+        let (line, column) = before.fold((1, 1), |(line, column), c| {
+            if c == '\n' { (line + 1, 1) } else { (line, column + 1) }
+        });
+        self.line = Some(line);
+        self.column = Some(column);
+    }
+}
+
+/// 1-based character offset of a line and column in `sql`
+fn char_position(sql: &str, line: usize, column: usize) -> Option<usize> {
+    let mut position = 1;
+    for (index, text) in sql.split('\n').enumerate() {
+        if index + 1 == line {
+            return (column <= text.chars().count() + 1).then_some(position + column - 1);
+        }
+        position += text.chars().count() + 1;
+    }
+    None
+}
+
+impl AppError {
+    /// HTTP status and machine readable code for this error
+    fn status_and_code(&self) -> (StatusCode, &'static str) {
+        match self {
+            AppError::DatabaseError(_) | AppError::QueryFailed(..) => (StatusCode::INTERNAL_SERVER_ERROR, "DATABASE_ERROR"),
+            AppError::ValidationError(_) | AppError::InvalidSql(..) => (StatusCode::BAD_REQUEST, "VALIDATION_ERROR"),
+            AppError::NotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND"),
+            AppError::InternalError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
+            AppError::ConnectionError(_) => (StatusCode::BAD_REQUEST, "CONNECTION_ERROR"),
+            AppError::ConnectionNotFound(_) => (StatusCode::NOT_FOUND, "CONNECTION_NOT_FOUND"),
+            AppError::LlmError(_) => (StatusCode::BAD_GATEWAY, "LLM_UPSTREAM_ERROR"),
+            AppError::QueryTimeout(_) => (StatusCode::GATEWAY_TIMEOUT, "QUERY_TIMEOUT"),
+            AppError::QueryCancelled(_) => (StatusCode::CONFLICT, "QUERY_CANCELLED"),
+            AppError::PermissionDenied(_) => (StatusCode::FORBIDDEN, "PERMISSION_DENIED"),
+            AppError::LimitExceeded(_) => (StatusCode::TOO_MANY_REQUESTS, "LIMIT_EXCEEDED"),
+            AppError::UnsupportedDriver(_) => (StatusCode::BAD_REQUEST, "UNSUPPORTED_DRIVER"),
+        }
+    }
+
+    /// Machine readable error code, as returned in the `code` field
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        self.status_and_code().1
+    }
+
+    /// Human readable error message
+    #[must_use]
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::DatabaseError(msg)
+            | AppError::ValidationError(msg)
+            | AppError::NotFound(msg)
+            | AppError::InternalError(msg)
+            | AppError::ConnectionError(msg)
+            | AppError::ConnectionNotFound(msg)
+            | AppError::LlmError(msg)
+            | AppError::QueryTimeout(msg)
+            | AppError::QueryCancelled(msg)
+            | AppError::PermissionDenied(msg)
+            | AppError::LimitExceeded(msg)
+            | AppError::UnsupportedDriver(msg)
+            | AppError::InvalidSql(msg, _)
+            | AppError::QueryFailed(msg, _) => msg,
+        }
+    }
+
+    /// Location and details of the SQL problem behind this error, if known
+    #[must_use]
+    pub fn diagnostics(&self) -> Option<&SqlDiagnostics> {
+        match self {
+            AppError::InvalidSql(_, diagnostics) | AppError::QueryFailed(_, diagnostics) => Some(diagnostics),
+            _ => None,
+        }
+    }
+
+    /// Resolve the line and column of a database reported position in the executed `sql`
+    #[must_use]
+    pub fn locate_in(mut self, sql: &str) -> Self {
+        if let AppError::QueryFailed(_, diagnostics) = &mut self {
+            diagnostics.locate_in(sql);
+        }
+        self
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message, code) = match self {
-            AppError::DatabaseError(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                msg,
-                Some("DATABASE_ERROR".to_string()),
-            ),
-            AppError::ValidationError(msg) => (
-                StatusCode::BAD_REQUEST,
-                msg,
-                Some("VALIDATION_ERROR".to_string()),
-            ),
-            AppError::NotFound(msg) => (
-                StatusCode::NOT_FOUND,
-                msg,
-                Some("NOT_FOUND".to_string()),
-            ),
-            AppError::InternalError(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                msg,
-                Some("INTERNAL_ERROR".to_string()),
-            ),
-            AppError::ConnectionError(msg) => (
-                StatusCode::BAD_REQUEST,
-                msg,
-                Some("CONNECTION_ERROR".to_string()),
-            ),
-        };
+        let (status, code) = self.status_and_code();
 
         let mut body = json!({
-            "error": error_message,
+            "error": self.message(),
+            "code": code,
         });
-        
-        if let Some(code) = code {
-            body["code"] = json!(code);
+        if let (Some(fields), Some(Ok(serde_json::Value::Object(diagnostics)))) =
+            (body.as_object_mut(), self.diagnostics().map(serde_json::to_value))
+        {
+            fields.extend(diagnostics);
         }
 
         (status, Json(body)).into_response()
@@ -58,8 +190,19 @@ impl IntoResponse for AppError {
 
 impl From<rusqlite::Error> for AppError {
     fn from(err: rusqlite::Error) -> Self {
+        if matches!(err, rusqlite::Error::QueryReturnedNoRows) {
+            return AppError::NotFound("Record not found".to_string());
+        }
+
         tracing::error!("[AppError] SQLite error: {:?}", err);
-        AppError::DatabaseError(err.to_string())
+        match err.sqlite_error_code() {
+            Some(
+                rusqlite::ErrorCode::ReadOnly
+                | rusqlite::ErrorCode::PermissionDenied
+                | rusqlite::ErrorCode::AuthorizationForStatementDenied,
+            ) => AppError::PermissionDenied(err.to_string()),
+            _ => AppError::DatabaseError(err.to_string()),
+        }
     }
 }
 
@@ -69,6 +212,30 @@ impl From<sqlx::Error> for AppError {
         if matches!(err, sqlx::Error::PoolClosed | sqlx::Error::Io(_)) {
             tracing::error!("[AppError] SQLx connection error: {:?}", err);
             AppError::ConnectionError(err.to_string())
+        } else if matches!(err, sqlx::Error::PoolTimedOut) {
+            tracing::warn!("[AppError] SQLx pool timed out: {:?}", err);
+            AppError::LimitExceeded(
+                "All connections to the database are busy. Please try again later.".to_string(),
+            )
+        } else if let sqlx::Error::Database(db_err) = &err {
+            tracing::error!("[AppError] SQLx database error: {:?}", err);
+            if let Some(error) = classify_database_error(db_err.as_ref()) {
+                return error;
+            }
+
+            let mut diagnostics = SqlDiagnostics {
+                sqlstate: db_err.code().map(|code| code.to_string()),
+                ..SqlDiagnostics::default()
+            };
+            if let Some(pg_err) = db_err.try_downcast_ref::<PgDatabaseError>() {
+                diagnostics.hint = pg_err.hint().map(ToString::to_string);
+                diagnostics.detail = pg_err.detail().map(ToString::to_string);
+                // Positions inside internally generated queries, e.g. of a function body, are not in the user's SQL
+                if let Some(PgErrorPosition::Original(position)) = pg_err.position() {
+                    diagnostics.position = Some(position);
+                }
+            }
+            AppError::QueryFailed(err.to_string(), Box::new(diagnostics))
         } else {
             tracing::error!("[AppError] SQLx database error: {:?}", err);
             AppError::DatabaseError(err.to_string())
@@ -76,6 +243,35 @@ impl From<sqlx::Error> for AppError {
     }
 }
 
+/// Error for database errors with a dedicated code, from the SQLSTATE or `MySQL` error number
+fn classify_database_error(db_err: &dyn sqlx::error::DatabaseError) -> Option<AppError> {
+    let message = db_err.message().to_string();
+
+    // MySQL reports most of these with the generic SQLSTATE HY000
+    if let Some(mysql_err) = db_err.try_downcast_ref::<MySqlDatabaseError>() {
+        return match mysql_err.number() {
+            // ER_QUERY_TIMEOUT, and MariaDB's ER_STATEMENT_TIMEOUT
+            3024 | 1969 => Some(AppError::QueryTimeout(message)),
+            // ER_QUERY_INTERRUPTED, raised by KILL QUERY
+            1317 => Some(AppError::QueryCancelled(message)),
+            // ER_DBACCESS_DENIED_ERROR, ER_TABLEACCESS_DENIED_ERROR, ER_COLUMNACCESS_DENIED_ERROR, ER_SPECIFIC_ACCESS_DENIED_ERROR
+            1044 | 1142 | 1143 | 1227 => Some(AppError::PermissionDenied(message)),
+            // ER_CON_COUNT_ERROR, ER_USER_LIMIT_REACHED
+            1040 | 1226 => Some(AppError::LimitExceeded(message)),
+            _ => None,
+        };
+    }
+
+    match db_err.code().as_deref() {
+        // query_canceled covers both statement timeouts and pg_cancel_backend
+        Some("57014") if message.contains("statement timeout") => Some(AppError::QueryTimeout(message)),
+        Some("57014") => Some(AppError::QueryCancelled(message)),
+        Some("42501") => Some(AppError::PermissionDenied(message)),
+        Some("53300") => Some(AppError::LimitExceeded(message)),
+        _ => None,
+    }
+}
+
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
         AppError::InternalError(err.to_string())
@@ -88,3 +284,56 @@ impl From<serde_json::Error> for AppError {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser_error_location() {
+        let sql = "SELECT *\nFROM users\nWHERE id = = 1";
+        let message = "sql parser error: Expected an expression:, found: = at Line: 3, Column 12";
+        let diagnostics = SqlDiagnostics::from_parser_message(sql, message);
+        assert_eq!(diagnostics.line, Some(3));
+        assert_eq!(diagnostics.column, Some(12));
+        assert_eq!(diagnostics.position, Some(32));
+
+        // Messages without a location give no position
+        assert_eq!(SqlDiagnostics::from_parser_message(sql, "sql parser error: recursion limit exceeded"), SqlDiagnostics::default());
+    }
+
+    #[test]
+    fn test_locate_database_position() {
+        let diagnostics = SqlDiagnostics {
+            position: Some(32),
+            sqlstate: Some("42601".to_string()),
+            ..SqlDiagnostics::default()
+        };
+        let error = AppError::QueryFailed("syntax error".to_string(), Box::new(diagnostics))
+            .locate_in("SELECT *\nFROM users\nWHERE id = = 1");
+
+        let diagnostics = error.diagnostics().unwrap();
+        assert_eq!((diagnostics.line, diagnostics.column), (Some(3), Some(12)));
+        assert_eq!(error.code(), "DATABASE_ERROR");
+    }
+
+    #[test]
+    fn test_error_status_and_code() {
+        let cases = [
+            (AppError::ConnectionNotFound("missing".to_string()), StatusCode::NOT_FOUND, "CONNECTION_NOT_FOUND"),
+            (AppError::LlmError("bad gateway".to_string()), StatusCode::BAD_GATEWAY, "LLM_UPSTREAM_ERROR"),
+            (AppError::QueryTimeout("slow".to_string()), StatusCode::GATEWAY_TIMEOUT, "QUERY_TIMEOUT"),
+            (AppError::QueryCancelled("stopped".to_string()), StatusCode::CONFLICT, "QUERY_CANCELLED"),
+            (AppError::PermissionDenied("denied".to_string()), StatusCode::FORBIDDEN, "PERMISSION_DENIED"),
+            (AppError::LimitExceeded("busy".to_string()), StatusCode::TOO_MANY_REQUESTS, "LIMIT_EXCEEDED"),
+            (AppError::UnsupportedDriver("redis".to_string()), StatusCode::BAD_REQUEST, "UNSUPPORTED_DRIVER"),
+        ];
+
+        for (error, status, code) in cases {
+            assert_eq!(error.code(), code);
+            assert_eq!(error.into_response().status(), status);
+        }
+
+        assert!(matches!(AppError::from(rusqlite::Error::QueryReturnedNoRows), AppError::NotFound(_)));
+        assert!(matches!(AppError::from(sqlx::Error::PoolTimedOut), AppError::LimitExceeded(_)));
+    }
+}
@@ -3,6 +3,7 @@
 pub mod config;
 pub mod error;
 pub mod db;
+pub mod drivers;
 pub mod models;
 pub mod services;
 pub mod api;
@@ -6,8 +6,10 @@ use axum::{
 use tower_http::cors::{CorsLayer, Any};
 use tokio::net::TcpListener;
 use std::sync::Arc;
+use std::time::Duration;
 
 mod db;
+mod drivers;
 mod error;
 mod config;
 mod api;
@@ -16,14 +18,15 @@ mod services;
 mod utils;
 mod types;
 
-use api::databases::{list_databases, get_database_metadata, upsert_database, delete_database};
-use api::queries::{execute_query, execute_natural_language_query};
+use api::databases::{list_databases, get_database_metadata, upsert_database, delete_database, count_table_rows, refresh_database_schema, list_schema_snapshots, diff_schema_snapshots, diff_database_schemas};
+use api::queries::{execute_query, execute_query_stream, fetch_query_page, execute_natural_language_query, cancel_query};
 use db::init_db;
 use config::Config;
 use services::database_service::DatabaseService;
 use services::schema_service::SchemaService;
 use services::llm_service::LLMService;
-use types::{SharedDatabaseService, SharedSchemaService, SharedLLMService, ConnectionPoolCache, SharedConnectionPoolCache};
+use services::query_cursor::QueryCursorStore;
+use types::{SharedDatabaseService, SharedSchemaService, SharedLLMService, ConnectionPoolCache, SharedConnectionPoolCache, SharedQueryCursorStore};
 
 #[tokio::main]
 async fn main() {
@@ -39,6 +42,9 @@ async fn main() {
     // Load configuration
     let config = Config::from_env().expect("Failed to load configuration");
 
+    // Only let file:// and sqlite:// connections open files under the configured root
+    drivers::set_data_root(std::path::PathBuf::from(&config.file_data_root));
+
     // Initialize SQLite database
     let sqlite_conn = init_db(&config.sqlite_db_path)
         .expect("Failed to initialize SQLite database");
@@ -49,8 +55,24 @@ async fn main() {
     // Create database service
     let db_service: SharedDatabaseService = Arc::new(DatabaseService::new(sqlite_conn.clone()));
 
+    // Create connection pool cache
+    let pool_cache: SharedConnectionPoolCache = Arc::new(ConnectionPoolCache::new());
+
     // Create schema service
-    let schema_service: SharedSchemaService = Arc::new(SchemaService::new(sqlite_conn, db_service.clone()));
+    let schema_service: SharedSchemaService =
+        Arc::new(SchemaService::new(sqlite_conn, db_service.clone(), pool_cache.clone()));
+
+    // Read schemas whose cache outlived their connection's TTL in the background
+    let refresh_schemas = schema_service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = refresh_schemas.refresh_expired().await {
+                tracing::error!(error = ?e, "failed to refresh expired schemas");
+            }
+        }
+    });
 
     // Create LLM service
     let llm_service: SharedLLMService = Arc::new(LLMService::new(
@@ -58,8 +80,18 @@ async fn main() {
         config.llm_api_url.clone(),
     ));
 
-    // Create connection pool cache
-    let pool_cache: SharedConnectionPoolCache = Arc::new(ConnectionPoolCache::new());
+    // Create query cursor store, closing idle cursors in the background
+    let cursors: SharedQueryCursorStore = Arc::new(QueryCursorStore::new());
+    let expiry_cursors = cursors.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = expiry_cursors.remove_expired() {
+                tracing::error!(error = ?e, "failed to remove expired query cursors");
+            }
+        }
+    });
 
     // Configure CORS to allow all origins
     let cors = CorsLayer::new()
@@ -74,9 +106,17 @@ async fn main() {
         .route("/api/v1/dbs/{name}", get(get_database_metadata))
         .route("/api/v1/dbs/{name}", put(upsert_database))
         .route("/api/v1/dbs/{name}", delete(delete_database))
+        .route("/api/v1/dbs/{name}/schema/refresh", post(refresh_database_schema))
+        .route("/api/v1/dbs/{name}/schema/snapshots", get(list_schema_snapshots))
+        .route("/api/v1/dbs/{name}/schema/diff", get(diff_schema_snapshots))
+        .route("/api/v1/dbs/{name}/tables/{schema}/{table}/row-count", get(count_table_rows))
         .route("/api/v1/dbs/{name}/query", post(execute_query))
+        .route("/api/v1/dbs/{name}/query/stream", post(execute_query_stream))
         .route("/api/v1/dbs/{name}/query/natural", post(execute_natural_language_query))
-        .with_state((db_service, schema_service, llm_service, pool_cache))
+        .route("/api/v1/dbs/{name}/query/{cursor}", get(fetch_query_page))
+        .route("/api/v1/dbs/{name}/queries/{id}", delete(cancel_query))
+        .route("/api/v1/schema-diff", get(diff_database_schemas))
+        .with_state((db_service, schema_service, llm_service, pool_cache, cursors))
         .layer(cors);
 
     // Start server
@@ -6,10 +6,94 @@ use serde::{Deserialize, Serialize};
 pub struct DatabaseConnection {
     pub name: String,
     pub url: String,
+    pub settings: ConnectionSettings,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Limits applied to every query run on a connection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConnectionSettings {
+    /// Longest a statement may run, in milliseconds
+    pub statement_timeout_ms: u64,
+    /// Most rows a query may return, across all of its pages
+    pub max_rows: usize,
+    /// Largest amount of row data, as JSON, sent in one response
+    pub max_response_bytes: usize,
+    /// LIMIT added to queries that have none, except paged ones, which are limited to `max_rows`
+    pub default_limit: usize,
+    /// Database role queries run as, instead of the role of the connection URL's user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Functions queries may not call, matched case-insensitively
+    pub denied_functions: Vec<String>,
+    /// Schemas read by introspection; when empty, every non-system schema on `PostgreSQL`
+    /// and the connection's database on `MySQL`
+    pub include_schemas: Vec<String>,
+    /// Schemas left out of introspection
+    pub exclude_schemas: Vec<String>,
+    /// How introspection counts the rows of each table
+    pub row_counts: RowCountMode,
+    /// Seconds cached schema metadata is used before it is read again; 0 keeps it until refreshed
+    pub schema_cache_ttl_secs: u64,
+}
+
+/// How introspection counts the rows of each table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RowCountMode {
+    /// Run `COUNT(*)` on every table, which scans large tables in full
+    #[default]
+    Exact,
+    /// Read the row counts the database keeps for its query planner, which may be out of date
+    Estimated,
+}
+
+/// Functions rejected unless a connection configures its own list
+///
+/// They can stall the server, read its files, reach other servers, affect other
+/// sessions or change state even inside a read-only transaction.
+pub const DEFAULT_DENIED_FUNCTIONS: &[&str] = &[
+    // PostgreSQL
+    "pg_sleep",
+    "pg_sleep_for",
+    "pg_sleep_until",
+    "pg_read_file",
+    "pg_read_binary_file",
+    "pg_ls_dir",
+    "lo_import",
+    "lo_export",
+    "dblink",
+    "dblink_exec",
+    "pg_cancel_backend",
+    "pg_terminate_backend",
+    "set_config",
+    "nextval",
+    "setval",
+    // MySQL
+    "sleep",
+    "benchmark",
+    "load_file",
+];
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        Self {
+            statement_timeout_ms: 30_000,
+            max_rows: 100_000,
+            max_response_bytes: 10 * 1024 * 1024,
+            default_limit: 1000,
+            role: None,
+            denied_functions: DEFAULT_DENIED_FUNCTIONS.iter().map(ToString::to_string).collect(),
+            include_schemas: Vec::new(),
+            exclude_schemas: Vec::new(),
+            row_counts: RowCountMode::Exact,
+            schema_cache_ttl_secs: 3600,
+        }
+    }
+}
+
 impl DatabaseConnection {
     #[must_use]
     #[allow(dead_code)]
@@ -18,6 +102,7 @@ impl DatabaseConnection {
         Self {
             name,
             url,
+            settings: ConnectionSettings::default(),
             created_at: now.clone(),
             updated_at: now,
         }
@@ -33,11 +118,17 @@ impl TryFrom<&rusqlite::Row<'_>> for DatabaseConnection {
     type Error = rusqlite::Error;
 
     fn try_from(row: &rusqlite::Row) -> Result<Self, Self::Error> {
+        let settings: String = row.get(2)?;
+        let settings = serde_json::from_str(&settings).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
         Ok(Self {
             name: row.get(0)?,
             url: row.get(1)?,
-            created_at: row.get(2)?,
-            updated_at: row.get(3)?,
+            settings,
+            created_at: row.get(3)?,
+            updated_at: row.get(4)?,
         })
     }
 }
@@ -4,6 +4,7 @@
 pub mod database;
 pub mod request;
 pub mod schema;
+pub mod schema_diff;
 pub mod query;
 pub mod natural_language;
 
@@ -4,5 +4,8 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct NaturalLanguageQueryRequest {
     pub prompt: String,
+    /// Client chosen id used to cancel the query, generated when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_id: Option<String>,
 }
 
@@ -4,13 +4,78 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct QueryRequest {
     pub sql: String,
+    /// Rows per page, defaults to 1000
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
+    /// Client chosen id used to cancel the query, generated when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_id: Option<String>,
+}
+
+/// Query string of a request for the next page of a cursor
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageRequest {
+    pub page_size: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryResponse {
-    pub columns: Vec<String>,
+    pub columns: Vec<QueryColumn>,
     pub rows: Vec<Vec<serde_json::Value>>,
     pub row_count: usize,
     pub execution_time_ms: u64,
+    /// Whether more rows can be fetched with `cursor`
+    #[serde(default)]
+    pub has_more: bool,
+    /// Whether rows were left out because the connection's row or byte limit was reached
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    /// Id of the execution, used to cancel it while it runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_id: Option<String>,
+}
+
+/// Description of a result set column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryColumn {
+    pub name: String,
+    /// Type name as reported by the database
+    pub db_type: String,
+    pub logical_type: LogicalType,
+    /// Whether the column can contain NULL, when the database reports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
+}
+
+/// Database independent category of a column type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogicalType {
+    Integer,
+    Decimal,
+    Text,
+    Timestamp,
+    Boolean,
+    Json,
+    Binary,
+    Array,
+}
+
+/// One line of a streamed query result (NDJSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum QueryStreamFrame {
+    /// First frame, describing the result columns
+    Header { query_id: String, columns: Vec<QueryColumn> },
+    /// One result row, in column order
+    Row { values: Vec<serde_json::Value> },
+    /// Last frame of a successful query
+    Trailer { row_count: usize, execution_time_ms: u64, truncated: bool },
+    /// Last frame when reading rows fails part way through
+    Error { code: String, message: String },
 }
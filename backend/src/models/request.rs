@@ -1,7 +1,12 @@
+use crate::models::database::ConnectionSettings;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateDatabaseRequest {
     pub url: String,
+    /// Query limits; missing fields take their defaults, and an existing
+    /// connection keeps its settings when this is omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<ConnectionSettings>,
 }
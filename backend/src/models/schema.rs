@@ -4,35 +4,308 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct SchemaMetadata {
     pub db_name: String,
+    /// Tables, with partitions listed under their parent
     pub tables: Vec<TableInfo>,
     pub views: Vec<ViewInfo>,
+    #[serde(default)]
+    pub materialized_views: Vec<ViewInfo>,
+    #[serde(default)]
+    pub functions: Vec<FunctionInfo>,
+    #[serde(default)]
+    pub sequences: Vec<SequenceInfo>,
+    /// When the metadata was read from the database, the same as `cached_at`
     pub updated_at: String,
+    /// When the metadata was read from the database and cached
+    pub cached_at: String,
+    /// Whether the metadata is older than the connection's schema cache TTL, because reading it again failed
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// Kind of relation a `TableInfo` describes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TableKind {
+    #[default]
+    Table,
+    /// Parent of a partitioned table, holding no rows itself
+    Partitioned,
+    /// Table whose rows live on a foreign server
+    Foreign,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TableInfo {
+    pub schema: String,
     pub name: String,
+    #[serde(default)]
+    pub kind: TableKind,
+    /// Comment documenting the table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub columns: Vec<ColumnInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub primary_key: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub row_count: Option<u64>,
+    /// Whether `row_count` is the database's estimate rather than an exact count
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub row_count_estimated: bool,
+    /// Storage and maintenance statistics, on `PostgreSQL`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<TableStatistics>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<IndexInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unique_constraints: Vec<UniqueConstraintInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub check_constraints: Vec<CheckConstraintInfo>,
+    /// Partitioning scheme of a partitioned table, e.g. `RANGE (created_at)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition_key: Option<String>,
+    /// Rows a partition holds, e.g. `FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition_bound: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub partitions: Vec<TableInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ViewInfo {
+    pub schema: String,
     pub name: String,
+    /// Comment documenting the view
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub columns: Vec<ColumnInfo>,
+    /// Query the view is defined by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub definition: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionInfo {
+    pub schema: String,
+    pub name: String,
+    /// Argument list, e.g. `customer integer, since date`
+    pub arguments: String,
+    pub return_type: String,
+    /// Whether the function returns a set of rows, so it can be queried like a table
+    pub returns_set: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceInfo {
+    pub schema: String,
+    pub name: String,
+    pub data_type: String,
+    pub start_value: i64,
+    pub increment: i64,
+}
+
+/// Exact number of rows in a table, counted on request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRowCount {
+    pub schema: String,
+    pub name: String,
+    pub row_count: u64,
+}
+
+/// Size and maintenance state of a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStatistics {
+    /// Size of the table with its indexes and TOAST data, in bytes
+    pub total_bytes: u64,
+    /// Size of the table's own rows, in bytes
+    pub table_bytes: u64,
+    pub index_bytes: u64,
+    /// Size of values stored out of line in the TOAST table, in bytes
+    pub toast_bytes: u64,
+    /// Rows deleted or updated but not vacuumed yet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_tuples: Option<u64>,
+    /// Last manual `VACUUM`, as an RFC 3339 timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_vacuum: Option<String>,
+    /// Last vacuum run by autovacuum
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_autovacuum: Option<String>,
+    /// Last manual `ANALYZE`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_analyze: Option<String>,
+    /// Last analyze run by autovacuum
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_autoanalyze: Option<String>,
+}
+
+/// Foreign key from columns of a table to columns of the table it references
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignKeyInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_schema: String,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+    /// Action taken when the referenced row is deleted, e.g. `CASCADE` or `NO ACTION`
+    pub on_delete: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexInfo {
+    pub name: String,
+    /// Indexed columns, or the expressions of an expression index
+    pub columns: Vec<String>,
+    pub unique: bool,
+    /// Index access method, e.g. `btree`, `hash` or `gin`
+    pub method: String,
+    /// Condition of a partial index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicate: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UniqueConstraintInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckConstraintInfo {
+    pub name: String,
+    pub expression: String,
+}
+
+impl TableInfo {
+    /// Name of the table qualified with its schema, e.g. `analytics.orders`
+    #[must_use]
+    pub fn qualified_name(&self) -> String {
+        qualified_name(&self.schema, &self.name)
+    }
+}
+
+impl ViewInfo {
+    /// Name of the view qualified with its schema
+    #[must_use]
+    pub fn qualified_name(&self) -> String {
+        qualified_name(&self.schema, &self.name)
+    }
+}
+
+impl FunctionInfo {
+    /// Qualified name with the argument list, which tells overloads apart, e.g. `public.top_customers(n integer)`
+    #[must_use]
+    pub fn signature(&self) -> String {
+        format!("{}({})", qualified_name(&self.schema, &self.name), self.arguments)
+    }
+}
+
+impl SequenceInfo {
+    /// Name of the sequence qualified with its schema
+    #[must_use]
+    pub fn qualified_name(&self) -> String {
+        qualified_name(&self.schema, &self.name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnInfo {
     pub name: String,
+    /// Column type; the name of the type for enums and domains
     pub data_type: String,
     pub nullable: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_value: Option<String>,
+    /// Comment documenting the column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Type a domain is based on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_type: Option<String>,
+    /// Labels of an enum column, in their sort order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<String>>,
+}
+
+fn qualified_name(schema: &str, name: &str) -> String {
+    format!("{schema}.{name}")
+}
+
+/// Schema metadata for tests, with everything not passed in left empty
+#[cfg(test)]
+pub mod builders {
+    use super::{ColumnInfo, IndexInfo, SchemaMetadata, TableInfo, TableKind};
+
+    pub fn column(name: &str, data_type: &str, nullable: bool) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default_value: None,
+            description: None,
+            base_type: None,
+            enum_values: None,
+        }
+    }
+
+    /// Table in the `public` schema
+    pub fn table(name: &str, columns: Vec<ColumnInfo>) -> TableInfo {
+        TableInfo {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            kind: TableKind::Table,
+            description: None,
+            columns,
+            primary_key: None,
+            row_count: None,
+            row_count_estimated: false,
+            statistics: None,
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            unique_constraints: Vec::new(),
+            check_constraints: Vec::new(),
+            partition_key: None,
+            partition_bound: None,
+            partitions: Vec::new(),
+        }
+    }
+
+    /// B-tree index on plain columns
+    pub fn index(name: &str, columns: &[&str], unique: bool) -> IndexInfo {
+        IndexInfo {
+            name: name.to_string(),
+            columns: columns.iter().map(ToString::to_string).collect(),
+            unique,
+            method: "btree".to_string(),
+            predicate: None,
+        }
+    }
+
+    pub fn schema(tables: Vec<TableInfo>) -> SchemaMetadata {
+        SchemaMetadata {
+            db_name: "app".to_string(),
+            tables,
+            views: Vec::new(),
+            materialized_views: Vec::new(),
+            functions: Vec::new(),
+            sequences: Vec::new(),
+            updated_at: String::new(),
+            cached_at: String::new(),
+            stale: false,
+        }
+    }
 }
@@ -0,0 +1,198 @@
+use crate::models::schema::{CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, IndexInfo, UniqueConstraintInfo};
+use serde::{Deserialize, Serialize};
+
+/// Schema of a database as read by one refresh, kept in the schema history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSnapshot {
+    /// Number of the snapshot, counting up from 1 for each connection
+    pub version: i64,
+    /// When the schema was read from the database
+    pub created_at: String,
+}
+
+/// Query string choosing the snapshots to compare
+///
+/// `from` defaults to the snapshot before `to`, which defaults to the latest snapshot.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiffRequest {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// Compare from the last snapshot taken at or before this time instead of `from`,
+    /// as an RFC 3339 timestamp or a date
+    pub since: Option<String>,
+}
+
+/// Changes between two snapshots of a database's schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+    pub db_name: String,
+    pub from: SchemaSnapshot,
+    pub to: SchemaSnapshot,
+    pub changes: SchemaDiff,
+}
+
+/// Query string of a comparison of two connections' schemas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiffRequest {
+    pub left: String,
+    pub right: String,
+    /// Side a migration script is written for, to bring it in line with the other side
+    pub migrate: Option<DiffSide>,
+}
+
+/// One of the two connections being compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffSide {
+    Left,
+    Right,
+}
+
+/// Differences between the schemas of two connections
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiff {
+    pub left: String,
+    pub right: String,
+    /// Changes going from the left schema to the right one
+    pub changes: SchemaDiff,
+    /// `PostgreSQL` statements bringing the `migrate` side in line with the other side
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub migration: Option<String>,
+}
+
+/// Structural differences between two schemas, going from the first to the second
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDiff {
+    /// Qualified names of tables only in the second schema
+    pub added_tables: Vec<String>,
+    /// Qualified names of tables only in the first schema
+    pub dropped_tables: Vec<String>,
+    /// Tables in both schemas whose columns, indexes or constraints differ
+    pub changed_tables: Vec<TableDiff>,
+    /// Qualified names of views and materialized views only in the second schema
+    #[serde(default)]
+    pub added_views: Vec<String>,
+    /// Qualified names of views and materialized views only in the first schema
+    #[serde(default)]
+    pub dropped_views: Vec<String>,
+    /// Qualified names of views and materialized views in both schemas whose definition differs
+    #[serde(default)]
+    pub changed_views: Vec<String>,
+}
+
+/// Differences of one table present in both schemas
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableDiff {
+    /// Qualified name of the table
+    pub table: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_columns: Vec<ColumnInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped_columns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retyped_columns: Vec<ColumnTypeChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nullability_changes: Vec<NullabilityChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub default_changes: Vec<DefaultChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_key_change: Option<PrimaryKeyChange>,
+    /// Indexes only in the second schema, or whose definition changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_indexes: Vec<IndexInfo>,
+    /// Names of indexes only in the first schema, or whose definition changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped_indexes: Vec<String>,
+    /// Foreign keys only in the second schema, or whose definition changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_foreign_keys: Vec<ForeignKeyInfo>,
+    /// Names of foreign keys only in the first schema, or whose definition changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped_foreign_keys: Vec<String>,
+    /// Unique constraints only in the second schema, or whose columns changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_unique_constraints: Vec<UniqueConstraintInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped_unique_constraints: Vec<String>,
+    /// Check constraints only in the second schema, or whose expression changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_check_constraints: Vec<CheckConstraintInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped_check_constraints: Vec<String>,
+}
+
+/// Column whose type differs between the two schemas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnTypeChange {
+    pub column: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Column that accepts NULL in one schema but not in the other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NullabilityChange {
+    pub column: String,
+    /// Whether the column accepts NULL in the second schema
+    pub nullable: bool,
+}
+
+/// Column whose default differs between the two schemas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultChange {
+    pub column: String,
+    /// Default expression in the second schema, if any
+    pub default: Option<String>,
+}
+
+/// Primary key columns of a table in each schema, `None` for a table without one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrimaryKeyChange {
+    pub from: Option<Vec<String>>,
+    pub to: Option<Vec<String>>,
+}
+
+impl SchemaDiff {
+    /// Whether both schemas have the same structure
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_tables.is_empty()
+            && self.dropped_tables.is_empty()
+            && self.changed_tables.is_empty()
+            && self.added_views.is_empty()
+            && self.dropped_views.is_empty()
+            && self.changed_views.is_empty()
+    }
+}
+
+impl TableDiff {
+    /// Whether the table has the same structure in both schemas
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_columns.is_empty()
+            && self.dropped_columns.is_empty()
+            && self.retyped_columns.is_empty()
+            && self.nullability_changes.is_empty()
+            && self.default_changes.is_empty()
+            && self.primary_key_change.is_none()
+            && self.added_indexes.is_empty()
+            && self.dropped_indexes.is_empty()
+            && self.added_foreign_keys.is_empty()
+            && self.dropped_foreign_keys.is_empty()
+            && self.added_unique_constraints.is_empty()
+            && self.dropped_unique_constraints.is_empty()
+            && self.added_check_constraints.is_empty()
+            && self.dropped_check_constraints.is_empty()
+    }
+}
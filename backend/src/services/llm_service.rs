@@ -1,5 +1,6 @@
+use crate::drivers::DatabaseKind;
 use crate::error::AppError;
-use crate::models::schema::SchemaMetadata;
+use crate::models::schema::{ColumnInfo, ForeignKeyInfo, FunctionInfo, SchemaMetadata, TableInfo};
 use serde::{Deserialize, Serialize};
 
 pub struct LLMService {
@@ -45,6 +46,7 @@ impl LLMService {
         &self,
         prompt: &str,
         schema: &SchemaMetadata,
+        kind: DatabaseKind,
     ) -> Result<String, AppError> {
         if self.api_key.is_empty() {
             return Err(AppError::InternalError(
@@ -55,26 +57,28 @@ impl LLMService {
         // Format schema as context
         let schema_context = Self::format_schema_context(schema);
 
-        // Create prompt for LLM
-        let system_prompt = r"You are a SQL expert. Convert natural language queries to PostgreSQL SELECT statements.
+        // Create prompt for LLM, naming the SQL dialect of the target database
+        let dialect = kind.sql_dialect_name();
+        let system_prompt = format!(r"You are a SQL expert. Convert natural language queries to {dialect} SELECT statements.
 
 Rules:
 1. Only generate SELECT statements (read-only queries)
-2. Use the provided schema information to determine table and column names
+2. Use the provided schema information to determine table and column names, qualifying tables and views with their schema
 3. Return ONLY the SQL query, no explanations or markdown formatting
-4. Use proper PostgreSQL syntax
+4. Use proper {dialect} syntax
 5. Include appropriate WHERE clauses, JOINs, and aggregations as needed
 6. Do not include LIMIT clauses (the system will add them automatically)
+7. Compare enum columns only with their listed values, and use the table and column descriptions to pick the right columns
 
 Schema Information:
-";
+");
 
         let user_prompt = format!("{}\n\nUser Query: {}", schema_context, prompt);
 
         let messages = vec![
             ChatMessage {
                 role: "system".to_string(),
-                content: system_prompt.to_string(),
+                content: system_prompt,
             },
             ChatMessage {
                 role: "user".to_string(),
@@ -97,7 +101,7 @@ Schema Information:
             .json(&request)
             .send()
             .await
-            .map_err(|e| AppError::InternalError(format!("LLM API request failed: {}", e)))?;
+            .map_err(|e| AppError::LlmError(format!("LLM API request failed: {}", e)))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -105,7 +109,7 @@ Schema Information:
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(AppError::InternalError(format!(
+            return Err(AppError::LlmError(format!(
                 "LLM API returned error {}: {}",
                 status, error_text
             )));
@@ -114,10 +118,10 @@ Schema Information:
         let chat_response: ChatResponse = response
             .json()
             .await
-            .map_err(|e| AppError::InternalError(format!("Failed to parse LLM response: {}", e)))?;
+            .map_err(|e| AppError::LlmError(format!("Failed to parse LLM response: {}", e)))?;
 
         if chat_response.choices.is_empty() {
-            return Err(AppError::InternalError(
+            return Err(AppError::LlmError(
                 "LLM API returned no choices".to_string(),
             ));
         }
@@ -137,7 +141,7 @@ Schema Information:
             .to_string();
 
         if sql.is_empty() {
-            return Err(AppError::InternalError(
+            return Err(AppError::LlmError(
                 "LLM did not generate a valid SQL query".to_string(),
             ));
         }
@@ -154,35 +158,205 @@ Schema Information:
         if !schema.tables.is_empty() {
             context.push_str("Tables:\n");
             for table in &schema.tables {
-                context.push_str(&format!("  - {} (", table.name));
+                context.push_str(&format!("  - {}", table.qualified_name()));
+                if let Some(ref description) = table.description {
+                    context.push_str(&format!(": {description}"));
+                }
+                context.push_str(" (");
                 if let Some(ref pk) = table.primary_key {
                     context.push_str(&format!("PK: {:?}, ", pk));
                 }
+                // Partitions are queried through their parent, so only their names are listed
+                if let Some(ref partition_key) = table.partition_key {
+                    let partitions: Vec<String> = table.partitions.iter().map(TableInfo::qualified_name).collect();
+                    context.push_str(&format!(
+                        "partitioned by {partition_key} into [{}], ",
+                        partitions.join(", ")
+                    ));
+                }
                 context.push_str("columns: ");
-                let column_names: Vec<String> = table
-                    .columns
-                    .iter()
-                    .map(|c| format!("{} ({})", c.name, c.data_type))
-                    .collect();
+                let column_names: Vec<String> = table.columns.iter().map(Self::format_column).collect();
                 context.push_str(&column_names.join(", "));
                 context.push_str(")\n");
             }
         }
 
-        if !schema.views.is_empty() {
-            context.push_str("\nViews:\n");
-            for view in &schema.views {
-                context.push_str(&format!("  - {} (columns: ", view.name));
-                let column_names: Vec<String> = view
-                    .columns
-                    .iter()
-                    .map(|c| format!("{} ({})", c.name, c.data_type))
-                    .collect();
+        // Foreign keys tell the LLM how tables join
+        let join_hints: Vec<String> = schema
+            .tables
+            .iter()
+            .flat_map(|table| table.foreign_keys.iter().map(move |fk| Self::join_hint(table, fk)))
+            .collect();
+        if !join_hints.is_empty() {
+            context.push_str("\nJoins (from foreign keys):\n");
+            for hint in join_hints {
+                context.push_str(&format!("  - {hint}\n"));
+            }
+        }
+
+        for (heading, views) in [("Views", &schema.views), ("Materialized views", &schema.materialized_views)] {
+            if views.is_empty() {
+                continue;
+            }
+            context.push_str(&format!("\n{heading}:\n"));
+            for view in views {
+                context.push_str(&format!("  - {}", view.qualified_name()));
+                if let Some(ref description) = view.description {
+                    context.push_str(&format!(": {description}"));
+                }
+                context.push_str(" (columns: ");
+                let column_names: Vec<String> = view.columns.iter().map(Self::format_column).collect();
                 context.push_str(&column_names.join(", "));
                 context.push_str(")\n");
             }
         }
 
+        // Set-returning functions can be selected from like tables
+        let row_functions: Vec<&FunctionInfo> = schema.functions.iter().filter(|f| f.returns_set).collect();
+        if !row_functions.is_empty() {
+            context.push_str("\nFunctions returning rows (use in FROM):\n");
+            for function in row_functions {
+                context.push_str(&format!("  - {} returns {}", function.signature(), function.return_type));
+                if let Some(ref description) = function.description {
+                    context.push_str(&format!(": {description}"));
+                }
+                context.push('\n');
+            }
+        }
+
         context
     }
+
+    /// Describe a column with its type, the values an enum allows and its comment
+    fn format_column(column: &ColumnInfo) -> String {
+        let mut details = column.data_type.clone();
+        if let Some(ref base_type) = column.base_type {
+            details.push_str(&format!(" over {base_type}"));
+        }
+        if let Some(ref values) = column.enum_values {
+            let values: Vec<String> = values.iter().map(|v| format!("'{}'", v.replace('\'', "''"))).collect();
+            details.push_str(&format!(", one of {}", values.join(", ")));
+        }
+        if let Some(ref description) = column.description {
+            details.push_str(&format!(", {description}"));
+        }
+        format!("{} ({details})", column.name)
+    }
+
+    /// Describe a foreign key as the JOIN it implies
+    fn join_hint(table: &TableInfo, foreign_key: &ForeignKeyInfo) -> String {
+        let table_name = table.qualified_name();
+        let referenced = format!("{}.{}", foreign_key.referenced_schema, foreign_key.referenced_table);
+        let conditions: Vec<String> = foreign_key
+            .columns
+            .iter()
+            .zip(&foreign_key.referenced_columns)
+            .map(|(column, referenced_column)| format!("{table_name}.{column} = {referenced}.{referenced_column}"))
+            .collect();
+        format!("{table_name} JOIN {referenced} ON {}", conditions.join(" AND "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schema::builders::{schema, table};
+    use crate::models::schema::{TableKind, ViewInfo};
+
+    #[test]
+    fn test_format_column() {
+        let column = ColumnInfo {
+            name: "status".to_string(),
+            data_type: "order_status".to_string(),
+            nullable: false,
+            default_value: None,
+            description: Some("Where the order is in checkout".to_string()),
+            base_type: None,
+            enum_values: Some(vec!["open".to_string(), "won't ship".to_string()]),
+        };
+        assert_eq!(
+            LLMService::format_column(&column),
+            "status (order_status, one of 'open', 'won''t ship', Where the order is in checkout)"
+        );
+
+        let column = ColumnInfo {
+            name: "email".to_string(),
+            data_type: "email_address".to_string(),
+            base_type: Some("text".to_string()),
+            description: None,
+            enum_values: None,
+            ..column
+        };
+        assert_eq!(LLMService::format_column(&column), "email (email_address over text)");
+    }
+
+    #[test]
+    fn test_schema_context_join_hints() {
+        let schema = schema(vec![
+            table("customers", Vec::new()),
+            TableInfo {
+                schema: "billing".to_string(),
+                foreign_keys: vec![ForeignKeyInfo {
+                    name: "invoices_customer_fkey".to_string(),
+                    columns: vec!["customer_id".to_string(), "region".to_string()],
+                    referenced_schema: "public".to_string(),
+                    referenced_table: "customers".to_string(),
+                    referenced_columns: vec!["id".to_string(), "region".to_string()],
+                    on_delete: "CASCADE".to_string(),
+                }],
+                ..table("invoices", Vec::new())
+            },
+        ]);
+
+        let context = LLMService::format_schema_context(&schema);
+        assert!(context.contains("  - billing.invoices (columns: )"));
+        assert!(context.contains(
+            "Joins (from foreign keys):\n  - billing.invoices JOIN public.customers ON \
+             billing.invoices.customer_id = public.customers.id AND billing.invoices.region = public.customers.region\n"
+        ));
+    }
+
+    #[test]
+    fn test_schema_context_partitions_and_functions() {
+        let partition = |name: &str| TableInfo {
+            row_count: Some(0),
+            partition_bound: Some("FOR VALUES IN ('eu')".to_string()),
+            ..table(name, Vec::new())
+        };
+        let function = |name: &str, returns_set: bool| FunctionInfo {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            arguments: "n integer".to_string(),
+            return_type: "SETOF customers".to_string(),
+            returns_set,
+            description: None,
+        };
+        let schema = SchemaMetadata {
+            materialized_views: vec![ViewInfo {
+                schema: "public".to_string(),
+                name: "daily_totals".to_string(),
+                description: None,
+                columns: Vec::new(),
+                definition: None,
+            }],
+            functions: vec![function("top_customers", true), function("add_one", false)],
+            ..schema(vec![TableInfo {
+                kind: TableKind::Partitioned,
+                partition_key: Some("LIST (region)".to_string()),
+                partition_bound: None,
+                partitions: vec![partition("events_eu"), partition("events_us")],
+                ..partition("events")
+            }])
+        };
+
+        let context = LLMService::format_schema_context(&schema);
+        assert!(context.contains(
+            "  - public.events (partitioned by LIST (region) into [public.events_eu, public.events_us], columns: )"
+        ));
+        assert!(context.contains("Materialized views:\n  - public.daily_totals (columns: )\n"));
+        assert!(context.contains(
+            "Functions returning rows (use in FROM):\n  - public.top_customers(n integer) returns SETOF customers\n"
+        ));
+        assert!(!context.contains("add_one"));
+    }
 }
@@ -3,7 +3,10 @@
 
 pub mod database_service;
 pub mod schema_service;
+pub mod schema_diff;
+pub mod schema_migration;
 pub mod query_executor;
+pub mod query_cursor;
 pub mod sql_validator;
 pub mod llm_service;
 
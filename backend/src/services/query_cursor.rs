@@ -0,0 +1,389 @@
+use crate::drivers::{QueryStream, RowReceiver};
+use crate::error::AppError;
+use crate::models::database::ConnectionSettings;
+use crate::models::query::{QueryColumn, QueryResponse};
+use crate::services::query_executor::row_size;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Rows returned per page when the client does not ask for a page size
+pub const DEFAULT_PAGE_SIZE: usize = 1000;
+
+/// Largest page a client can request
+pub const MAX_PAGE_SIZE: usize = 10_000;
+
+/// Cursors not read for this long are closed, or sooner once their statement timeout has passed
+const CURSOR_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Open cursors allowed per database; each one holds a pooled connection
+const MAX_CURSORS_PER_DATABASE: usize = 3;
+
+/// Page size to use for a client request, within `1..=MAX_PAGE_SIZE`
+#[must_use]
+pub fn page_size(requested: Option<usize>) -> usize {
+    requested.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Query result that is still being read, page by page
+struct QueryCursor {
+    db_name: String,
+    query_id: String,
+    columns: Vec<QueryColumn>,
+    rows: RowReceiver,
+    /// Row read ahead to find out whether another page exists
+    pending: Option<Vec<serde_json::Value>>,
+    /// Rows the query may still return under the connection's row limit
+    rows_left: usize,
+    /// Whether the query's LIMIT was lowered to the row limit, so reaching it cuts the result short
+    clamped: bool,
+    /// Largest amount of row data sent in one page
+    max_page_bytes: usize,
+    last_used: Instant,
+    /// When the connection's statement timeout stops the query, which keeps running between pages
+    expires_at: Instant,
+}
+
+/// Open query cursors, keyed by their opaque id
+///
+/// A cursor keeps the query's row stream, and with it the database connection,
+/// until the last page is read, the cursor sits idle for too long, or the
+/// connection is removed.
+pub struct QueryCursorStore {
+    cursors: Mutex<HashMap<String, QueryCursor>>,
+}
+
+impl QueryCursorStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            cursors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Read the first page of a query result, keeping a cursor open if more rows remain
+    ///
+    /// A page ends early when its rows reach the connection's byte limit. The
+    /// result is truncated once the row limit is reached with rows left or by a
+    /// `clamped` LIMIT, or if a single row is larger than the byte limit.
+    ///
+    /// The statement stays open until its last page is read, so the cursor
+    /// expires once the connection's statement timeout has passed.
+    pub async fn open(
+        &self,
+        db_name: &str,
+        query_id: &str,
+        stream: QueryStream,
+        settings: &ConnectionSettings,
+        clamped: bool,
+        page_size: usize,
+    ) -> Result<QueryResponse, AppError> {
+        let start_time = Instant::now();
+        let cursor = QueryCursor {
+            db_name: db_name.to_string(),
+            query_id: query_id.to_string(),
+            columns: stream.columns,
+            rows: stream.rows,
+            pending: None,
+            rows_left: settings.max_rows,
+            clamped,
+            max_page_bytes: settings.max_response_bytes,
+            last_used: start_time,
+            expires_at: start_time + Duration::from_millis(settings.statement_timeout_ms),
+        };
+
+        let cursor_id = format!("{:032x}", rand::random::<u128>());
+        self.read_page(cursor_id, cursor, page_size, start_time).await
+    }
+
+    /// Read the next page of an open cursor
+    pub async fn next_page(
+        &self,
+        db_name: &str,
+        cursor_id: &str,
+        page_size: usize,
+    ) -> Result<QueryResponse, AppError> {
+        let start_time = Instant::now();
+        self.remove_expired()?;
+
+        // The cursor is taken out while its page is read, so concurrent reads of one cursor fail fast
+        let cursor = {
+            let mut cursors = self.lock()?;
+            match cursors.get(cursor_id) {
+                Some(cursor) if cursor.db_name == db_name => cursors.remove(cursor_id),
+                _ => None,
+            }
+        }
+        .ok_or_else(|| AppError::NotFound(format!("Query cursor '{cursor_id}' not found or expired")))?;
+
+        self.read_page(cursor_id.to_string(), cursor, page_size, start_time).await
+    }
+
+    /// Close every cursor of a database, e.g. when its connection is removed
+    pub fn close_database(&self, db_name: &str) -> Result<(), AppError> {
+        let mut cursors = self.lock()?;
+        let before = cursors.len();
+        cursors.retain(|_, cursor| cursor.db_name != db_name);
+        tracing::debug!(database_name = %db_name, closed = before - cursors.len(), "closed query cursors");
+        Ok(())
+    }
+
+    /// Close cursors that have not been read within the idle timeout or whose statement timed out
+    pub fn remove_expired(&self) -> Result<(), AppError> {
+        let mut cursors = self.lock()?;
+        let now = Instant::now();
+        cursors.retain(|cursor_id, cursor| {
+            let expired = cursor.last_used.elapsed() > CURSOR_IDLE_TIMEOUT || now >= cursor.expires_at;
+            if expired {
+                tracing::info!(cursor_id = %cursor_id, database_name = %cursor.db_name, "query cursor expired");
+            }
+            !expired
+        });
+        Ok(())
+    }
+
+    /// Read up to `page_size` rows, storing the cursor again if the result has more
+    async fn read_page(
+        &self,
+        cursor_id: String,
+        mut cursor: QueryCursor,
+        page_size: usize,
+        start_time: Instant,
+    ) -> Result<QueryResponse, AppError> {
+        let mut rows = Vec::with_capacity(page_size.min(DEFAULT_PAGE_SIZE));
+        let mut page_bytes = 0;
+        let mut truncated = false;
+        while rows.len() < page_size {
+            let row = match cursor.pending.take() {
+                Some(row) => row,
+                None => match cursor.rows.recv().await {
+                    Some(row) => row?,
+                    None => break,
+                },
+            };
+
+            let size = row_size(&row);
+            if cursor.rows_left == 0 {
+                truncated = true;
+                break;
+            }
+            if page_bytes + size > cursor.max_page_bytes {
+                // Leave the row for the next page, unless it can never fit in one
+                if rows.is_empty() {
+                    truncated = true;
+                } else {
+                    cursor.pending = Some(row);
+                }
+                break;
+            }
+
+            page_bytes += size;
+            cursor.rows_left -= 1;
+            rows.push(row);
+        }
+
+        // Read one row ahead, so the client knows whether to ask for another page
+        if !truncated && cursor.pending.is_none() {
+            cursor.pending = match cursor.rows.recv().await {
+                Some(row) => Some(row?),
+                None => None,
+            };
+        }
+        if cursor.rows_left == 0 && (cursor.pending.is_some() || cursor.clamped) {
+            truncated = true;
+        }
+        if truncated {
+            tracing::info!(cursor_id = %cursor_id, database_name = %cursor.db_name, "query result truncated");
+        }
+        let has_more = !truncated && cursor.pending.is_some();
+        let columns = cursor.columns.clone();
+        let query_id = cursor.query_id.clone();
+
+        if has_more {
+            cursor.last_used = Instant::now();
+            self.store(cursor_id.clone(), cursor)?;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        Ok(QueryResponse {
+            columns,
+            row_count: rows.len(),
+            rows,
+            execution_time_ms: execution_time,
+            has_more,
+            truncated,
+            cursor: has_more.then_some(cursor_id),
+            query_id: Some(query_id),
+        })
+    }
+
+    /// Keep a cursor open, closing the least recently used one of its database if at the limit
+    fn store(&self, cursor_id: String, cursor: QueryCursor) -> Result<(), AppError> {
+        let mut cursors = self.lock()?;
+
+        let open: Vec<(&String, Instant)> = cursors
+            .iter()
+            .filter(|(_, open)| open.db_name == cursor.db_name)
+            .map(|(id, open)| (id, open.last_used))
+            .collect();
+        if open.len() >= MAX_CURSORS_PER_DATABASE
+            && let Some(oldest) = open.iter().min_by_key(|(_, last_used)| *last_used).map(|(id, _)| (*id).clone())
+        {
+            tracing::info!(cursor_id = %oldest, database_name = %cursor.db_name, "closing least recently used query cursor");
+            cursors.remove(&oldest);
+        }
+
+        cursors.insert(cursor_id, cursor);
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, QueryCursor>>, AppError> {
+        self.cursors.lock().map_err(|e| {
+            tracing::error!(error = ?e, "query cursor mutex poisoned");
+            AppError::InternalError(format!("Failed to acquire lock: {e:?}"))
+        })
+    }
+}
+
+impl Default for QueryCursorStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::{DatabaseDriver, SqliteDriver};
+
+    async fn numbers_stream(count: usize) -> QueryStream {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let driver = SqliteDriver::from_connection(conn);
+        let sql = format!(
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < {count}) SELECT x FROM n"
+        );
+        driver.execute_stream(&sql, &format!("numbers-{count}"), &ConnectionSettings::default()).await.unwrap()
+    }
+
+    #[test]
+    fn test_page_size() {
+        assert_eq!(page_size(None), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_size(Some(0)), 1);
+        assert_eq!(page_size(Some(50)), 50);
+        assert_eq!(page_size(Some(1_000_000)), MAX_PAGE_SIZE);
+    }
+
+    #[tokio::test]
+    async fn test_pages_until_exhausted() {
+        let store = QueryCursorStore::new();
+
+        let first = store.open("db", "q", numbers_stream(5).await, &ConnectionSettings::default(), false, 2).await.unwrap();
+        assert_eq!(first.rows, vec![vec![serde_json::json!(1)], vec![serde_json::json!(2)]]);
+        assert!(first.has_more);
+        let cursor = first.cursor.unwrap();
+
+        // Cursors are scoped to their database
+        assert!(matches!(store.next_page("other", &cursor, 2).await, Err(AppError::NotFound(_))));
+
+        let second = store.next_page("db", &cursor, 2).await.unwrap();
+        assert_eq!(second.row_count, 2);
+        assert_eq!(second.cursor.as_deref(), Some(cursor.as_str()));
+
+        let last = store.next_page("db", &cursor, 2).await.unwrap();
+        assert_eq!(last.rows, vec![vec![serde_json::json!(5)]]);
+        assert!(!last.has_more);
+        assert!(last.cursor.is_none());
+
+        // The cursor is closed once the last page has been read
+        assert!(matches!(store.next_page("db", &cursor, 2).await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_exact_page_has_no_cursor() {
+        let store = QueryCursorStore::new();
+
+        let page = store.open("db", "q", numbers_stream(3).await, &ConnectionSettings::default(), false, 3).await.unwrap();
+        assert_eq!(page.row_count, 3);
+        assert!(!page.has_more);
+        assert!(page.cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cursor_limit_and_close_database() {
+        let store = QueryCursorStore::new();
+
+        let mut cursors = Vec::new();
+        for _ in 0..=MAX_CURSORS_PER_DATABASE {
+            cursors.push(store.open("db", "q", numbers_stream(10).await, &ConnectionSettings::default(), false, 1).await.unwrap().cursor.unwrap());
+        }
+
+        // Opening one more cursor than allowed closes the least recently used one
+        assert!(store.next_page("db", &cursors[0], 1).await.is_err());
+        assert!(store.next_page("db", &cursors[1], 1).await.is_ok());
+
+        store.close_database("db").unwrap();
+        assert!(store.next_page("db", &cursors[2], 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_row_limit_truncates_across_pages() {
+        let store = QueryCursorStore::new();
+        let settings = ConnectionSettings { max_rows: 3, ..ConnectionSettings::default() };
+
+        let first = store.open("db", "q", numbers_stream(10).await, &settings, false, 2).await.unwrap();
+        assert!(first.has_more);
+        assert!(!first.truncated);
+
+        let last = store.next_page("db", &first.cursor.unwrap(), 2).await.unwrap();
+        assert_eq!(last.rows, vec![vec![serde_json::json!(3)]]);
+        assert!(!last.has_more);
+        assert!(last.truncated);
+
+        // Exactly reaching the limit is not a truncation
+        let exact = store.open("db", "q", numbers_stream(3).await, &settings, false, 5).await.unwrap();
+        assert_eq!(exact.row_count, 3);
+        assert!(!exact.truncated);
+
+        // Unless the query's own LIMIT was lowered to it
+        let clamped = store.open("db", "q", numbers_stream(3).await, &settings, true, 5).await.unwrap();
+        assert_eq!(clamped.row_count, 3);
+        assert!(clamped.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_expires_with_statement_timeout() {
+        let store = QueryCursorStore::new();
+        let settings = ConnectionSettings { statement_timeout_ms: 20, ..ConnectionSettings::default() };
+
+        let first = store.open("db", "q", numbers_stream(10).await, &settings, false, 2).await.unwrap();
+        let cursor = first.cursor.unwrap();
+
+        // The database has stopped the statement by now, however recently the cursor was read
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(matches!(store.next_page("db", &cursor, 2).await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_byte_limit_ends_page_early() {
+        let store = QueryCursorStore::new();
+        // Each row serializes to 3 bytes, e.g. `[1]`
+        let settings = ConnectionSettings { max_response_bytes: 7, ..ConnectionSettings::default() };
+
+        let first = store.open("db", "q", numbers_stream(5).await, &settings, false, 10).await.unwrap();
+        assert_eq!(first.row_count, 2);
+        assert!(first.has_more);
+        assert!(!first.truncated);
+
+        let second = store.next_page("db", &first.cursor.unwrap(), 10).await.unwrap();
+        assert_eq!(second.rows, vec![vec![serde_json::json!(3)], vec![serde_json::json!(4)]]);
+
+        // A row larger than a whole page truncates the result
+        let tiny = ConnectionSettings { max_response_bytes: 2, ..ConnectionSettings::default() };
+        let page = store.open("db", "q", numbers_stream(5).await, &tiny, false, 10).await.unwrap();
+        assert_eq!(page.row_count, 0);
+        assert!(page.truncated);
+        assert!(page.cursor.is_none());
+    }
+}
@@ -1,130 +1,324 @@
+use crate::drivers::{DatabaseDriver, QueryStream};
 use crate::error::AppError;
-use crate::models::query::QueryResponse;
-use sqlx::{PgPool, Row, Column};
+use crate::models::database::ConnectionSettings;
+use crate::models::query::{QueryResponse, QueryStreamFrame};
+use crate::services::query_cursor::QueryCursorStore;
+use crate::services::sql_validator::ValidatedSql;
+use futures_util::stream::{self, Stream, StreamExt};
 use std::time::Instant;
 
+/// Longest client chosen query id accepted
+const MAX_QUERY_ID_LEN: usize = 64;
+
+/// Id of a query execution: the client's choice if valid, otherwise a random one
+pub fn query_id(requested: Option<String>) -> Result<String, AppError> {
+    match requested {
+        Some(id) => {
+            let valid = !id.is_empty()
+                && id.len() <= MAX_QUERY_ID_LEN
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if valid {
+                Ok(id)
+            } else {
+                Err(AppError::ValidationError(format!(
+                    "Query id must be 1 to {MAX_QUERY_ID_LEN} letters, digits, '-' or '_'"
+                )))
+            }
+        }
+        None => Ok(format!("{:032x}", rand::random::<u128>())),
+    }
+}
+
+/// Room a result has left under the connection's row and byte limits
+pub(crate) struct ResultBudget {
+    rows: usize,
+    bytes: usize,
+}
+
+impl ResultBudget {
+    pub(crate) fn new(max_rows: usize, max_bytes: usize) -> Self {
+        Self { rows: max_rows, bytes: max_bytes }
+    }
+
+    /// Take room for `row`, returning false if it does not fit
+    pub(crate) fn admit(&mut self, row: &[serde_json::Value]) -> bool {
+        let size = row_size(row);
+        if self.rows == 0 || size > self.bytes {
+            return false;
+        }
+        self.rows -= 1;
+        self.bytes -= size;
+        true
+    }
+}
+
+/// Size of a row as sent to the client, in bytes of JSON
+pub(crate) fn row_size(row: &[serde_json::Value]) -> usize {
+    serde_json::to_vec(row).map_or(0, |json| json.len())
+}
+
 pub struct QueryExecutor;
 
 impl QueryExecutor {
-    /// Execute a SQL query against `PostgreSQL` database
+    /// Execute a SQL query against the target database
+    ///
+    /// Rows beyond the connection's row or byte limit are not read; the query is
+    /// cancelled instead and the response marked as truncated. So is a result that
+    /// reached a LIMIT lowered to the connection's maximum rows.
     pub async fn execute_query(
-        pool: &PgPool,
-        sql: &str,
+        driver: &dyn DatabaseDriver,
+        query: &ValidatedSql,
+        query_id: &str,
+        settings: &ConnectionSettings,
     ) -> Result<QueryResponse, AppError> {
         let start_time = Instant::now();
+        let sql = query.sql.as_str();
 
         // Execute query
-        let rows = sqlx::query(sql)
-            .fetch_all(pool)
-            .await?;
-
-        #[allow(clippy::cast_possible_truncation)]
-        let execution_time = start_time.elapsed().as_millis() as u64;
-        
-        // Get column names - try from first row, or use metadata query if empty
-        let columns = if let Some(first_row) = rows.first() {
-            first_row.columns()
-                .iter()
-                .map(|col| col.name().to_string())
-                .collect()
-        } else {
-            // For empty results, get column metadata by executing with LIMIT 0
-            Self::get_column_names_from_metadata(pool, sql).await?
-        };
-        
-        // Convert rows to JSON values
-        let mut result_rows = Vec::new();
-        for row in rows {
-            let mut row_values = Vec::new();
-            for col_name in &columns {
-                // Try to get value as JSON
-                // sqlx doesn't directly support getting as JSON, so we'll use try_get with different types
-                let value = Self::get_value_as_json(&row, col_name);
-                row_values.push(value);
+        let QueryStream { columns, rows: mut receiver } = driver
+            .execute_stream(sql, query_id, settings)
+            .await
+            .map_err(|e| e.locate_in(sql))?;
+
+        let mut budget = ResultBudget::new(settings.max_rows, settings.max_response_bytes);
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        while let Some(row) = receiver.recv().await {
+            let row = row?;
+            if !budget.admit(&row) {
+                tracing::info!(query_id = %query_id, row_count = rows.len(), "query result truncated");
+                truncated = true;
+                break;
             }
-            result_rows.push(row_values);
+            rows.push(row);
         }
-        
-        let row_count = result_rows.len();
-        
+        if query.clamped && rows.len() == settings.max_rows {
+            tracing::info!(query_id = %query_id, row_count = rows.len(), "query result cut short by the lowered LIMIT");
+            truncated = true;
+        }
+
+        let execution_time = elapsed_ms(start_time);
+
+        let row_count = rows.len();
+
         Ok(QueryResponse {
             columns,
-            rows: result_rows,
+            rows,
             row_count,
             execution_time_ms: execution_time,
+            has_more: false,
+            truncated,
+            cursor: None,
+            query_id: Some(query_id.to_string()),
         })
     }
-    
-    /// Get column names from query metadata (for empty result sets)
-    async fn get_column_names_from_metadata(
-        pool: &PgPool,
-        sql: &str,
-    ) -> Result<Vec<String>, AppError> {
-        // Execute with LIMIT 0 to get metadata without data
-        let metadata_sql = if sql.to_uppercase().contains("LIMIT") {
-            // Wrap in subquery to add LIMIT 0
-            format!("SELECT * FROM ({sql}) AS _ LIMIT 0")
-        } else {
-            format!("{sql} LIMIT 0")
-        };
-
-        let metadata_rows = sqlx::query(&metadata_sql)
-            .fetch_all(pool)
-            .await;
 
-        if let Ok(rows) = metadata_rows && let Some(row) = rows.first() {
-            return Ok(row.columns().iter().map(|c| c.name().to_string()).collect());
-        }
+    /// Execute a SQL query and return its first page
+    ///
+    /// If more rows remain, the result stays open as a cursor in `cursors`.
+    pub async fn execute_paged(
+        driver: &dyn DatabaseDriver,
+        query: &ValidatedSql,
+        query_id: &str,
+        settings: &ConnectionSettings,
+        cursors: &QueryCursorStore,
+        db_name: &str,
+        page_size: usize,
+    ) -> Result<QueryResponse, AppError> {
+        let start_time = Instant::now();
+
+        let stream = driver
+            .execute_stream(&query.sql, query_id, settings)
+            .await
+            .map_err(|e| e.locate_in(&query.sql))?;
+        let mut response = cursors.open(db_name, query_id, stream, settings, query.clamped, page_size).await?;
 
-        // Fallback: return empty
-        Ok(Vec::new())
+        // Include the time to start the query, not only to read the page
+        response.execution_time_ms = elapsed_ms(start_time);
+        Ok(response)
     }
-    
-    /// Get value from row as JSON, trying different types
-    fn get_value_as_json(row: &sqlx::postgres::PgRow, col_name: &str) -> serde_json::Value {
-        // Try different types in order of likelihood
-        // String
-        if let Ok(val) = row.try_get::<Option<String>, _>(col_name) {
-            return val.map(serde_json::Value::String)
-                .unwrap_or(serde_json::Value::Null);
-        }
-        
-        // Integer
-        if let Ok(val) = row.try_get::<Option<i64>, _>(col_name) {
-            return val.map(|v| serde_json::Value::Number(v.into()))
-                .unwrap_or(serde_json::Value::Null);
-        }
-        
-        // Float
-        if let Ok(val) = row.try_get::<Option<f64>, _>(col_name) {
-            return val.and_then(|v| serde_json::Number::from_f64(v))
-                .map(serde_json::Value::Number)
-                .unwrap_or(serde_json::Value::Null);
-        }
-        
-        // Boolean
-        if let Ok(val) = row.try_get::<Option<bool>, _>(col_name) {
-            return val.map(serde_json::Value::Bool)
-                .unwrap_or(serde_json::Value::Null);
-        }
-        
-        // Try as JSON value directly (PostgreSQL json/jsonb)
-        if let Ok(val) = row.try_get::<Option<serde_json::Value>, _>(col_name) {
-            return val.unwrap_or(serde_json::Value::Null);
-        }
-        
-        // Fallback: try to get as text and parse
-        if let Ok(val) = row.try_get::<Option<String>, _>(col_name) {
-            if let Some(text) = val {
-                // Try to parse as JSON
-                if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(&text) {
-                    return json_val;
+
+    /// Execute a SQL query and stream its result as frames
+    ///
+    /// A header frame with the columns is followed by one frame per row and a
+    /// trailer, or an error frame if reading rows fails part way through. The
+    /// stream ends early with a truncated trailer when the connection's row or
+    /// byte limit is reached, and the trailer is also truncated when the rows
+    /// reached a LIMIT lowered to the connection's maximum rows.
+    pub async fn stream_query(
+        driver: &dyn DatabaseDriver,
+        query: &ValidatedSql,
+        query_id: &str,
+        settings: &ConnectionSettings,
+    ) -> Result<impl Stream<Item = QueryStreamFrame> + Send + 'static, AppError> {
+        let start_time = Instant::now();
+        let sql = query.sql.as_str();
+        let clamped_rows = query.clamped.then_some(settings.max_rows);
+
+        let QueryStream { columns, rows } = driver
+            .execute_stream(sql, query_id, settings)
+            .await
+            .map_err(|e| e.locate_in(sql))?;
+        let budget = ResultBudget::new(settings.max_rows, settings.max_response_bytes);
+
+        let header = stream::iter([QueryStreamFrame::Header {
+            query_id: query_id.to_string(),
+            columns,
+        }]);
+        let body = stream::unfold(Some((rows, budget, 0)), move |state| async move {
+            let (mut rows, mut budget, row_count) = state?;
+            match rows.recv().await {
+                Some(Ok(values)) if budget.admit(&values) => {
+                    Some((QueryStreamFrame::Row { values }, Some((rows, budget, row_count + 1))))
+                }
+                Some(Ok(_)) => {
+                    tracing::info!(row_count = row_count, "query stream truncated");
+                    let frame = QueryStreamFrame::Trailer {
+                        row_count,
+                        execution_time_ms: elapsed_ms(start_time),
+                        truncated: true,
+                    };
+                    Some((frame, None))
+                }
+                Some(Err(e)) => {
+                    tracing::error!(error = ?e, row_count = row_count, "query stream failed");
+                    let frame = QueryStreamFrame::Error {
+                        code: e.code().to_string(),
+                        message: e.message().to_string(),
+                    };
+                    Some((frame, None))
+                }
+                None => {
+                    let frame = QueryStreamFrame::Trailer {
+                        row_count,
+                        execution_time_ms: elapsed_ms(start_time),
+                        truncated: clamped_rows == Some(row_count),
+                    };
+                    Some((frame, None))
                 }
-                return serde_json::Value::String(text);
             }
-        }
-        
-        // Default to null if we can't determine the type
-        serde_json::Value::Null
+        });
+
+        Ok(header.chain(body))
+    }
+}
+
+/// Milliseconds elapsed since `start_time`
+fn elapsed_ms(start_time: Instant) -> u64 {
+    #[allow(clippy::cast_possible_truncation)]
+    let elapsed = start_time.elapsed().as_millis() as u64;
+    elapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::SqliteDriver;
+    use crate::models::query::LogicalType;
+    use crate::services::query_cursor::page_size;
+    use crate::services::sql_validator::{validate_sql, validate_sql_for_paging};
+
+    /// Run `sql` as is, without validation
+    fn unchecked(sql: &str) -> ValidatedSql {
+        ValidatedSql { sql: sql.to_string(), clamped: false }
+    }
+
+    #[tokio::test]
+    async fn test_stream_query_frames() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (id INTEGER, label TEXT);
+             INSERT INTO items VALUES (1, 'a'), (2, 'b');"
+        ).unwrap();
+        let driver = SqliteDriver::from_connection(conn);
+
+        let frames: Vec<QueryStreamFrame> = QueryExecutor::stream_query(&driver, &unchecked("SELECT id, label FROM items ORDER BY id"), "q1", &ConnectionSettings::default())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(frames.len(), 4);
+        assert!(matches!(
+            &frames[0],
+            QueryStreamFrame::Header { query_id, columns }
+                if query_id == "q1" && columns[0].name == "id" && columns[0].logical_type == LogicalType::Integer
+        ));
+        assert_eq!(frames[1], QueryStreamFrame::Row { values: vec![serde_json::json!(1), serde_json::json!("a")] });
+        assert!(matches!(frames[3], QueryStreamFrame::Trailer { row_count: 2, .. }));
+
+        // Frames serialize as tagged objects, one per NDJSON line
+        let trailer = serde_json::to_value(&frames[3]).unwrap();
+        assert_eq!(trailer["type"], "trailer");
+        assert_eq!(trailer["rowCount"], 2);
+        assert_eq!(serde_json::to_value(&frames[2]).unwrap(), serde_json::json!({"type": "row", "values": [2, "b"]}));
+    }
+
+    #[tokio::test]
+    async fn test_limits_truncate_results() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let driver = SqliteDriver::from_connection(conn);
+        let sql = &unchecked("WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 10) SELECT x FROM n");
+
+        let settings = ConnectionSettings { max_rows: 4, ..ConnectionSettings::default() };
+        let response = QueryExecutor::execute_query(&driver, sql, "rows", &settings).await.unwrap();
+        assert_eq!(response.row_count, 4);
+        assert!(response.truncated);
+
+        // Rows serialize as `[1]` .. `[9]` (3 bytes each) and `[10]`
+        let settings = ConnectionSettings { max_response_bytes: 10, ..ConnectionSettings::default() };
+        let frames: Vec<QueryStreamFrame> = QueryExecutor::stream_query(&driver, sql, "bytes", &settings)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert!(matches!(frames.last(), Some(QueryStreamFrame::Trailer { row_count: 3, truncated: true, .. })));
+
+        let response = QueryExecutor::execute_query(&driver, sql, "all", &ConnectionSettings::default()).await.unwrap();
+        assert_eq!(response.row_count, 10);
+        assert!(!response.truncated);
+
+        // A LIMIT lowered to the maximum rows cuts the result short too
+        let settings = ConnectionSettings { max_rows: 4, ..ConnectionSettings::default() };
+        let clamped = validate_sql(&format!("{} LIMIT 8", sql.sql), driver.dialect(), &settings).unwrap();
+        let response = QueryExecutor::execute_query(&driver, &clamped, "clamped", &settings).await.unwrap();
+        assert_eq!(response.row_count, 4);
+        assert!(response.truncated);
+        let frames: Vec<QueryStreamFrame> = QueryExecutor::stream_query(&driver, &clamped, "clamped", &settings)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert!(matches!(frames.last(), Some(QueryStreamFrame::Trailer { row_count: 4, truncated: true, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_paged_query_is_not_cut_at_default_limit() {
+        let driver = SqliteDriver::from_connection(rusqlite::Connection::open_in_memory().unwrap());
+        let settings = ConnectionSettings::default();
+        let cursors = QueryCursorStore::new();
+        let sql = validate_sql_for_paging(
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 1001) SELECT x FROM n",
+            driver.dialect(),
+            &settings,
+        ).unwrap();
+
+        let first = QueryExecutor::execute_paged(&driver, &sql, "q", &settings, &cursors, "db", page_size(None)).await.unwrap();
+        assert_eq!(first.row_count, 1000);
+        assert!(first.has_more);
+        assert!(!first.truncated);
+
+        let last = cursors.next_page("db", &first.cursor.unwrap(), page_size(None)).await.unwrap();
+        assert_eq!(last.rows, vec![vec![serde_json::json!(1001)]]);
+        assert!(!last.has_more);
+    }
+
+    #[test]
+    fn test_query_id() {
+        assert_eq!(query_id(Some("run-42_a".to_string())).unwrap(), "run-42_a");
+        assert_eq!(query_id(None).unwrap().len(), 32);
+        assert!(query_id(Some(String::new())).is_err());
+        assert!(query_id(Some("a b".to_string())).is_err());
+        assert!(query_id(Some("x".repeat(MAX_QUERY_ID_LEN + 1))).is_err());
     }
 }
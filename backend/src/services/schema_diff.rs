@@ -0,0 +1,277 @@
+// Structural comparison of schema metadata
+
+use crate::models::schema::{SchemaMetadata, TableInfo, ViewInfo};
+use crate::models::schema_diff::{
+    ColumnTypeChange, DefaultChange, NullabilityChange, PrimaryKeyChange, SchemaDiff, TableDiff,
+};
+use std::collections::BTreeMap;
+
+/// Compare the tables and views of two schemas, reporting what changed going from `from` to `to`
+///
+/// Columns are compared by type, nullability and default. Partitions are compared like any other
+/// table. Row counts, statistics and comments are ignored,
+/// and so are functions, sequences and types.
+#[must_use]
+pub fn diff_schemas(from: &SchemaMetadata, to: &SchemaMetadata) -> SchemaDiff {
+    let from_tables = tables_by_name(&from.tables);
+    let to_tables = tables_by_name(&to.tables);
+
+    let mut diff = SchemaDiff::default();
+    for (name, table) in &to_tables {
+        match from_tables.get(name) {
+            Some(previous) => {
+                let table_diff = diff_table(previous, table);
+                if !table_diff.is_empty() {
+                    diff.changed_tables.push(table_diff);
+                }
+            }
+            None => diff.added_tables.push(name.clone()),
+        }
+    }
+    diff.dropped_tables = from_tables
+        .keys()
+        .filter(|name| !to_tables.contains_key(*name))
+        .cloned()
+        .collect();
+
+    let from_views = views_by_name(from);
+    let to_views = views_by_name(to);
+    for (name, view) in &to_views {
+        match from_views.get(name) {
+            Some(previous) if previous.definition != view.definition => diff.changed_views.push(name.clone()),
+            Some(_) => {}
+            None => diff.added_views.push(name.clone()),
+        }
+    }
+    diff.dropped_views = from_views
+        .keys()
+        .filter(|name| !to_views.contains_key(*name))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+/// Tables keyed by qualified name, with partitions alongside their parent
+pub(crate) fn tables_by_name(tables: &[TableInfo]) -> BTreeMap<String, &TableInfo> {
+    let mut by_name = BTreeMap::new();
+    let mut pending: Vec<&TableInfo> = tables.iter().collect();
+    while let Some(table) = pending.pop() {
+        by_name.insert(table.qualified_name(), table);
+        pending.extend(&table.partitions);
+    }
+    by_name
+}
+
+/// Views and materialized views keyed by qualified name
+pub(crate) fn views_by_name(schema: &SchemaMetadata) -> BTreeMap<String, &ViewInfo> {
+    schema
+        .views
+        .iter()
+        .chain(&schema.materialized_views)
+        .map(|view| (view.qualified_name(), view))
+        .collect()
+}
+
+fn diff_table(from: &TableInfo, to: &TableInfo) -> TableDiff {
+    let mut diff = TableDiff {
+        table: to.qualified_name(),
+        ..TableDiff::default()
+    };
+
+    for column in &to.columns {
+        let Some(previous) = from.columns.iter().find(|c| c.name == column.name) else {
+            diff.added_columns.push(column.clone());
+            continue;
+        };
+        if previous.data_type != column.data_type {
+            diff.retyped_columns.push(ColumnTypeChange {
+                column: column.name.clone(),
+                from: previous.data_type.clone(),
+                to: column.data_type.clone(),
+            });
+        }
+        if previous.nullable != column.nullable {
+            diff.nullability_changes.push(NullabilityChange {
+                column: column.name.clone(),
+                nullable: column.nullable,
+            });
+        }
+        if previous.default_value != column.default_value {
+            diff.default_changes.push(DefaultChange {
+                column: column.name.clone(),
+                default: column.default_value.clone(),
+            });
+        }
+    }
+    diff.dropped_columns = from
+        .columns
+        .iter()
+        .filter(|column| !to.columns.iter().any(|c| c.name == column.name))
+        .map(|column| column.name.clone())
+        .collect();
+
+    if from.primary_key != to.primary_key {
+        diff.primary_key_change = Some(PrimaryKeyChange {
+            from: from.primary_key.clone(),
+            to: to.primary_key.clone(),
+        });
+    }
+
+    // An index or constraint whose definition changed is reported as dropped and added again
+    (diff.added_indexes, diff.dropped_indexes) = diff_named(&from.indexes, &to.indexes, |index| &index.name);
+    (diff.added_foreign_keys, diff.dropped_foreign_keys) =
+        diff_named(&from.foreign_keys, &to.foreign_keys, |foreign_key| &foreign_key.name);
+    (diff.added_unique_constraints, diff.dropped_unique_constraints) =
+        diff_named(&from.unique_constraints, &to.unique_constraints, |unique| &unique.name);
+    (diff.added_check_constraints, diff.dropped_check_constraints) =
+        diff_named(&from.check_constraints, &to.check_constraints, |check| &check.name);
+
+    diff
+}
+
+/// Items only in `to`, and the names of items only in `from`
+fn diff_named<T: Clone + PartialEq>(from: &[T], to: &[T], name: impl Fn(&T) -> &String) -> (Vec<T>, Vec<String>) {
+    let added = to.iter().filter(|item| !from.contains(item)).cloned().collect();
+    let dropped = from.iter().filter(|item| !to.contains(item)).map(|item| name(item).clone()).collect();
+    (added, dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schema::builders::{column, index, schema, table};
+    use crate::models::schema::{CheckConstraintInfo, ColumnInfo, ForeignKeyInfo};
+
+    #[test]
+    fn test_diff_identical_schemas() {
+        let customers = table("customers", vec![column("id", "integer", false)]);
+        let from = schema(vec![customers.clone()]);
+        // Row counts are not part of the structure
+        let to = schema(vec![TableInfo { row_count: Some(42), ..customers }]);
+
+        let diff = diff_schemas(&from, &to);
+        assert!(diff.added_tables.is_empty());
+        assert!(diff.dropped_tables.is_empty());
+        assert!(diff.changed_tables.is_empty());
+        assert!(diff.added_views.is_empty());
+    }
+
+    #[test]
+    fn test_diff_tables_and_columns() {
+        let from = schema(vec![
+            TableInfo {
+                primary_key: Some(vec!["id".to_string()]),
+                ..table("customers", vec![
+                    column("id", "integer", false),
+                    column("email", "text", true),
+                    column("fax", "text", true),
+                ])
+            },
+            table("legacy", Vec::new()),
+        ]);
+        let to = schema(vec![
+            TableInfo {
+                primary_key: Some(vec!["id".to_string(), "email".to_string()]),
+                ..table("customers", vec![
+                    column("id", "bigint", false),
+                    ColumnInfo { default_value: Some("''::text".to_string()), ..column("email", "text", false) },
+                    column("phone", "text", true),
+                ])
+            },
+            table("invoices", Vec::new()),
+        ]);
+
+        let diff = diff_schemas(&from, &to);
+        assert_eq!(diff.added_tables, vec!["public.invoices"]);
+        assert_eq!(diff.dropped_tables, vec!["public.legacy"]);
+        assert_eq!(diff.changed_tables.len(), 1);
+
+        let customers = &diff.changed_tables[0];
+        assert_eq!(customers.table, "public.customers");
+        assert_eq!(customers.added_columns.len(), 1);
+        assert_eq!(customers.added_columns[0].name, "phone");
+        assert_eq!(customers.dropped_columns, vec!["fax"]);
+        assert_eq!(customers.retyped_columns.len(), 1);
+        assert_eq!(
+            (customers.retyped_columns[0].column.as_str(), customers.retyped_columns[0].from.as_str(), customers.retyped_columns[0].to.as_str()),
+            ("id", "integer", "bigint")
+        );
+        assert_eq!(customers.nullability_changes.len(), 1);
+        assert_eq!(customers.nullability_changes[0].column, "email");
+        assert!(!customers.nullability_changes[0].nullable);
+        assert_eq!(customers.default_changes.len(), 1);
+        assert_eq!(customers.default_changes[0].default.as_deref(), Some("''::text"));
+        let primary_key = customers.primary_key_change.as_ref().unwrap();
+        assert_eq!(primary_key.from, Some(vec!["id".to_string()]));
+        assert_eq!(primary_key.to, Some(vec!["id".to_string(), "email".to_string()]));
+    }
+
+    #[test]
+    fn test_diff_views() {
+        let view = |name: &str, definition: &str| ViewInfo {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            description: None,
+            columns: Vec::new(),
+            definition: Some(definition.to_string()),
+        };
+        let from = SchemaMetadata {
+            views: vec![view("active_customers", "SELECT id FROM customers"), view("old_report", "SELECT 1")],
+            ..schema(Vec::new())
+        };
+        let to = SchemaMetadata {
+            views: vec![view("active_customers", "SELECT id FROM customers WHERE active")],
+            materialized_views: vec![view("daily_totals", "SELECT 2")],
+            ..schema(Vec::new())
+        };
+
+        let diff = diff_schemas(&from, &to);
+        assert_eq!(diff.added_views, vec!["public.daily_totals"]);
+        assert_eq!(diff.dropped_views, vec!["public.old_report"]);
+        assert_eq!(diff.changed_views, vec!["public.active_customers"]);
+    }
+
+    #[test]
+    fn test_diff_indexes_constraints_and_partitions() {
+        let foreign_key = ForeignKeyInfo {
+            name: "orders_customer_id_fkey".to_string(),
+            columns: vec!["customer_id".to_string()],
+            referenced_schema: "public".to_string(),
+            referenced_table: "customers".to_string(),
+            referenced_columns: vec!["id".to_string()],
+            on_delete: "NO ACTION".to_string(),
+        };
+        let check = |expression: &str| CheckConstraintInfo {
+            name: "orders_customer_id_check".to_string(),
+            expression: expression.to_string(),
+        };
+        let orders = table("orders", vec![column("customer_id", "integer", false)]);
+        let from = schema(vec![TableInfo {
+            indexes: vec![index("orders_customer_idx", &["customer_id"], false), index("orders_old_idx", &["customer_id"], false)],
+            check_constraints: vec![check("customer_id > 0")],
+            partitions: vec![table("orders_2024", Vec::new())],
+            ..orders.clone()
+        }]);
+        let to = schema(vec![TableInfo {
+            // The index became unique, so it is dropped and created again
+            indexes: vec![index("orders_customer_idx", &["customer_id"], true)],
+            foreign_keys: vec![foreign_key.clone()],
+            check_constraints: vec![check("customer_id >= 0")],
+            partitions: vec![table("orders_2024", Vec::new()), table("orders_2025", Vec::new())],
+            ..orders
+        }]);
+
+        let diff = diff_schemas(&from, &to);
+        assert_eq!(diff.added_tables, vec!["public.orders_2025"]);
+        assert!(diff.dropped_tables.is_empty());
+
+        let orders = &diff.changed_tables[0];
+        assert_eq!(orders.added_indexes, vec![index("orders_customer_idx", &["customer_id"], true)]);
+        assert_eq!(orders.dropped_indexes, vec!["orders_customer_idx", "orders_old_idx"]);
+        assert_eq!(orders.added_foreign_keys, vec![foreign_key]);
+        assert!(orders.dropped_foreign_keys.is_empty());
+        assert_eq!(orders.added_check_constraints, vec![check("customer_id >= 0")]);
+        assert_eq!(orders.dropped_check_constraints, vec!["orders_customer_id_check"]);
+    }
+}
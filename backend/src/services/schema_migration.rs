@@ -0,0 +1,471 @@
+// Best-effort PostgreSQL migration between two schemas
+
+use crate::models::schema::{
+    CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, IndexInfo, SchemaMetadata, TableInfo, TableKind,
+    UniqueConstraintInfo, ViewInfo,
+};
+use crate::models::schema_diff::TableDiff;
+use crate::services::schema_diff::{diff_schemas, tables_by_name, views_by_name};
+use std::collections::HashSet;
+
+/// Write the `PostgreSQL` statements that change schema `current` into schema `desired`
+///
+/// The script is meant to be reviewed before it is run. It covers tables, columns, indexes,
+/// foreign keys and views, but not functions, sequences or types, and it does not move data:
+/// e.g. adding a NOT NULL column without a default to a table with rows fails.
+#[must_use]
+pub fn migration_script(current: &SchemaMetadata, desired: &SchemaMetadata) -> String {
+    let diff = diff_schemas(current, desired);
+    let current_tables = tables_by_name(&current.tables);
+    let desired_tables = tables_by_name(&desired.tables);
+    let current_views = views_by_name(current);
+    let desired_views = views_by_name(desired);
+    let added_tables: HashSet<&str> = diff.added_tables.iter().map(String::as_str).collect();
+    let dropped_tables: HashSet<&str> = diff.dropped_tables.iter().map(String::as_str).collect();
+
+    // Partitions inherit their columns, indexes and foreign keys from their parent
+    let changed_tables: Vec<_> = diff
+        .changed_tables
+        .iter()
+        .filter_map(|change| {
+            let table = desired_tables.get(&change.table)?;
+            Some((*table, *current_tables.get(&change.table)?, change))
+        })
+        .filter(|(table, _, _)| table.partition_bound.is_none())
+        .collect();
+
+    let mut statements = Vec::new();
+
+    // Constraints and views go first, as they may depend on what is dropped or changed
+    for (table, previous, change) in &changed_tables {
+        if change.primary_key_change.as_ref().is_some_and(|primary_key| primary_key.from.is_some()) {
+            statements.push(format!(
+                "ALTER TABLE {} DROP CONSTRAINT {};",
+                table_name(table),
+                quote(&primary_key_name(previous))
+            ));
+        }
+        let constraints = change
+            .dropped_foreign_keys
+            .iter()
+            .chain(&change.dropped_unique_constraints)
+            .chain(&change.dropped_check_constraints);
+        for name in constraints {
+            statements.push(format!("ALTER TABLE {} DROP CONSTRAINT {};", table_name(table), quote(name)));
+        }
+    }
+    for name in diff.dropped_views.iter().chain(&diff.changed_views) {
+        if let Some(view) = current_views.get(name) {
+            statements.push(format!("DROP {} {};", view_keyword(current, view), view_name(view)));
+        }
+    }
+    // Indexes backing a constraint go away with it
+    for (_, previous, change) in &changed_tables {
+        let dropped = previous
+            .indexes
+            .iter()
+            .filter(|index| change.dropped_indexes.contains(&index.name) && !backs_constraint(previous, index));
+        for index in dropped {
+            statements.push(format!("DROP INDEX {}.{};", quote(&previous.schema), quote(&index.name)));
+        }
+    }
+    // Dropping a partitioned table drops its partitions
+    walk_tables(&current.tables, None, &mut |table, _| {
+        let dropped = dropped_tables.contains(table.qualified_name().as_str());
+        if dropped {
+            statements.push(format!("DROP TABLE {};", table_name(table)));
+        }
+        !dropped
+    });
+
+    let mut new_tables = Vec::new();
+    walk_tables(&desired.tables, None, &mut |table, parent| {
+        if added_tables.contains(table.qualified_name().as_str()) {
+            statements.push(create_table(table, parent));
+            if table.partition_bound.is_none() && table.kind != TableKind::Foreign {
+                new_tables.push(table);
+            }
+        }
+        true
+    });
+
+    for (table, _, change) in &changed_tables {
+        statements.extend(alter_table(table, change));
+    }
+
+    // Indexes and foreign keys are added once every table exists
+    for table in &new_tables {
+        for index in table.indexes.iter().filter(|index| !backs_constraint(table, index)) {
+            statements.push(create_index(table, index));
+        }
+    }
+    for (table, _, change) in &changed_tables {
+        for index in change.added_indexes.iter().filter(|index| !backs_constraint(table, index)) {
+            statements.push(create_index(table, index));
+        }
+    }
+    for table in &new_tables {
+        for foreign_key in &table.foreign_keys {
+            statements.push(add_foreign_key(table, foreign_key));
+        }
+    }
+    for (table, _, change) in &changed_tables {
+        for foreign_key in &change.added_foreign_keys {
+            statements.push(add_foreign_key(table, foreign_key));
+        }
+    }
+
+    for name in diff.added_views.iter().chain(&diff.changed_views) {
+        if let Some(view) = desired_views.get(name) {
+            statements.push(create_view(desired, view));
+        }
+    }
+
+    if statements.is_empty() {
+        return "-- The schemas already match\n".to_string();
+    }
+    format!("BEGIN;\n\n{}\n\nCOMMIT;\n", statements.join("\n"))
+}
+
+/// Visit tables before their partitions, descending into a table's partitions while `visit` returns true
+fn walk_tables<'a>(
+    tables: &'a [TableInfo],
+    parent: Option<&'a TableInfo>,
+    visit: &mut impl FnMut(&'a TableInfo, Option<&'a TableInfo>) -> bool,
+) {
+    for table in tables {
+        if visit(table, parent) {
+            walk_tables(&table.partitions, Some(table), visit);
+        }
+    }
+}
+
+/// Change the columns of a table and add its new primary key, unique and check constraints
+fn alter_table(table: &TableInfo, change: &TableDiff) -> Vec<String> {
+    let name = table_name(table);
+    let mut statements = Vec::new();
+
+    for column in &change.added_columns {
+        statements.push(format!("ALTER TABLE {name} ADD COLUMN {};", column_definition(column)));
+    }
+    for column in &change.dropped_columns {
+        statements.push(format!("ALTER TABLE {name} DROP COLUMN {};", quote(column)));
+    }
+    for retyped in &change.retyped_columns {
+        let column = quote(&retyped.column);
+        statements.push(format!(
+            "ALTER TABLE {name} ALTER COLUMN {column} TYPE {} USING {column}::{};",
+            retyped.to, retyped.to
+        ));
+    }
+    for default in &change.default_changes {
+        let column = quote(&default.column);
+        statements.push(match &default.default {
+            Some(default) => format!("ALTER TABLE {name} ALTER COLUMN {column} SET DEFAULT {default};"),
+            None => format!("ALTER TABLE {name} ALTER COLUMN {column} DROP DEFAULT;"),
+        });
+    }
+    for nullability in &change.nullability_changes {
+        let action = if nullability.nullable { "DROP" } else { "SET" };
+        statements.push(format!(
+            "ALTER TABLE {name} ALTER COLUMN {} {action} NOT NULL;",
+            quote(&nullability.column)
+        ));
+    }
+    if let Some(primary_key) = change.primary_key_change.as_ref().and_then(|change| change.to.as_ref()) {
+        statements.push(format!("ALTER TABLE {name} ADD PRIMARY KEY ({});", quote_list(primary_key)));
+    }
+    for unique in &change.added_unique_constraints {
+        statements.push(format!("ALTER TABLE {name} ADD {};", unique_constraint(unique)));
+    }
+    for check in &change.added_check_constraints {
+        statements.push(format!("ALTER TABLE {name} ADD {};", check_constraint(check)));
+    }
+
+    statements
+}
+
+fn create_table(table: &TableInfo, parent: Option<&TableInfo>) -> String {
+    let name = table_name(table);
+    if table.kind == TableKind::Foreign {
+        return format!("-- {name} is a foreign table, create it with CREATE FOREIGN TABLE and its server's options");
+    }
+    if let (Some(parent), Some(bound)) = (parent, &table.partition_bound) {
+        return format!("CREATE TABLE {name} PARTITION OF {} {bound};", table_name(parent));
+    }
+
+    let mut lines: Vec<String> = table.columns.iter().map(column_definition).collect();
+    if let Some(primary_key) = &table.primary_key {
+        lines.push(format!("PRIMARY KEY ({})", quote_list(primary_key)));
+    }
+    lines.extend(table.unique_constraints.iter().map(unique_constraint));
+    lines.extend(table.check_constraints.iter().map(check_constraint));
+
+    let partition_by = table
+        .partition_key
+        .as_ref()
+        .map(|key| format!(" PARTITION BY {key}"))
+        .unwrap_or_default();
+    format!("CREATE TABLE {name} (\n    {}\n){partition_by};", lines.join(",\n    "))
+}
+
+/// Column as written in CREATE TABLE or ADD COLUMN
+///
+/// A column filled from its own sequence is written as a serial column, which creates the sequence.
+fn column_definition(column: &ColumnInfo) -> String {
+    let serial = match column.data_type.as_str() {
+        "smallint" => Some("smallserial"),
+        "integer" => Some("serial"),
+        "bigint" => Some("bigserial"),
+        _ => None,
+    }
+    .filter(|_| column.default_value.as_deref().is_some_and(|default| default.starts_with("nextval(")));
+
+    let mut definition = format!("{} {}", quote(&column.name), serial.unwrap_or(&column.data_type));
+    if !column.nullable {
+        definition.push_str(" NOT NULL");
+    }
+    if let (None, Some(default)) = (serial, &column.default_value) {
+        definition.push_str(" DEFAULT ");
+        definition.push_str(default);
+    }
+    definition
+}
+
+fn unique_constraint(unique: &UniqueConstraintInfo) -> String {
+    format!("CONSTRAINT {} UNIQUE ({})", quote(&unique.name), quote_list(&unique.columns))
+}
+
+fn check_constraint(check: &CheckConstraintInfo) -> String {
+    format!("CONSTRAINT {} CHECK ({})", quote(&check.name), check.expression)
+}
+
+/// Name of a table's primary key constraint, which is also the name of the index backing it
+///
+/// Without that index in the metadata, `PostgreSQL`'s default name is assumed.
+fn primary_key_name(table: &TableInfo) -> String {
+    table
+        .indexes
+        .iter()
+        .find(|index| backs_primary_key(table, index))
+        .map_or_else(|| format!("{}_pkey", table.name), |index| index.name.clone())
+}
+
+/// Whether an index is the one `PostgreSQL` creates for the table's primary key or a unique constraint
+fn backs_constraint(table: &TableInfo, index: &IndexInfo) -> bool {
+    table.unique_constraints.iter().any(|unique| unique.name == index.name) || backs_primary_key(table, index)
+}
+
+fn backs_primary_key(table: &TableInfo, index: &IndexInfo) -> bool {
+    index.unique && index.predicate.is_none() && table.primary_key.as_ref() == Some(&index.columns)
+}
+
+fn create_index(table: &TableInfo, index: &IndexInfo) -> String {
+    // Expression columns are written as returned by the database, plain columns are quoted
+    let columns: Vec<String> = index
+        .columns
+        .iter()
+        .map(|column| if table.columns.iter().any(|c| &c.name == column) { quote(column) } else { column.clone() })
+        .collect();
+    let predicate = index.predicate.as_ref().map(|predicate| format!(" WHERE {predicate}")).unwrap_or_default();
+
+    format!(
+        "CREATE {}INDEX {} ON {} USING {} ({}){predicate};",
+        if index.unique { "UNIQUE " } else { "" },
+        quote(&index.name),
+        table_name(table),
+        index.method,
+        columns.join(", "),
+    )
+}
+
+fn add_foreign_key(table: &TableInfo, foreign_key: &ForeignKeyInfo) -> String {
+    format!(
+        "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}.{} ({}) ON DELETE {};",
+        table_name(table),
+        quote(&foreign_key.name),
+        quote_list(&foreign_key.columns),
+        quote(&foreign_key.referenced_schema),
+        quote(&foreign_key.referenced_table),
+        quote_list(&foreign_key.referenced_columns),
+        foreign_key.on_delete,
+    )
+}
+
+fn create_view(schema: &SchemaMetadata, view: &ViewInfo) -> String {
+    let keyword = view_keyword(schema, view);
+    match &view.definition {
+        Some(definition) => format!(
+            "CREATE {keyword} {} AS\n{};",
+            view_name(view),
+            definition.trim().trim_end_matches(';')
+        ),
+        None => format!("-- The definition of {keyword} {} is unknown", view_name(view)),
+    }
+}
+
+/// `VIEW` or `MATERIALIZED VIEW`, depending on which list of `schema` holds `view`
+fn view_keyword(schema: &SchemaMetadata, view: &ViewInfo) -> &'static str {
+    let name = view.qualified_name();
+    if schema.materialized_views.iter().any(|v| v.qualified_name() == name) {
+        "MATERIALIZED VIEW"
+    } else {
+        "VIEW"
+    }
+}
+
+fn table_name(table: &TableInfo) -> String {
+    format!("{}.{}", quote(&table.schema), quote(&table.name))
+}
+
+fn view_name(view: &ViewInfo) -> String {
+    format!("{}.{}", quote(&view.schema), quote(&view.name))
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_list(names: &[String]) -> String {
+    names.iter().map(|name| quote(name)).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schema::builders::{column, index, schema, table};
+
+    #[test]
+    fn test_migration_of_matching_schemas() {
+        let customers = schema(vec![table("customers", vec![column("id", "integer", false)])]);
+        assert_eq!(migration_script(&customers, &customers), "-- The schemas already match\n");
+    }
+
+    #[test]
+    fn test_migration_creates_tables_with_constraints() {
+        let id = ColumnInfo {
+            default_value: Some("nextval('invoices_id_seq'::regclass)".to_string()),
+            ..column("id", "integer", false)
+        };
+        let status = ColumnInfo { default_value: Some("'open'::text".to_string()), ..column("status", "text", false) };
+        let invoices = TableInfo {
+            primary_key: Some(vec!["id".to_string()]),
+            unique_constraints: vec![UniqueConstraintInfo { name: "invoices_number_key".to_string(), columns: vec!["number".to_string()] }],
+            indexes: vec![
+                index("invoices_pkey", &["id"], true),
+                index("invoices_number_key", &["number"], true),
+                IndexInfo { predicate: Some("status = 'open'::text".to_string()), ..index("invoices_open_idx", &["lower(status)"], false) },
+            ],
+            foreign_keys: vec![ForeignKeyInfo {
+                name: "invoices_customer_id_fkey".to_string(),
+                columns: vec!["customer_id".to_string()],
+                referenced_schema: "public".to_string(),
+                referenced_table: "customers".to_string(),
+                referenced_columns: vec!["id".to_string()],
+                on_delete: "CASCADE".to_string(),
+            }],
+            ..table("invoices", vec![id, column("number", "character varying(20)", true), column("customer_id", "integer", true), status])
+        };
+        let events = TableInfo {
+            kind: TableKind::Partitioned,
+            partition_key: Some("LIST (region)".to_string()),
+            partitions: vec![TableInfo {
+                partition_bound: Some("FOR VALUES IN ('eu')".to_string()),
+                ..table("events_eu", vec![column("region", "text", false)])
+            }],
+            ..table("events", vec![column("region", "text", false)])
+        };
+
+        let script = migration_script(&schema(Vec::new()), &schema(vec![invoices, events]));
+        assert!(script.starts_with("BEGIN;\n"));
+        assert!(script.ends_with("COMMIT;\n"));
+        assert!(script.contains(
+            "CREATE TABLE \"public\".\"invoices\" (\n    \"id\" serial NOT NULL,\n    \"number\" character varying(20),\n    \
+             \"customer_id\" integer,\n    \"status\" text NOT NULL DEFAULT 'open'::text,\n    PRIMARY KEY (\"id\"),\n    \
+             CONSTRAINT \"invoices_number_key\" UNIQUE (\"number\")\n);"
+        ));
+        // Indexes backing the primary key and unique constraint are created with the table
+        assert!(!script.contains("invoices_pkey"));
+        assert!(!script.contains("CREATE UNIQUE INDEX \"invoices_number_key\""));
+        assert!(script.contains(
+            "CREATE INDEX \"invoices_open_idx\" ON \"public\".\"invoices\" USING btree (lower(status)) WHERE status = 'open'::text;"
+        ));
+        assert!(script.contains(
+            "ALTER TABLE \"public\".\"invoices\" ADD CONSTRAINT \"invoices_customer_id_fkey\" FOREIGN KEY (\"customer_id\") \
+             REFERENCES \"public\".\"customers\" (\"id\") ON DELETE CASCADE;"
+        ));
+        assert!(script.contains("CREATE TABLE \"public\".\"events\" (\n    \"region\" text NOT NULL\n) PARTITION BY LIST (region);"));
+        assert!(script.contains("CREATE TABLE \"public\".\"events_eu\" PARTITION OF \"public\".\"events\" FOR VALUES IN ('eu');"));
+    }
+
+    #[test]
+    fn test_migration_alters_and_drops() {
+        let view = |definition: &str| ViewInfo {
+            schema: "public".to_string(),
+            name: "active_customers".to_string(),
+            description: None,
+            columns: Vec::new(),
+            definition: Some(definition.to_string()),
+        };
+        let current = SchemaMetadata {
+            views: vec![view(" SELECT customers.id\n   FROM customers;")],
+            ..schema(vec![
+                TableInfo {
+                    primary_key: Some(vec!["id".to_string()]),
+                    indexes: vec![
+                        index("customers_pk", &["id"], true),
+                        index("customers_email_idx", &["email"], false),
+                        index("customers_fax_key", &["fax"], true),
+                    ],
+                    unique_constraints: vec![UniqueConstraintInfo { name: "customers_fax_key".to_string(), columns: vec!["fax".to_string()] }],
+                    ..table("customers", vec![column("id", "integer", false), column("email", "text", true), column("fax", "text", true)])
+                },
+                TableInfo {
+                    partitions: vec![TableInfo { partition_bound: Some("DEFAULT".to_string()), ..table("legacy_default", Vec::new()) }],
+                    ..table("legacy", Vec::new())
+                },
+            ])
+        };
+        let desired = SchemaMetadata {
+            views: vec![view(" SELECT customers.id\n   FROM customers\n  WHERE customers.active;")],
+            ..schema(vec![TableInfo {
+                primary_key: Some(vec!["id".to_string(), "email".to_string()]),
+                indexes: vec![index("customers_pkey", &["id", "email"], true), index("customers_email_idx", &["email"], true)],
+                check_constraints: vec![CheckConstraintInfo { name: "customers_email_check".to_string(), expression: "email ~~ '%@%'::text".to_string() }],
+                ..table("customers", vec![
+                    column("id", "bigint", false),
+                    ColumnInfo { default_value: Some("''::text".to_string()), ..column("email", "text", false) },
+                    column("active", "boolean", true),
+                ])
+            }])
+        };
+
+        let script = migration_script(&current, &desired);
+        let position = |statement: &str| script.find(statement).unwrap_or_else(|| panic!("missing {statement} in {script}"));
+
+        // The view is dropped before the columns it reads change, and created again afterwards
+        let drop_view = position("DROP VIEW \"public\".\"active_customers\";");
+        let retype = position("ALTER TABLE \"public\".\"customers\" ALTER COLUMN \"id\" TYPE bigint USING \"id\"::bigint;");
+        let create_view = position("CREATE VIEW \"public\".\"active_customers\" AS\nSELECT customers.id\n   FROM customers\n  WHERE customers.active;");
+        assert!(drop_view < retype && retype < create_view);
+
+        position("ALTER TABLE \"public\".\"customers\" ADD COLUMN \"active\" boolean;");
+        position("ALTER TABLE \"public\".\"customers\" DROP COLUMN \"fax\";");
+        position("ALTER TABLE \"public\".\"customers\" ALTER COLUMN \"email\" SET NOT NULL;");
+        position("ALTER TABLE \"public\".\"customers\" ALTER COLUMN \"email\" SET DEFAULT ''::text;");
+        // The primary key is dropped by the name of its index and added again once its columns are NOT NULL
+        assert!(position("ALTER TABLE \"public\".\"customers\" DROP CONSTRAINT \"customers_pk\";")
+            < position("ALTER TABLE \"public\".\"customers\" ADD PRIMARY KEY (\"id\", \"email\");"));
+        assert!(!script.contains("INDEX \"customers_pk"));
+        assert!(position("DROP INDEX \"public\".\"customers_email_idx\";")
+            < position("CREATE UNIQUE INDEX \"customers_email_idx\" ON \"public\".\"customers\" USING btree (\"email\");"));
+        // The unique constraint's index is dropped with the constraint
+        assert!(position("ALTER TABLE \"public\".\"customers\" DROP CONSTRAINT \"customers_fax_key\";")
+            < position("ALTER TABLE \"public\".\"customers\" DROP COLUMN \"fax\";"));
+        assert!(!script.contains("DROP INDEX \"public\".\"customers_fax_key\""));
+        position("ALTER TABLE \"public\".\"customers\" ADD CONSTRAINT \"customers_email_check\" CHECK (email ~~ '%@%'::text);");
+        // Dropping the partitioned table drops its partition
+        position("DROP TABLE \"public\".\"legacy\";");
+        assert!(!script.contains("legacy_default"));
+    }
+}
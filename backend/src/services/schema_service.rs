@@ -1,226 +1,256 @@
+use crate::drivers;
 use crate::error::AppError;
-use crate::models::schema::{SchemaMetadata, TableInfo, ViewInfo, ColumnInfo};
+use crate::models::database::{ConnectionSettings, DatabaseConnection};
+use crate::models::schema::{SchemaMetadata, TableInfo, TableKind, ViewInfo, ColumnInfo};
+use crate::models::schema_diff::{
+    ConnectionDiff, ConnectionDiffRequest, DiffSide, SchemaSnapshot, SnapshotDiff, SnapshotDiffRequest,
+};
 use crate::services::database_service::DatabaseService;
-use rusqlite::Connection;
+use crate::services::schema_diff::diff_schemas;
+use crate::services::schema_migration::migration_script;
+use crate::types::SharedConnectionPoolCache;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
 use serde_json;
-use sqlx::{PgPool, Row};
 use std::sync::{Arc, Mutex};
 
 pub struct SchemaService {
     sqlite_conn: Arc<Mutex<Connection>>,
     db_service: Arc<DatabaseService>,
+    pool_cache: SharedConnectionPoolCache,
 }
 
 impl SchemaService {
-    pub fn new(sqlite_conn: Arc<Mutex<Connection>>, db_service: Arc<DatabaseService>) -> Self {
+    pub fn new(
+        sqlite_conn: Arc<Mutex<Connection>>,
+        db_service: Arc<DatabaseService>,
+        pool_cache: SharedConnectionPoolCache,
+    ) -> Self {
         Self {
             sqlite_conn,
             db_service,
+            pool_cache,
         }
     }
 
     /// Retrieve schema metadata for a database
+    ///
+    /// Cached metadata older than the connection's schema cache TTL is read again. If that
+    /// fails, e.g. because the database is down, the cached metadata is returned marked stale.
     pub async fn get_schema_metadata(&self, db_name: &str) -> Result<SchemaMetadata, AppError> {
         // Check if database exists
-        self.db_service.get_connection(db_name)?;
+        let connection = self.db_service.get_connection(db_name)?;
 
         // Check cache first, but only use it if all tables have row_count
-        if let Ok(cached) = self.get_cached_metadata(db_name) {
-            // Check if cached data has row_count for all tables
-            let has_all_row_counts = cached.tables.iter().all(|table| table.row_count.is_some());
+        if let Ok(mut cached) = self.get_cached_metadata(db_name) {
+            // Check if cached data has row_count for all tables, foreign tables are never counted
+            let has_all_row_counts = cached
+                .tables
+                .iter()
+                .all(|table| table.row_count.is_some() || table.kind == TableKind::Foreign);
             if has_all_row_counts {
-                return Ok(cached);
+                if !is_expired(&cached.cached_at, connection.settings.schema_cache_ttl_secs) {
+                    return Ok(cached);
+                }
+                return match self.refresh(&connection).await {
+                    Ok(metadata) => Ok(metadata),
+                    Err(e) => {
+                        tracing::warn!(database_name = %db_name, error = ?e, "failed to refresh expired schema, using cached schema");
+                        cached.stale = true;
+                        Ok(cached)
+                    }
+                };
             }
             // If cache is missing row_count, clear it and fetch fresh data
             self.clear_cache(db_name)?;
         }
 
-        // Retrieve from target database
-        let connection = self.db_service.get_connection(db_name)?;
-        let mut metadata = self.retrieve_from_database(&connection.url, db_name).await?;
-        metadata.db_name = db_name.to_string();
-
-        // Cache the metadata
-        self.cache_metadata(db_name, &metadata)?;
+        self.refresh(&connection).await
+    }
 
-        Ok(metadata)
+    /// Read the schema of a database again, replacing its cached metadata
+    pub async fn refresh_schema_metadata(&self, db_name: &str) -> Result<SchemaMetadata, AppError> {
+        let connection = self.db_service.get_connection(db_name)?;
+        self.refresh(&connection).await
     }
 
-    /// Retrieve schema from `PostgreSQL` database
-    async fn retrieve_from_database(&self, url: &str, db_name: &str) -> Result<SchemaMetadata, AppError> {
-        if !url.starts_with("postgres://") && !url.starts_with("postgresql://") {
-            return Err(AppError::ValidationError(
-                "Only PostgreSQL databases are supported".to_string(),
-            ));
+    /// Forget what was read through a connection before its URL or settings changed
+    ///
+    /// A new URL may point at another database, so the pooled driver is dropped too.
+    /// New settings, e.g. other schema lists, only need the schema to be read again.
+    pub async fn connection_updated(
+        &self,
+        previous: &DatabaseConnection,
+        current: &DatabaseConnection,
+    ) -> Result<(), AppError> {
+        if previous.url != current.url {
+            self.pool_cache.remove(&current.name).await;
         }
-
-        let pool = PgPool::connect(url).await?;
-
-        // Get all tables
-        let table_names: Vec<String> = sqlx::query_scalar(
-            "SELECT table_name FROM information_schema.tables
-             WHERE table_schema = 'public' AND table_type = 'BASE TABLE'
-             ORDER BY table_name"
-        )
-        .fetch_all(&pool)
-        .await?;
-
-        let mut tables = Vec::new();
-        for table_name in table_names {
-            tables.push(Self::fetch_table_info(&pool, &table_name).await?);
+        if previous.url != current.url || previous.settings != current.settings {
+            tracing::info!(database_name = %current.name, "connection changed, clearing cached schema");
+            self.clear_cache(&current.name)?;
         }
+        Ok(())
+    }
 
-        // Get all views
-        let view_names: Vec<String> = sqlx::query_scalar(
-            "SELECT table_name FROM information_schema.views
-             WHERE table_schema = 'public'
-             ORDER BY table_name"
-        )
-        .fetch_all(&pool)
-        .await?;
+    /// Read the schema of every connection whose cached metadata is older than its TTL
+    ///
+    /// Connections whose schema was never requested are left alone.
+    pub async fn refresh_expired(&self) -> Result<(), AppError> {
+        for connection in self.db_service.list_connections()? {
+            let Some(cached_at) = self.cache_time(&connection.name)? else {
+                continue;
+            };
+            if !is_expired(&cached_at, connection.settings.schema_cache_ttl_secs) {
+                continue;
+            }
 
-        let mut views = Vec::new();
-        for view_name in view_names {
-            views.push(Self::fetch_view_info(&pool, &view_name).await?);
+            tracing::info!(database_name = %connection.name, cached_at = %cached_at, "refreshing expired schema");
+            if let Err(e) = self.refresh(&connection).await {
+                tracing::warn!(database_name = %connection.name, error = ?e, "failed to refresh expired schema");
+            }
         }
-
-        Ok(SchemaMetadata {
-            db_name: db_name.to_string(),
-            tables,
-            views,
-            updated_at: chrono::Utc::now().to_rfc3339(),
-        })
+        Ok(())
     }
 
-    /// Fetch information for a single table
-    async fn fetch_table_info(pool: &PgPool, table_name: &str) -> Result<TableInfo, AppError> {
-        // Get columns for this table
-        let columns: Vec<(String, String, bool, Option<String>)> = sqlx::query(
-            "SELECT column_name, data_type, is_nullable = 'YES' as nullable, column_default
-             FROM information_schema.columns
-             WHERE table_schema = 'public' AND table_name = $1
-             ORDER BY ordinal_position"
-        )
-        .bind(table_name)
-        .map(|row: sqlx::postgres::PgRow| {
-            (
-                row.get(0),
-                row.get(1),
-                row.get(2),
-                row.get(3),
-            )
-        })
-        .fetch_all(pool)
-        .await?;
-
-        // Get primary key columns
-        let primary_key: Vec<String> = sqlx::query_scalar(
-            "SELECT kcu.column_name
-             FROM information_schema.table_constraints tc
-             JOIN information_schema.key_column_usage kcu
-                 ON tc.constraint_name = kcu.constraint_name
-             WHERE tc.table_schema = 'public'
-                 AND tc.table_name = $1
-                 AND tc.constraint_type = 'PRIMARY KEY'
-             ORDER BY kcu.ordinal_position"
-        )
-        .bind(table_name)
-        .fetch_all(pool)
-        .await?;
+    /// List the schema snapshots of a database, oldest first
+    pub fn list_snapshots(&self, db_name: &str) -> Result<Vec<SchemaSnapshot>, AppError> {
+        self.db_service.get_connection(db_name)?;
+
+        let conn = self.sqlite_conn.lock()
+            .map_err(|e| {
+                tracing::error!(error = ?e, "SQLite mutex poisoned");
+                AppError::DatabaseError(format!("Failed to acquire lock: {e:?}"))
+            })?;
+        let mut stmt = conn.prepare(
+            "SELECT version, created_at FROM schema_snapshots WHERE db_name = ?1 ORDER BY version"
+        )?;
+        let snapshots = stmt
+            .query_map([db_name], |row| Ok(SchemaSnapshot { version: row.get(0)?, created_at: row.get(1)? }))?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // Get row count for this table
-        let row_count = Self::fetch_table_row_count(pool, table_name).await;
+        Ok(snapshots)
+    }
 
-        let column_infos: Vec<ColumnInfo> = columns
-            .into_iter()
-            .map(|(name, data_type, nullable, default_value)| {
-                ColumnInfo {
-                    name,
-                    data_type,
-                    nullable,
-                    default_value,
-                }
+    /// Compare two schema snapshots of a database
+    ///
+    /// Without `to` the latest snapshot is compared, and without `from` or `since` the one before it.
+    pub fn diff_snapshots(&self, db_name: &str, request: &SnapshotDiffRequest) -> Result<SnapshotDiff, AppError> {
+        let snapshots = self.list_snapshots(db_name)?;
+        let find = |version: i64| {
+            snapshots.iter().find(|snapshot| snapshot.version == version).ok_or_else(|| {
+                AppError::NotFound(format!("Database '{db_name}' has no schema snapshot {version}"))
             })
-            .collect();
+        };
+
+        let to = match request.to {
+            Some(version) => find(version)?,
+            None => snapshots.last().ok_or_else(|| {
+                AppError::NotFound(format!("Database '{db_name}' has no schema snapshots yet"))
+            })?,
+        };
+        let from = match (request.from, request.since.as_deref()) {
+            (Some(_), Some(_)) => {
+                return Err(AppError::ValidationError("Pass either 'from' or 'since', not both".to_string()));
+            }
+            (Some(version), None) => find(version)?,
+            (None, Some(since)) => {
+                let since = parse_since(since)?;
+                snapshots
+                    .iter()
+                    .rev()
+                    .find(|snapshot| DateTime::parse_from_rfc3339(&snapshot.created_at).is_ok_and(|at| at <= since))
+                    .ok_or_else(|| {
+                        AppError::NotFound(format!("Database '{db_name}' has no schema snapshot taken by {}", since.to_rfc3339()))
+                    })?
+            }
+            (None, None) => snapshots.iter().rev().find(|snapshot| snapshot.version < to.version).ok_or_else(|| {
+                AppError::NotFound(format!("Database '{db_name}' has no schema snapshot before {}", to.version))
+            })?,
+        };
+
+        let changes = diff_schemas(
+            &self.snapshot_metadata(db_name, from.version)?,
+            &self.snapshot_metadata(db_name, to.version)?,
+        );
 
-        Ok(TableInfo {
-            name: table_name.to_string(),
-            columns: column_infos,
-            primary_key: if primary_key.is_empty() { None } else { Some(primary_key) },
-            row_count,
+        Ok(SnapshotDiff {
+            db_name: db_name.to_string(),
+            from: from.clone(),
+            to: to.clone(),
+            changes,
         })
     }
 
-    /// Fetch row count for a table (with validation and error handling)
-    async fn fetch_table_row_count(pool: &PgPool, table_name: &str) -> Option<u64> {
-        // Validate table name to prevent SQL injection
-        if !is_valid_identifier(table_name) {
-            tracing::warn!(table = %table_name, "invalid table name, skipping row count");
-            return None;
-        }
-
-        match sqlx::query_scalar::<_, i64>(
-            &format!("SELECT COUNT(*) FROM \"{table_name}\"")
-        )
-        .fetch_one(pool)
-        .await
-        {
-            Ok(count) => {
-                #[allow(clippy::cast_sign_loss)]
-                if count >= 0 {
-                    Some(count as u64)
-                } else {
-                    tracing::warn!(table = %table_name, count = count, "negative row count");
-                    None
+    /// Compare the schemas of two connections, e.g. staging and production
+    ///
+    /// With `migrate`, also write the `PostgreSQL` statements bringing that side in line with the other.
+    /// The script is built from both schemas' types and defaults, so both have to be `PostgreSQL`.
+    pub async fn diff_connections(&self, request: &ConnectionDiffRequest) -> Result<ConnectionDiff, AppError> {
+        if request.migrate.is_some() {
+            for name in [&request.left, &request.right] {
+                let kind = drivers::DatabaseKind::from_url(&self.db_service.get_connection(name)?.url);
+                if kind != Some(drivers::DatabaseKind::Postgres) {
+                    return Err(AppError::ValidationError(format!(
+                        "Migration scripts are written for PostgreSQL, '{name}' is a {} database",
+                        kind.map_or("different", drivers::DatabaseKind::display_name)
+                    )));
                 }
             }
-            Err(e) => {
-                tracing::warn!(
-                    table = %table_name,
-                    error = ?e,
-                    "failed to get row count"
-                );
-                None
-            }
         }
-    }
 
-    /// Fetch information for a single view
-    async fn fetch_view_info(pool: &PgPool, view_name: &str) -> Result<ViewInfo, AppError> {
-        // Get columns for this view
-        let columns: Vec<(String, String, bool, Option<String>)> = sqlx::query(
-            "SELECT column_name, data_type, is_nullable = 'YES' as nullable, column_default
-             FROM information_schema.columns
-             WHERE table_schema = 'public' AND table_name = $1
-             ORDER BY ordinal_position"
-        )
-        .bind(view_name)
-        .map(|row: sqlx::postgres::PgRow| {
-            (
-                row.get(0),
-                row.get(1),
-                row.get(2),
-                row.get(3),
-            )
+        let (left, right) = tokio::try_join!(
+            self.get_schema_metadata(&request.left),
+            self.get_schema_metadata(&request.right),
+        )?;
+        let migration = request.migrate.map(|side| match side {
+            DiffSide::Left => migration_script(&left, &right),
+            DiffSide::Right => migration_script(&right, &left),
+        });
+
+        Ok(ConnectionDiff {
+            left: request.left.clone(),
+            right: request.right.clone(),
+            changes: diff_schemas(&left, &right),
+            migration,
         })
-        .fetch_all(pool)
-        .await?;
+    }
 
-        let column_infos: Vec<ColumnInfo> = columns
-            .into_iter()
-            .map(|(name, data_type, nullable, default_value)| {
-                ColumnInfo {
-                    name,
-                    data_type,
-                    nullable,
-                    default_value,
-                }
-            })
-            .collect();
+    /// Retrieve the schema from the target database and cache it
+    ///
+    /// File connections load their data again first, so queries run against the same
+    /// data the refreshed schema describes. Other connections keep their pooled driver.
+    async fn refresh(&self, connection: &DatabaseConnection) -> Result<SchemaMetadata, AppError> {
+        self.pool_cache.reload(&connection.name).await?;
+        let metadata = self
+            .retrieve_from_database(&connection.url, &connection.settings, &connection.name)
+            .await?;
+        self.cache_metadata(&connection.name, &metadata)?;
+        Ok(metadata)
+    }
 
-        Ok(ViewInfo {
-            name: view_name.to_string(),
-            columns: column_infos,
+    /// Retrieve schema from the target database using its pooled driver
+    async fn retrieve_from_database(
+        &self,
+        url: &str,
+        settings: &ConnectionSettings,
+        db_name: &str,
+    ) -> Result<SchemaMetadata, AppError> {
+        let driver = self.pool_cache.get_or_create(db_name, url).await?;
+        let schema = driver.introspect(settings).await?;
+        let now = Utc::now().to_rfc3339();
+
+        Ok(SchemaMetadata {
+            db_name: db_name.to_string(),
+            tables: schema.tables,
+            views: schema.views,
+            materialized_views: schema.materialized_views,
+            functions: schema.functions,
+            sequences: schema.sequences,
+            updated_at: now.clone(),
+            cached_at: now,
+            stale: false,
         })
     }
 
@@ -233,28 +263,49 @@ impl SchemaService {
             })?;
 
         let mut stmt = conn.prepare(
-            "SELECT table_name, table_type, metadata_json FROM schema_metadata WHERE db_name = ?1"
+            "SELECT table_name, table_type, metadata_json, updated_at FROM schema_metadata WHERE db_name = ?1"
         )?;
 
-        let rows: Vec<(String, String, String)> = stmt
-            .query_map([db_name], |row| {
-                Ok((
-                    row.get(0)?,
-                    row.get(1)?,
-                    row.get(2)?,
-                ))
-            })?
+        let rows: Vec<(String, String, String, String)> = stmt
+            .query_map([db_name], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
             .collect::<Result<Vec<_>, _>>()?;
 
-        if rows.is_empty() {
+        // Every entry is written at once, with the time the schema was read
+        let Some(cached_at) = rows.iter().map(|(_, _, _, updated_at)| updated_at.clone()).min() else {
             return Err(AppError::NotFound("No cached metadata found".to_string()));
-        }
+        };
 
         let mut tables = Vec::new();
         let mut views = Vec::new();
+        let mut materialized_views = Vec::new();
+        let mut functions = Vec::new();
+        let mut sequences = Vec::new();
+
+        for (qualified_name, table_type, metadata_json, _) in rows {
+            // Functions and sequences are cached as serialized, they have no older format
+            match table_type.as_str() {
+                "function" => {
+                    functions.push(serde_json::from_str(&metadata_json)?);
+                    continue;
+                }
+                "sequence" => {
+                    sequences.push(serde_json::from_str(&metadata_json)?);
+                    continue;
+                }
+                _ => {}
+            }
 
-        for (table_name, table_type, metadata_json) in rows {
             let metadata: serde_json::Value = serde_json::from_str(&metadata_json)?;
+            // Entries cached before schemas were introspected are keyed by bare table name
+            let (Some(schema), Some(name)) = (
+                metadata.get("schema").and_then(serde_json::Value::as_str),
+                metadata.get("name").and_then(serde_json::Value::as_str),
+            ) else {
+                return Err(AppError::NotFound(format!(
+                    "Cached metadata for '{qualified_name}' has no schema"
+                )));
+            };
+            let (schema, name) = (schema.to_string(), name.to_string());
             let columns: Vec<ColumnInfo> = serde_json::from_value(
                 metadata.get("columns").cloned().unwrap_or(serde_json::json!([]))
             )?;
@@ -270,16 +321,36 @@ impl SchemaService {
                     .and_then(serde_json::Value::as_u64);
 
                 tables.push(TableInfo {
-                    name: table_name,
+                    schema,
+                    name,
+                    kind: cached_field(&metadata, "kind", &qualified_name)?,
+                    description: cached_field(&metadata, "description", &qualified_name)?,
                     columns,
                     primary_key,
                     row_count,
+                    row_count_estimated: cached_field(&metadata, "rowCountEstimated", &qualified_name)?,
+                    statistics: cached_field(&metadata, "statistics", &qualified_name)?,
+                    foreign_keys: cached_field(&metadata, "foreignKeys", &qualified_name)?,
+                    indexes: cached_field(&metadata, "indexes", &qualified_name)?,
+                    unique_constraints: cached_field(&metadata, "uniqueConstraints", &qualified_name)?,
+                    check_constraints: cached_field(&metadata, "checkConstraints", &qualified_name)?,
+                    partition_key: cached_field(&metadata, "partitionKey", &qualified_name)?,
+                    partition_bound: cached_field(&metadata, "partitionBound", &qualified_name)?,
+                    partitions: cached_field(&metadata, "partitions", &qualified_name)?,
                 });
             } else {
-                views.push(ViewInfo {
-                    name: table_name,
+                let view = ViewInfo {
+                    schema,
+                    name,
+                    description: cached_field(&metadata, "description", &qualified_name)?,
+                    definition: cached_field(&metadata, "definition", &qualified_name)?,
                     columns,
-                });
+                };
+                if table_type == "materialized_view" {
+                    materialized_views.push(view);
+                } else {
+                    views.push(view);
+                }
             }
         }
 
@@ -287,10 +358,45 @@ impl SchemaService {
             db_name: db_name.to_string(),
             tables,
             views,
-            updated_at: chrono::Utc::now().to_rfc3339(),
+            materialized_views,
+            functions,
+            sequences,
+            updated_at: cached_at.clone(),
+            cached_at,
+            stale: false,
         })
     }
 
+    /// Read the schema kept by a snapshot
+    fn snapshot_metadata(&self, db_name: &str, version: i64) -> Result<SchemaMetadata, AppError> {
+        let conn = self.sqlite_conn.lock()
+            .map_err(|e| {
+                tracing::error!(error = ?e, "SQLite mutex poisoned");
+                AppError::DatabaseError(format!("Failed to acquire lock: {e:?}"))
+            })?;
+        let metadata_json: String = conn.query_row(
+            "SELECT metadata_json FROM schema_snapshots WHERE db_name = ?1 AND version = ?2",
+            rusqlite::params![db_name, version],
+            |row| row.get(0),
+        )?;
+        Ok(serde_json::from_str(&metadata_json)?)
+    }
+
+    /// When the cached metadata of a database was read, if it is cached
+    fn cache_time(&self, db_name: &str) -> Result<Option<String>, AppError> {
+        let conn = self.sqlite_conn.lock()
+            .map_err(|e| {
+                tracing::error!(error = ?e, "SQLite mutex poisoned");
+                AppError::DatabaseError(format!("Failed to acquire lock: {e:?}"))
+            })?;
+        let cached_at = conn.query_row(
+            "SELECT MIN(updated_at) FROM schema_metadata WHERE db_name = ?1",
+            [db_name],
+            |row| row.get(0),
+        )?;
+        Ok(cached_at)
+    }
+
     /// Clear cache for a database
     fn clear_cache(&self, db_name: &str) -> Result<(), AppError> {
         let conn = self.sqlite_conn.lock()
@@ -302,32 +408,50 @@ impl SchemaService {
         Ok(())
     }
 
-    /// Cache metadata in `SQLite`
+    /// Cache metadata in `SQLite`, keeping it as the next snapshot in the schema history
+    ///
+    /// The cache is replaced in one transaction, so a failure leaves the previous cache in place.
+    /// A snapshot is only added when the structure changed since the latest one.
     fn cache_metadata(&self, db_name: &str, metadata: &SchemaMetadata) -> Result<(), AppError> {
-        let conn = self.sqlite_conn.lock()
+        let mut conn = self.sqlite_conn.lock()
             .map_err(|e| {
                 tracing::error!(error = ?e, "SQLite mutex poisoned");
                 AppError::DatabaseError(format!("Failed to acquire lock: {e:?}"))
             })?;
+        let tx = conn.transaction()?;
+
         // Delete existing cache
-        conn.execute("DELETE FROM schema_metadata WHERE db_name = ?1", [db_name])?;
+        tx.execute("DELETE FROM schema_metadata WHERE db_name = ?1", [db_name])?;
 
-        let now = chrono::Utc::now().to_rfc3339();
+        let now = &metadata.cached_at;
 
         // Cache tables
         for table in &metadata.tables {
             let metadata_json = serde_json::json!({
+                "schema": table.schema,
+                "name": table.name,
+                "description": table.description,
                 "columns": table.columns,
                 "primaryKey": table.primary_key,
                 "rowCount": table.row_count,
+                "rowCountEstimated": table.row_count_estimated,
+                "statistics": table.statistics,
+                "foreignKeys": table.foreign_keys,
+                "indexes": table.indexes,
+                "uniqueConstraints": table.unique_constraints,
+                "checkConstraints": table.check_constraints,
+                "kind": table.kind,
+                "partitionKey": table.partition_key,
+                "partitionBound": table.partition_bound,
+                "partitions": table.partitions,
             });
 
-            conn.execute(
+            tx.execute(
                 "INSERT INTO schema_metadata (db_name, table_name, table_type, metadata_json, updated_at) 
                  VALUES (?1, ?2, ?3, ?4, ?5)",
                 rusqlite::params![
                     db_name,
-                    table.name,
+                    table.qualified_name(),
                     "table",
                     metadata_json.to_string(),
                     now
@@ -335,35 +459,308 @@ impl SchemaService {
             )?;
         }
 
-        // Cache views
-        for view in &metadata.views {
+        // Cache views and materialized views
+        let views = metadata.views.iter().map(|view| (view, "view"))
+            .chain(metadata.materialized_views.iter().map(|view| (view, "materialized_view")));
+        for (view, table_type) in views {
             let metadata_json = serde_json::json!({
+                "schema": view.schema,
+                "name": view.name,
+                "description": view.description,
+                "definition": view.definition,
                 "columns": view.columns,
             });
 
-            conn.execute(
+            tx.execute(
                 "INSERT INTO schema_metadata (db_name, table_name, table_type, metadata_json, updated_at) 
                  VALUES (?1, ?2, ?3, ?4, ?5)",
                 rusqlite::params![
                     db_name,
-                    view.name,
-                    "view",
+                    view.qualified_name(),
+                    table_type,
                     metadata_json.to_string(),
                     now
                 ],
             )?;
         }
 
+        // Cache functions, keyed by signature since they can be overloaded
+        for function in &metadata.functions {
+            tx.execute(
+                "INSERT INTO schema_metadata (db_name, table_name, table_type, metadata_json, updated_at) 
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    db_name,
+                    function.signature(),
+                    "function",
+                    serde_json::to_string(function)?,
+                    now
+                ],
+            )?;
+        }
+
+        // Cache sequences
+        for sequence in &metadata.sequences {
+            tx.execute(
+                "INSERT INTO schema_metadata (db_name, table_name, table_type, metadata_json, updated_at) 
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    db_name,
+                    sequence.qualified_name(),
+                    "sequence",
+                    serde_json::to_string(sequence)?,
+                    now
+                ],
+            )?;
+        }
+
+        add_snapshot(&tx, db_name, metadata)?;
+        tx.commit()?;
         Ok(())
     }
 }
 
-/// Validate that a string is a valid `PostgreSQL` identifier
-/// This prevents SQL injection when using identifiers in format! macros
-fn is_valid_identifier(name: &str) -> bool {
-    !name.is_empty()
-        && name.len() <= 63 // PostgreSQL identifier length limit
-        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
-        && name.chars().next().is_some_and(|c| !c.is_ascii_digit()) // Can't start with digit
+/// Keep `metadata` as the next snapshot of a database, unless its structure matches the latest one
+///
+/// Snapshots are never overwritten, so versions only count up.
+fn add_snapshot(conn: &Connection, db_name: &str, metadata: &SchemaMetadata) -> Result<(), AppError> {
+    let latest: Option<String> = conn
+        .query_row(
+            "SELECT metadata_json FROM schema_snapshots WHERE db_name = ?1 ORDER BY version DESC LIMIT 1",
+            [db_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(latest) = latest
+        && diff_schemas(&serde_json::from_str(&latest)?, metadata).is_empty()
+    {
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO schema_snapshots (db_name, version, metadata_json, created_at)
+         SELECT ?1, COALESCE(MAX(version), 0) + 1, ?2, ?3 FROM schema_snapshots WHERE db_name = ?1",
+        rusqlite::params![db_name, serde_json::to_string(metadata)?, metadata.cached_at],
+    )?;
+    Ok(())
+}
+
+/// Read a field of a cached table or view
+///
+/// Entries cached before the field was introspected lack it, and are treated as not cached.
+fn cached_field<T: DeserializeOwned>(
+    metadata: &serde_json::Value,
+    key: &str,
+    qualified_name: &str,
+) -> Result<T, AppError> {
+    let value = metadata.get(key).ok_or_else(|| {
+        AppError::NotFound(format!("Cached metadata for '{qualified_name}' has no {key}"))
+    })?;
+    Ok(serde_json::from_value(value.clone())?)
+}
+
+/// Read the `since` bound of a snapshot diff, an RFC 3339 timestamp or a date taken as midnight UTC
+fn parse_since(since: &str) -> Result<DateTime<Utc>, AppError> {
+    if let Ok(since) = DateTime::parse_from_rfc3339(since) {
+        return Ok(since.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(since, "%Y-%m-%d")
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+        .map_err(|_| AppError::ValidationError(format!(
+            "Invalid 'since' value '{since}', expected an RFC 3339 timestamp or a YYYY-MM-DD date"
+        )))
+}
+
+/// Whether metadata cached at `cached_at` is older than `ttl_secs`; a TTL of 0 never expires
+fn is_expired(cached_at: &str, ttl_secs: u64) -> bool {
+    if ttl_secs == 0 {
+        return false;
+    }
+    let Ok(cached_at) = DateTime::parse_from_rfc3339(cached_at) else {
+        return true;
+    };
+    let Some(ttl) = i64::try_from(ttl_secs).ok().and_then(chrono::TimeDelta::try_seconds) else {
+        return false;
+    };
+    Utc::now().signed_duration_since(cached_at) >= ttl
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_db;
+    use crate::models::request::CreateDatabaseRequest;
+    use crate::types::ConnectionPoolCache;
+
+    #[test]
+    fn test_is_expired() {
+        let now = chrono::Utc::now();
+        let fresh = (now - chrono::TimeDelta::seconds(30)).to_rfc3339();
+        let old = (now - chrono::TimeDelta::seconds(7200)).to_rfc3339();
+
+        assert!(!is_expired(&fresh, 3600));
+        assert!(is_expired(&old, 3600));
+        // A TTL of 0 keeps the cache until it is refreshed explicitly
+        assert!(!is_expired(&old, 0));
+        // A cache time that cannot be read is treated as expired
+        assert!(is_expired("", 3600));
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_are_kept_as_snapshots() {
+        let target = std::env::temp_dir().join("db_query_schema_snapshots.db");
+        let _ = std::fs::remove_file(&target);
+        Connection::open(&target).unwrap()
+            .execute_batch("CREATE TABLE customers (id INTEGER PRIMARY KEY, fax TEXT);")
+            .unwrap();
+
+        let sqlite_conn = Arc::new(Mutex::new(init_db(":memory:").unwrap()));
+        let db_service = Arc::new(DatabaseService::new(Arc::clone(&sqlite_conn)));
+        let request = CreateDatabaseRequest { url: format!("sqlite://{}", target.display()), settings: None };
+        db_service.store_connection("app", &request).unwrap();
+        let pool_cache = Arc::new(ConnectionPoolCache::new());
+        let service = SchemaService::new(sqlite_conn, db_service, Arc::clone(&pool_cache));
+
+        // Reading the cached schema does not take a snapshot
+        service.get_schema_metadata("app").await.unwrap();
+        service.get_schema_metadata("app").await.unwrap();
+        Connection::open(&target).unwrap()
+            .execute_batch("ALTER TABLE customers DROP COLUMN fax; CREATE TABLE invoices (id INTEGER PRIMARY KEY);")
+            .unwrap();
+        let before = pool_cache.get_or_create("app", &request.url).await.unwrap();
+        service.refresh_schema_metadata("app").await.unwrap();
+        // The database is queried directly, so its driver is kept
+        let after = pool_cache.get("app").await.unwrap();
+        assert!(Arc::ptr_eq(&before, &after));
+        // A refresh that finds the same structure adds no snapshot
+        service.refresh_schema_metadata("app").await.unwrap();
+
+        let snapshots = service.list_snapshots("app").unwrap();
+        assert_eq!(snapshots.iter().map(|s| s.version).collect::<Vec<_>>(), vec![1, 2]);
+
+        let diff = service.diff_snapshots("app", &SnapshotDiffRequest::default()).unwrap();
+        assert_eq!((diff.from.version, diff.to.version), (1, 2));
+        assert_eq!(diff.changes.added_tables, vec!["main.invoices"]);
+        assert_eq!(diff.changes.changed_tables[0].dropped_columns, vec!["fax"]);
+
+        // Nothing was taken before the first refresh
+        let request = SnapshotDiffRequest { since: Some("2000-01-01".to_string()), ..SnapshotDiffRequest::default() };
+        assert!(matches!(service.diff_snapshots("app", &request), Err(AppError::NotFound(_))));
+
+        let _ = std::fs::remove_file(&target);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_query_across_refresh() {
+        let dir = std::env::temp_dir().join("db_query_schema_refresh_cancel");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("users.csv"), "id,name\n1,alice\n").unwrap();
+
+        let sqlite_conn = Arc::new(Mutex::new(init_db(":memory:").unwrap()));
+        let db_service = Arc::new(DatabaseService::new(Arc::clone(&sqlite_conn)));
+        let request = CreateDatabaseRequest { url: format!("file://{}", dir.display()), settings: None };
+        db_service.store_connection("files", &request).unwrap();
+        let pool_cache = Arc::new(ConnectionPoolCache::new());
+        let service = SchemaService::new(sqlite_conn, db_service, Arc::clone(&pool_cache));
+
+        let before = pool_cache.get_or_create("files", &request.url).await.unwrap();
+        let driver = Arc::clone(&before);
+        let query = tokio::spawn(async move {
+            let endless = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT count(*) FROM n";
+            driver.execute(endless, "q1", &ConnectionSettings::default()).await
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // The files are loaded again, and the query started before can still be cancelled
+        std::fs::write(dir.join("orders.csv"), "id,total\n1,9.5\n").unwrap();
+        let metadata = service.refresh_schema_metadata("files").await.unwrap();
+        assert_eq!(metadata.tables.len(), 2);
+        let after = pool_cache.get("files").await.unwrap();
+        assert!(!Arc::ptr_eq(&before, &after));
+
+        assert!(after.cancel("q1").await.unwrap());
+        assert!(matches!(query.await.unwrap(), Err(AppError::QueryCancelled(_))));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_updated_connection_is_read_again() {
+        let targets: Vec<_> = ["a", "b"]
+            .iter()
+            .map(|name| {
+                let target = std::env::temp_dir().join(format!("db_query_schema_updated_{name}.db"));
+                let _ = std::fs::remove_file(&target);
+                Connection::open(&target).unwrap()
+                    .execute_batch(&format!("CREATE TABLE table_{name} (id INTEGER PRIMARY KEY);"))
+                    .unwrap();
+                format!("sqlite://{}", target.display())
+            })
+            .collect();
+
+        let sqlite_conn = Arc::new(Mutex::new(init_db(":memory:").unwrap()));
+        let db_service = Arc::new(DatabaseService::new(Arc::clone(&sqlite_conn)));
+        let pool_cache = Arc::new(ConnectionPoolCache::new());
+        let service = SchemaService::new(sqlite_conn, Arc::clone(&db_service), Arc::clone(&pool_cache));
+        let store = |url: &str, settings: Option<ConnectionSettings>| {
+            db_service.store_connection("app", &CreateDatabaseRequest { url: url.to_string(), settings }).unwrap()
+        };
+
+        let first = store(&targets[0], None);
+        service.get_schema_metadata("app").await.unwrap();
+        pool_cache.get_or_create("app", &first.url).await.unwrap();
+
+        // Storing the same connection again keeps the cache and the driver
+        let same = store(&targets[0], None);
+        service.connection_updated(&first, &same).await.unwrap();
+        assert!(service.cache_time("app").unwrap().is_some());
+        assert!(pool_cache.get("app").await.is_some());
+
+        // New settings only clear the cache
+        let settings = ConnectionSettings { schema_cache_ttl_secs: 60, ..ConnectionSettings::default() };
+        let resettled = store(&targets[0], Some(settings.clone()));
+        service.connection_updated(&same, &resettled).await.unwrap();
+        assert!(service.cache_time("app").unwrap().is_none());
+        assert!(pool_cache.get("app").await.is_some());
+
+        // A new URL shows the other database's schema
+        service.get_schema_metadata("app").await.unwrap();
+        let moved = store(&targets[1], Some(settings));
+        service.connection_updated(&resettled, &moved).await.unwrap();
+        assert!(pool_cache.get("app").await.is_none());
+        let metadata = service.get_schema_metadata("app").await.unwrap();
+        assert_eq!(metadata.tables[0].name, "table_b");
+
+        for target in &targets {
+            let _ = std::fs::remove_file(target.trim_start_matches("sqlite://"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migration_requires_postgres_on_both_sides() {
+        let sqlite_conn = Arc::new(Mutex::new(init_db(":memory:").unwrap()));
+        let db_service = Arc::new(DatabaseService::new(Arc::clone(&sqlite_conn)));
+        for (name, url) in [("prod", "postgres://app@localhost/prod"), ("lite", "sqlite:///tmp/lite.db")] {
+            let request = CreateDatabaseRequest { url: url.to_string(), settings: None };
+            db_service.store_connection(name, &request).unwrap();
+        }
+        let service = SchemaService::new(sqlite_conn, db_service, Arc::new(ConnectionPoolCache::new()));
+
+        // The script for the PostgreSQL side would be built from the SQLite schema
+        let request = ConnectionDiffRequest {
+            left: "prod".to_string(),
+            right: "lite".to_string(),
+            migrate: Some(DiffSide::Left),
+        };
+        let result = service.diff_connections(&request).await;
+        assert!(matches!(&result, Err(AppError::ValidationError(message)) if message.contains("'lite'")), "{result:?}");
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("2026-10-13").unwrap().to_rfc3339(), "2026-10-13T00:00:00+00:00");
+        assert_eq!(parse_since("2026-10-13T09:30:00+02:00").unwrap().to_rfc3339(), "2026-10-13T07:30:00+00:00");
+        assert!(matches!(parse_since("last tuesday"), Err(AppError::ValidationError(_))));
+    }
+}
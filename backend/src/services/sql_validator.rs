@@ -1,15 +1,53 @@
-use crate::error::AppError;
-use sqlparser::ast::{Statement, Query, SetExpr};
-use sqlparser::dialect::PostgreSqlDialect;
+use crate::error::{AppError, SqlDiagnostics};
+use crate::models::database::ConnectionSettings;
+use sqlparser::ast::{
+    Distinct, Expr, Function, FunctionArg, FunctionArgExpr, GroupByExpr, JoinConstraint, JoinOperator,
+    ObjectName, OrderByExpr, Query, Select, SelectItem, SetExpr, Statement, TableFactor,
+    TableWithJoins, Value, WindowType,
+};
+use sqlparser::dialect::Dialect;
 use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+/// A query that passed validation, ready to run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedSql {
+    pub sql: String,
+    /// Whether the query's own LIMIT or FETCH count was lowered to the connection's maximum rows,
+    /// so a result of that many rows may have been cut short
+    pub clamped: bool,
+}
 
 /// Validates SQL query and ensures it's a SELECT statement only
-pub fn validate_sql(sql: &str) -> Result<String, AppError> {
-    let dialect = PostgreSqlDialect {};
-    
+///
+/// The query is parsed with the dialect of the target database, checked against
+/// the connection's denied functions, and gets a LIMIT of the connection's
+/// default limit if it has none, or at most its maximum rows otherwise.
+pub fn validate_sql(sql: &str, dialect: &dyn Dialect, settings: &ConnectionSettings) -> Result<ValidatedSql, AppError> {
+    let query = parse_select_query(sql, dialect, settings)?;
+
+    // Add a LIMIT if missing, or lower one above the maximum
+    apply_limit(*query, statement_text(sql, dialect)?, settings, false)
+}
+
+/// Validates a query whose result is read page by page
+///
+/// Pages bound each response, so a query without LIMIT is limited to the
+/// connection's maximum rows instead of its default limit, and a result of that
+/// many rows counts as possibly cut short.
+pub fn validate_sql_for_paging(
+    sql: &str,
+    dialect: &dyn Dialect,
+    settings: &ConnectionSettings,
+) -> Result<ValidatedSql, AppError> {
+    let query = parse_select_query(sql, dialect, settings)?;
+    apply_limit(*query, statement_text(sql, dialect)?, settings, true)
+}
+
+/// Parse a single statement and ensure it's a SELECT query
+fn parse_select_query(sql: &str, dialect: &dyn Dialect, settings: &ConnectionSettings) -> Result<Box<Query>, AppError> {
     // Parse SQL
-    let statements = Parser::parse_sql(&dialect, sql)
-        .map_err(|e| AppError::ValidationError(format!("Invalid SQL syntax: {}", e)))?;
+    let statements = Parser::parse_sql(dialect, sql).map_err(|e| invalid_sql(sql, &e.to_string()))?;
     
     if statements.is_empty() {
         return Err(AppError::ValidationError("Empty SQL statement".to_string()));
@@ -22,15 +60,12 @@ pub fn validate_sql(sql: &str) -> Result<String, AppError> {
     }
     
     // Check if it's a SELECT statement
-    match &statements[0] {
-        Statement::Query(query) => {
-            // Validate it's a SELECT query
-            validate_select_query(query)?;
+    match statements.into_iter().next() {
+        Some(Statement::Query(query)) => {
+            // Check every nested query and expression is a read-only SELECT, not just the top level
+            QueryChecker { denied_functions: &settings.denied_functions }.query(&query)?;
             
-            // Add LIMIT if missing
-            let sql_with_limit = add_limit_if_missing(sql, query)?;
-            
-            Ok(sql_with_limit)
+            Ok(query)
         }
         _ => Err(AppError::ValidationError(
             "Only SELECT statements are allowed".to_string(),
@@ -38,102 +73,599 @@ pub fn validate_sql(sql: &str) -> Result<String, AppError> {
     }
 }
 
-/// Validates that the query is a SELECT statement
-fn validate_select_query(query: &Query) -> Result<(), AppError> {
-    match query.body.as_ref() {
-        SetExpr::Select(_) => Ok(()),
-        SetExpr::Query(_) => {
-            // Recursively check nested queries
-            validate_select_query(query)
+/// Syntax error in `sql`, located from the parser's message
+fn invalid_sql(sql: &str, error: &str) -> AppError {
+    let message = format!("Invalid SQL syntax: {error}");
+    let diagnostics = SqlDiagnostics::from_parser_message(sql, &message);
+    AppError::InvalidSql(message, Box::new(diagnostics))
+}
+
+/// The statement in `sql` without trailing whitespace, comments and semicolons
+///
+/// Leading text is kept as is, so positions reported by the database for the
+/// executed query match the SQL the user wrote.
+fn statement_text<'s>(sql: &'s str, dialect: &dyn Dialect) -> Result<&'s str, AppError> {
+    let tokens = Tokenizer::new(dialect, sql)
+        .tokenize_with_location()
+        .map_err(|e| invalid_sql(sql, &e.to_string()))?;
+
+    let last = tokens
+        .iter()
+        .rposition(|token| !matches!(token.token, Token::Whitespace(_) | Token::SemiColon | Token::EOF));
+    // The statement ends where the first token after its last one starts
+    let end = last
+        .and_then(|last| tokens.get(last + 1))
+        .map_or(Some(sql.len()), |next| {
+            byte_offset(sql, usize::try_from(next.location.line).ok()?, usize::try_from(next.location.column).ok()?)
+        })
+        .unwrap_or(sql.len());
+
+    Ok(&sql[..end])
+}
+
+/// Byte offset of a 1-based line and column in `sql`
+fn byte_offset(sql: &str, line: usize, column: usize) -> Option<usize> {
+    let mut line_start = 0;
+    for (index, text) in sql.split('\n').enumerate() {
+        if index + 1 == line {
+            return text
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain([text.len()])
+                .nth(column - 1)
+                .map(|offset| line_start + offset);
         }
-        SetExpr::SetOperation { .. } => {
-            // UNION, INTERSECT, EXCEPT are allowed if they're SELECT operations
-            Ok(())
+        line_start += text.len() + 1;
+    }
+    None
+}
+
+/// Walks a parsed query, rejecting anything but SELECTs and constructs that are
+/// unsafe in a read-only query
+///
+/// Covers CTEs, parenthesized queries, both sides of set operations, derived
+/// tables, joins and subqueries in expressions, so neither a data-modifying
+/// statement nor a denied function can hide anywhere in the query.
+struct QueryChecker<'a> {
+    denied_functions: &'a [String],
+}
+
+impl QueryChecker<'_> {
+    fn query(&self, query: &Query) -> Result<(), AppError> {
+        if !query.locks.is_empty() {
+            return Err(AppError::ValidationError(
+                "Locking clauses (FOR UPDATE, FOR SHARE) are not allowed".to_string(),
+            ));
         }
-        SetExpr::Values(_) => {
-            // VALUES clause is allowed
-            Ok(())
+
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.query(&cte.query)?;
+            }
         }
-        _ => Err(AppError::ValidationError(
-            "Only SELECT statements are allowed".to_string(),
-        )),
+        self.set_expr(&query.body)?;
+        self.order_by(&query.order_by)?;
+        self.exprs(query.limit.iter().chain(&query.limit_by))?;
+        if let Some(offset) = &query.offset {
+            self.expr(&offset.value)?;
+        }
+        if let Some(fetch) = &query.fetch {
+            self.exprs(&fetch.quantity)?;
+        }
+        Ok(())
     }
-}
 
-/// Adds LIMIT 1000 if the query doesn't have a LIMIT clause
-fn add_limit_if_missing(original_sql: &str, query: &Query) -> Result<String, AppError> {
-    // Check if LIMIT already exists
-    if query.limit.is_some() {
-        return Ok(original_sql.to_string());
+    fn set_expr(&self, set_expr: &SetExpr) -> Result<(), AppError> {
+        match set_expr {
+            SetExpr::Select(select) => self.select(select),
+            SetExpr::Query(query) => self.query(query),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.set_expr(left)?;
+                self.set_expr(right)
+            }
+            SetExpr::Values(values) => self.exprs(values.rows.iter().flatten()),
+            SetExpr::Table(_) => Ok(()),
+            _ => Err(AppError::ValidationError(
+                "Only SELECT statements are allowed".to_string(),
+            )),
+        }
     }
-    
-    // Check if FETCH FIRST exists (SQL standard)
-    if query.fetch.is_some() {
-        return Ok(original_sql.to_string());
+
+    fn select(&self, select: &Select) -> Result<(), AppError> {
+        if select.into.is_some() {
+            return Err(AppError::ValidationError(
+                "SELECT INTO is not allowed".to_string(),
+            ));
+        }
+
+        if let Some(Distinct::On(exprs)) = &select.distinct {
+            self.exprs(exprs)?;
+        }
+        for item in &select.projection {
+            match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => self.expr(expr)?,
+                SelectItem::QualifiedWildcard(..) | SelectItem::Wildcard(_) => {}
+            }
+        }
+        for table in &select.from {
+            self.table_with_joins(table)?;
+        }
+        for lateral_view in &select.lateral_views {
+            self.expr(&lateral_view.lateral_view)?;
+        }
+        self.exprs(&select.selection)?;
+        if let GroupByExpr::Expressions(exprs) = &select.group_by {
+            self.exprs(exprs)?;
+        }
+        self.exprs(
+            select.cluster_by.iter()
+                .chain(&select.distribute_by)
+                .chain(&select.sort_by)
+                .chain(&select.having)
+                .chain(&select.qualify),
+        )?;
+        for window in &select.named_window {
+            self.exprs(&window.1.partition_by)?;
+            self.order_by(&window.1.order_by)?;
+        }
+        Ok(())
     }
-    
-    // Add LIMIT 1000 to the end of the query
-    // Simple approach: append LIMIT if not present
-    let sql_upper = original_sql.trim().to_uppercase();
-    
-    // Check if LIMIT or FETCH is already in the SQL string (case-insensitive)
-    if sql_upper.contains("LIMIT") || sql_upper.contains("FETCH") {
-        return Ok(original_sql.to_string());
+
+    fn table_with_joins(&self, table: &TableWithJoins) -> Result<(), AppError> {
+        self.table_factor(&table.relation)?;
+        for join in &table.joins {
+            self.table_factor(&join.relation)?;
+            match &join.join_operator {
+                JoinOperator::Inner(constraint)
+                | JoinOperator::LeftOuter(constraint)
+                | JoinOperator::RightOuter(constraint)
+                | JoinOperator::FullOuter(constraint)
+                | JoinOperator::LeftSemi(constraint)
+                | JoinOperator::RightSemi(constraint)
+                | JoinOperator::LeftAnti(constraint)
+                | JoinOperator::RightAnti(constraint) => {
+                    if let JoinConstraint::On(expr) = constraint {
+                        self.expr(expr)?;
+                    }
+                }
+                JoinOperator::CrossJoin | JoinOperator::CrossApply | JoinOperator::OuterApply => {}
+            }
+        }
+        Ok(())
     }
-    
-    // Append LIMIT 1000
-    let mut result = original_sql.trim().to_string();
-    
-    // Remove trailing semicolon if present
-    if result.ends_with(';') {
-        result.pop();
-        result = result.trim_end().to_string();
+
+    fn table_factor(&self, table: &TableFactor) -> Result<(), AppError> {
+        match table {
+            // Table valued functions, e.g. `FROM dblink(...)`, parse as tables with arguments
+            TableFactor::Table { name, args, .. } => {
+                if let Some(args) = args {
+                    self.check_function_name(name)?;
+                    self.function_args(args)?;
+                }
+                Ok(())
+            }
+            TableFactor::Function { name, args, .. } => {
+                self.check_function_name(name)?;
+                self.function_args(args)
+            }
+            TableFactor::Derived { subquery, .. } => self.query(subquery),
+            TableFactor::TableFunction { expr, .. } => self.expr(expr),
+            TableFactor::UNNEST { array_exprs, .. } => self.exprs(array_exprs),
+            TableFactor::NestedJoin { table_with_joins, .. } => self.table_with_joins(table_with_joins),
+            TableFactor::Pivot { table, aggregate_function, .. } => {
+                self.table_factor(table)?;
+                self.expr(aggregate_function)
+            }
+            TableFactor::Unpivot { table, .. } => self.table_factor(table),
+        }
     }
-    
-    result.push_str(" LIMIT 1000");
-    
-    Ok(result)
+
+    fn expr(&self, expr: &Expr) -> Result<(), AppError> {
+        match expr {
+            Expr::Function(function) => self.function(function),
+            Expr::Subquery(query) | Expr::ArraySubquery(query) | Expr::Exists { subquery: query, .. } => {
+                self.query(query)
+            }
+            Expr::InSubquery { expr, subquery, .. } => {
+                self.expr(expr)?;
+                self.query(subquery)
+            }
+            Expr::JsonAccess { left, right, .. }
+            | Expr::IsDistinctFrom(left, right)
+            | Expr::IsNotDistinctFrom(left, right)
+            | Expr::BinaryOp { left, right, .. }
+            | Expr::AnyOp { left, right, .. }
+            | Expr::AllOp { left, right, .. }
+            | Expr::InUnnest { expr: left, array_expr: right, .. }
+            | Expr::Like { expr: left, pattern: right, .. }
+            | Expr::ILike { expr: left, pattern: right, .. }
+            | Expr::SimilarTo { expr: left, pattern: right, .. }
+            | Expr::RLike { expr: left, pattern: right, .. }
+            | Expr::Position { expr: left, r#in: right }
+            | Expr::AggregateExpressionWithFilter { expr: left, filter: right } => {
+                self.expr(left)?;
+                self.expr(right)
+            }
+            Expr::CompositeAccess { expr, .. }
+            | Expr::IsFalse(expr)
+            | Expr::IsNotFalse(expr)
+            | Expr::IsTrue(expr)
+            | Expr::IsNotTrue(expr)
+            | Expr::IsNull(expr)
+            | Expr::IsNotNull(expr)
+            | Expr::IsUnknown(expr)
+            | Expr::IsNotUnknown(expr)
+            | Expr::UnaryOp { expr, .. }
+            | Expr::Convert { expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::TryCast { expr, .. }
+            | Expr::SafeCast { expr, .. }
+            | Expr::AtTimeZone { timestamp: expr, .. }
+            | Expr::Extract { expr, .. }
+            | Expr::Ceil { expr, .. }
+            | Expr::Floor { expr, .. }
+            | Expr::Collate { expr, .. }
+            | Expr::Nested(expr)
+            | Expr::Named { expr, .. } => self.expr(expr),
+            Expr::InList { expr, list, .. } => {
+                self.expr(expr)?;
+                self.exprs(list)
+            }
+            Expr::Between { expr, low, high, .. } => self.exprs([expr, low, high].map(AsRef::as_ref)),
+            Expr::Substring { expr, substring_from, substring_for, .. } => {
+                self.expr(expr)?;
+                self.exprs(substring_from.iter().chain(substring_for).map(AsRef::as_ref))
+            }
+            Expr::Trim { expr, trim_what, trim_characters, .. } => {
+                self.expr(expr)?;
+                self.exprs(trim_what.as_deref())?;
+                self.exprs(trim_characters.iter().flatten())
+            }
+            Expr::Overlay { expr, overlay_what, overlay_from, overlay_for } => {
+                self.exprs([expr, overlay_what, overlay_from].map(AsRef::as_ref))?;
+                self.exprs(overlay_for.as_deref())
+            }
+            Expr::MapAccess { column, keys } => {
+                self.expr(column)?;
+                self.exprs(keys)
+            }
+            Expr::ArrayIndex { obj, indexes } => {
+                self.expr(obj)?;
+                self.exprs(indexes)
+            }
+            Expr::Case { operand, conditions, results, else_result } => {
+                self.exprs(operand.as_deref())?;
+                self.exprs(conditions.iter().chain(results))?;
+                self.exprs(else_result.as_deref())
+            }
+            Expr::ListAgg(list_agg) => {
+                self.expr(&list_agg.expr)?;
+                self.exprs(list_agg.separator.as_deref())?;
+                self.order_by(&list_agg.within_group)
+            }
+            Expr::ArrayAgg(array_agg) => {
+                self.expr(&array_agg.expr)?;
+                self.order_by(array_agg.order_by.iter().flatten())?;
+                self.exprs(array_agg.limit.as_deref())
+            }
+            Expr::GroupingSets(sets) | Expr::Cube(sets) | Expr::Rollup(sets) => self.exprs(sets.iter().flatten()),
+            Expr::Tuple(exprs) | Expr::Struct { values: exprs, .. } => self.exprs(exprs),
+            Expr::Array(array) => self.exprs(&array.elem),
+            Expr::Interval(interval) => self.expr(&interval.value),
+            // Identifiers and literals have no nested expressions. The match lists every
+            // variant, so one added by a parser upgrade has to be walked before it compiles
+            Expr::Identifier(_)
+            | Expr::CompoundIdentifier(_)
+            | Expr::Value(_)
+            | Expr::TypedString { .. }
+            | Expr::IntroducedString { .. }
+            | Expr::MatchAgainst { .. } => Ok(()),
+        }
+    }
+
+    fn exprs<'e>(&self, exprs: impl IntoIterator<Item = &'e Expr>) -> Result<(), AppError> {
+        exprs.into_iter().try_for_each(|expr| self.expr(expr))
+    }
+
+    fn order_by<'e>(&self, order_by: impl IntoIterator<Item = &'e OrderByExpr>) -> Result<(), AppError> {
+        self.exprs(order_by.into_iter().map(|order_by| &order_by.expr))
+    }
+
+    fn function(&self, function: &Function) -> Result<(), AppError> {
+        self.check_function_name(&function.name)?;
+        self.function_args(&function.args)?;
+        self.exprs(function.filter.as_deref())?;
+        self.order_by(&function.order_by)?;
+        if let Some(WindowType::WindowSpec(window)) = &function.over {
+            self.exprs(&window.partition_by)?;
+            self.order_by(&window.order_by)?;
+        }
+        Ok(())
+    }
+
+    fn function_args(&self, args: &[FunctionArg]) -> Result<(), AppError> {
+        for arg in args {
+            let (FunctionArg::Named { arg, .. } | FunctionArg::Unnamed(arg)) = arg;
+            if let FunctionArgExpr::Expr(expr) = arg {
+                self.expr(expr)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject a denied function, whether or not its name is schema qualified
+    fn check_function_name(&self, name: &ObjectName) -> Result<(), AppError> {
+        let Some(function) = name.0.last() else {
+            return Ok(());
+        };
+        if self.denied_functions.iter().any(|denied| denied.eq_ignore_ascii_case(&function.value)) {
+            return Err(AppError::ValidationError(format!(
+                "Function '{}' is not allowed",
+                function.value.to_lowercase()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Bound the rows a query returns by adding or lowering its top-level LIMIT
+///
+/// A query without LIMIT or FETCH gets the connection's default limit, or its
+/// maximum rows when `paged`, appended to `text`, its own statement text. A larger
+/// literal limit is lowered to the connection's maximum rows by serializing the
+/// rewritten AST, which drops any comments and original formatting.
+fn apply_limit(mut query: Query, text: &str, settings: &ConnectionSettings, paged: bool) -> Result<ValidatedSql, AppError> {
+    let max_rows = settings.max_rows;
+    let (added_limit, added_clamps) = if paged { (max_rows, true) } else { (settings.default_limit, false) };
+
+    // Whether the AST was rewritten, and whether that lowered the query's own row count
+    let (rewritten, clamped) = if let Some(limit) = &mut query.limit {
+        let clamped = clamp_row_count(limit, max_rows, "LIMIT")?;
+        (clamped, clamped)
+    } else if let Some(fetch) = &mut query.fetch {
+        if fetch.percent {
+            return Err(AppError::ValidationError(
+                "FETCH with PERCENT is not supported; use a row count".to_string(),
+            ));
+        }
+        // FETCH FIRST ROW ONLY, without a quantity, returns a single row
+        let clamped = match &mut fetch.quantity {
+            Some(quantity) => clamp_row_count(quantity, max_rows, "FETCH")?,
+            None => false,
+        };
+        (clamped, clamped)
+    } else if query.offset.is_some() || ends_with_limit_all(text) {
+        // Not every database accepts LIMIT after OFFSET, and LIMIT ALL parses as no
+        // LIMIT, so let the AST place it
+        query.limit = Some(row_count(added_limit));
+        (true, added_clamps)
+    } else {
+        return Ok(ValidatedSql { sql: format!("{text} LIMIT {added_limit}"), clamped: added_clamps });
+    };
+
+    let sql = if rewritten { query.to_string() } else { text.to_string() };
+    Ok(ValidatedSql { sql, clamped })
+}
+
+fn ends_with_limit_all(text: &str) -> bool {
+    text.rsplit(char::is_whitespace).next().is_some_and(|word| word.eq_ignore_ascii_case("ALL"))
+}
+
+/// Lower a literal row count above `max_rows` to `max_rows`, returning whether it was lowered
+///
+/// Row counts computed by an expression cannot be bounded, so they are rejected.
+fn clamp_row_count(count: &mut Expr, max_rows: usize, clause: &str) -> Result<bool, AppError> {
+    let Expr::Value(Value::Number(value, _)) = count else {
+        return Err(AppError::ValidationError(format!("{clause} must be a number of rows")));
+    };
+    // A count too large for usize is above the maximum too
+    if value.parse::<usize>().map_or(true, |rows| rows > max_rows) {
+        tracing::debug!(requested = %value, max_rows = max_rows, "clamping {clause} to the maximum rows");
+        *count = row_count(max_rows);
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+fn row_count(rows: usize) -> Expr {
+    Expr::Value(Value::Number(rows.to_string(), false))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sqlparser::dialect::PostgreSqlDialect;
     
     #[test]
     fn test_validate_select() {
         let sql = "SELECT * FROM users";
-        assert!(validate_sql(sql).is_ok());
+        assert!(validate_sql(sql, &PostgreSqlDialect {}, &ConnectionSettings::default()).is_ok());
     }
     
     #[test]
     fn test_reject_insert() {
         let sql = "INSERT INTO users (name) VALUES ('test')";
-        assert!(validate_sql(sql).is_err());
+        assert!(validate_sql(sql, &PostgreSqlDialect {}, &ConnectionSettings::default()).is_err());
     }
     
     #[test]
     fn test_reject_update() {
         let sql = "UPDATE users SET name = 'test'";
-        assert!(validate_sql(sql).is_err());
+        assert!(validate_sql(sql, &PostgreSqlDialect {}, &ConnectionSettings::default()).is_err());
     }
     
     #[test]
     fn test_reject_delete() {
         let sql = "DELETE FROM users";
-        assert!(validate_sql(sql).is_err());
+        assert!(validate_sql(sql, &PostgreSqlDialect {}, &ConnectionSettings::default()).is_err());
     }
     
     #[test]
     fn test_add_limit() {
         let sql = "SELECT * FROM users";
-        let result = validate_sql(sql).unwrap();
+        let result = validate_sql(sql, &PostgreSqlDialect {}, &ConnectionSettings::default()).unwrap().sql;
         assert!(result.contains("LIMIT 1000"));
+
+        let settings = ConnectionSettings { default_limit: 50, ..ConnectionSettings::default() };
+        let result = validate_sql("SELECT * FROM users;", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM users LIMIT 50");
+
+        // Paged queries are only limited to the maximum rows
+        let settings = ConnectionSettings { default_limit: 50, max_rows: 5000, ..ConnectionSettings::default() };
+        let result = validate_sql_for_paging("SELECT * FROM users", &PostgreSqlDialect {}, &settings).unwrap();
+        assert_eq!(result, ValidatedSql { sql: "SELECT * FROM users LIMIT 5000".to_string(), clamped: true });
+        let result = validate_sql_for_paging("SELECT * FROM users LIMIT 10", &PostgreSqlDialect {}, &settings).unwrap();
+        assert_eq!(result, ValidatedSql { sql: "SELECT * FROM users LIMIT 10".to_string(), clamped: false });
     }
     
     #[test]
     fn test_preserve_existing_limit() {
         let sql = "SELECT * FROM users LIMIT 10";
-        let result = validate_sql(sql).unwrap();
+        let result = validate_sql(sql, &PostgreSqlDialect {}, &ConnectionSettings::default()).unwrap().sql;
         assert_eq!(result, sql);
     }
+
+    #[test]
+    fn test_limit_ignores_keywords_outside_the_clause() {
+        let settings = ConnectionSettings::default();
+        let result = validate_sql("SELECT * FROM limits", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM limits LIMIT 1000");
+
+        let result = validate_sql("SELECT * FROM notes WHERE note = 'fetch'", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM notes WHERE note = 'fetch' LIMIT 1000");
+
+        // A subquery's LIMIT does not bound the outer query
+        let result = validate_sql("SELECT * FROM (SELECT * FROM users LIMIT 5) AS u", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM (SELECT * FROM users LIMIT 5) AS u LIMIT 1000");
+    }
+
+    #[test]
+    fn test_limit_not_commented_out() {
+        let settings = ConnectionSettings::default();
+        let result = validate_sql("SELECT * FROM users -- all of them", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM users LIMIT 1000");
+
+        let result = validate_sql("SELECT * FROM users ORDER BY id; -- done", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM users ORDER BY id LIMIT 1000");
+    }
+
+    #[test]
+    fn test_limit_keeps_statement_text() {
+        // The statement keeps its layout, so database error positions match the user's SQL
+        let settings = ConnectionSettings::default();
+        let result = validate_sql("  SELECT 'é' AS name,\n  id\nFROM users /* all */ ;\n", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "  SELECT 'é' AS name,\n  id\nFROM users LIMIT 1000");
+
+        let result = validate_sql("SELECT * FROM users LIMIT ALL", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM users LIMIT 1000");
+    }
+
+    #[test]
+    fn test_syntax_error_location() {
+        let result = validate_sql("SELECT *\nFROM users\nWHERE id = = 1", &PostgreSqlDialect {}, &ConnectionSettings::default());
+        let Err(error) = result else {
+            unreachable!("the query is invalid");
+        };
+        assert_eq!(error.code(), "VALIDATION_ERROR");
+
+        let diagnostics = error.diagnostics().unwrap();
+        assert_eq!((diagnostics.line, diagnostics.column, diagnostics.position), (Some(3), Some(12), Some(32)));
+    }
+
+    #[test]
+    fn test_clamp_limit_to_max_rows() {
+        let settings = ConnectionSettings { max_rows: 5000, ..ConnectionSettings::default() };
+        let result = validate_sql("SELECT * FROM users LIMIT 1000000", &PostgreSqlDialect {}, &settings).unwrap();
+        assert_eq!(result, ValidatedSql { sql: "SELECT * FROM users LIMIT 5000".to_string(), clamped: true });
+        // Neither a LIMIT within the maximum nor the default LIMIT is a clamp
+        assert!(!validate_sql("SELECT * FROM users LIMIT 5000", &PostgreSqlDialect {}, &settings).unwrap().clamped);
+        assert!(!validate_sql("SELECT * FROM users OFFSET 5", &PostgreSqlDialect {}, &settings).unwrap().clamped);
+
+        let result = validate_sql("SELECT * FROM users LIMIT 99999999999999999999999", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM users LIMIT 5000");
+
+        let result = validate_sql("SELECT * FROM users FETCH FIRST 10000 ROWS ONLY", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT * FROM users FETCH FIRST 5000 ROWS ONLY");
+
+        let result = validate_sql("SELECT 1 UNION SELECT 2 LIMIT 7000", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "SELECT 1 UNION SELECT 2 LIMIT 5000");
+
+        assert!(validate_sql("SELECT * FROM users LIMIT 10 * 1000", &PostgreSqlDialect {}, &settings).is_err());
+        assert!(validate_sql("SELECT * FROM users FETCH FIRST 50 PERCENT ROWS ONLY", &PostgreSqlDialect {}, &settings).is_err());
+    }
+
+    #[test]
+    fn test_parenthesized_queries() {
+        let settings = ConnectionSettings::default();
+        let result = validate_sql("(SELECT 1)", &PostgreSqlDialect {}, &settings).unwrap().sql;
+        assert_eq!(result, "(SELECT 1) LIMIT 1000");
+
+        assert!(validate_sql("(((SELECT * FROM users)))", &PostgreSqlDialect {}, &settings).is_ok());
+        assert!(validate_sql("(SELECT 1) UNION ((SELECT 2) EXCEPT (SELECT 3))", &PostgreSqlDialect {}, &settings).is_ok());
+        assert!(validate_sql("WITH u AS ((SELECT * FROM users)) SELECT * FROM u", &PostgreSqlDialect {}, &settings).is_ok());
+    }
+
+    #[test]
+    fn test_reject_modifications_in_nested_queries() {
+        let settings = ConnectionSettings::default();
+        let rejected = [
+            "SELECT * FROM users UNION (INSERT INTO users (name) VALUES ('x') RETURNING *)",
+            "(UPDATE users SET name = 'x' RETURNING *) UNION SELECT * FROM users",
+            "WITH changed AS (UPDATE users SET name = 'x' RETURNING *) SELECT * FROM changed",
+            "WITH a AS (SELECT 1), b AS (INSERT INTO users (name) VALUES ('x') RETURNING id) SELECT * FROM a",
+            "SELECT * FROM (INSERT INTO users (name) VALUES ('x') RETURNING *) AS u",
+            "SELECT * FROM users WHERE EXISTS (WITH changed AS (UPDATE users SET name = 'x' RETURNING id) SELECT * FROM changed)",
+        ];
+
+        for sql in rejected {
+            let result = validate_sql(sql, &PostgreSqlDialect {}, &settings);
+            assert!(matches!(result, Err(AppError::ValidationError(_))), "{sql}: {result:?}");
+        }
+    }
+
+    #[test]
+    fn test_reject_denied_functions() {
+        let settings = ConnectionSettings::default();
+        let rejected = [
+            ("SELECT pg_sleep(10)", "pg_sleep"),
+            ("SELECT PG_CATALOG.PG_SLEEP(10)", "pg_sleep"),
+            ("SELECT * FROM dblink('host=other', 'SELECT 1') AS t", "dblink"),
+            ("WITH ids AS (SELECT nextval('users_id_seq') AS id) SELECT * FROM ids", "nextval"),
+            ("SELECT * FROM (SELECT set_config('role', 'admin', false)) AS s", "set_config"),
+            ("SELECT id FROM users WHERE id IN (SELECT pg_terminate_backend(1))", "pg_terminate_backend"),
+            ("SELECT 1 UNION SELECT length(pg_read_file('/etc/passwd'))", "pg_read_file"),
+            ("SELECT CASE WHEN EXISTS (SELECT lo_import('/etc/passwd')) THEN 1 END", "lo_import"),
+            ("SELECT DISTINCT ON (pg_read_file('/etc/passwd')) 1", "pg_read_file"),
+            ("SELECT CONVERT(pg_sleep(600), text)", "pg_sleep"),
+        ];
+
+        for (sql, function) in rejected {
+            let result = validate_sql(sql, &PostgreSqlDialect {}, &settings);
+            assert!(
+                matches!(&result, Err(AppError::ValidationError(message)) if message.contains(function)),
+                "{sql}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_denied_functions_are_configurable() {
+        let sql = "SELECT nextval('users_id_seq')";
+        assert!(validate_sql(sql, &PostgreSqlDialect {}, &ConnectionSettings::default()).is_err());
+
+        let settings = ConnectionSettings {
+            denied_functions: vec!["md5".to_string()],
+            ..ConnectionSettings::default()
+        };
+        assert!(validate_sql(sql, &PostgreSqlDialect {}, &settings).is_ok());
+        assert!(validate_sql("SELECT MD5(name) FROM users", &PostgreSqlDialect {}, &settings).is_err());
+    }
+
+    #[test]
+    fn test_reject_select_into_and_locking() {
+        let settings = ConnectionSettings::default();
+        assert!(validate_sql("SELECT * INTO backup FROM users", &PostgreSqlDialect {}, &settings).is_err());
+        assert!(validate_sql("SELECT * FROM users FOR UPDATE", &PostgreSqlDialect {}, &settings).is_err());
+        assert!(validate_sql("SELECT * FROM users FOR SHARE NOWAIT", &PostgreSqlDialect {}, &settings).is_err());
+    }
 }
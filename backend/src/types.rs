@@ -1,23 +1,24 @@
 // Shared type definitions
 
+use crate::drivers::{self, SharedDriver};
 use crate::error::AppError;
 use crate::services::database_service::DatabaseService;
 use crate::services::schema_service::SchemaService;
 use crate::services::llm_service::LLMService;
-use sqlx::PgPool;
+use crate::services::query_cursor::QueryCursorStore;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::RwLock;
 
 // Shared service types for Axum state
 pub type SharedDatabaseService = Arc<DatabaseService>;
 pub type SharedSchemaService = Arc<SchemaService>;
 pub type SharedLLMService = Arc<LLMService>;
+pub type SharedQueryCursorStore = Arc<QueryCursorStore>;
 
-/// Connection pool cache for reusing PostgreSQL connection pools
+/// Connection pool cache for reusing database drivers (and their connection pools)
 pub struct ConnectionPoolCache {
-    pools: Arc<RwLock<HashMap<String, Arc<PgPool>>>>,
+    pools: Arc<RwLock<HashMap<String, SharedDriver>>>,
 }
 
 impl ConnectionPoolCache {
@@ -27,8 +28,13 @@ impl ConnectionPoolCache {
         }
     }
 
-    /// Get an existing pool or create a new one
-    pub async fn get_or_create(&self, name: &str, url: &str) -> Result<Arc<PgPool>, AppError> {
+    /// Get the driver of a database, if it is connected
+    pub async fn get(&self, name: &str) -> Option<SharedDriver> {
+        self.pools.read().await.get(name).cloned()
+    }
+
+    /// Get an existing driver or connect a new one
+    pub async fn get_or_create(&self, name: &str, url: &str) -> Result<SharedDriver, AppError> {
         // Try read lock first (fast path)
         {
             let pools = self.pools.read().await;
@@ -47,27 +53,45 @@ impl ConnectionPoolCache {
             return Ok(Arc::clone(pool));
         }
 
-        // Create new pool with configuration
+        // Connect using the driver matching the URL scheme
         tracing::info!(database_name = %name, "creating new connection pool");
-        let pool_options = sqlx::postgres::PgPoolOptions::new()
-            .max_connections(5) // Limit per database
-            .acquire_timeout(Duration::from_secs(30))
-            .idle_timeout(Duration::from_secs(600)) // 10 minutes
-            .max_lifetime(Duration::from_secs(3600)); // 1 hour
-
-        let pool = Arc::new(pool_options.connect(url).await?);
+        let pool = drivers::connect(url).await?;
         pools.insert(name.to_string(), Arc::clone(&pool));
 
         tracing::info!(database_name = %name, "connection pool created and cached");
         Ok(pool)
     }
 
-    /// Remove a pool from the cache (e.g., when database is deleted)
+    /// Replace the driver of a database by one with its data read again, for drivers keeping a copy of it
+    ///
+    /// Drivers querying the database directly stay in place, with their pool and running queries.
+    /// A replaced driver keeps serving the queries and cursors started on it, which can still be cancelled.
+    pub async fn reload(&self, name: &str) -> Result<(), AppError> {
+        let Some(current) = self.get(name).await else {
+            return Ok(());
+        };
+        let Some(reloaded) = current.reload().await? else {
+            return Ok(());
+        };
+
+        // The connection may have been updated or removed while the data was read
+        let mut pools = self.pools.write().await;
+        if pools.get(name).is_some_and(|pool| Arc::ptr_eq(pool, &current)) {
+            tracing::info!(database_name = %name, "replacing driver with reloaded data");
+            pools.insert(name.to_string(), reloaded);
+        }
+        Ok(())
+    }
+
+    /// Remove a pool from the cache (e.g., when database is deleted or its URL changed)
+    ///
+    /// The next query connects again. Queries and cursors still using the old pool
+    /// finish on it, and it is closed in the background once they release it.
     pub async fn remove(&self, name: &str) {
         let mut pools = self.pools.write().await;
         if let Some(pool) = pools.remove(name) {
             tracing::info!(database_name = %name, "removing connection pool from cache");
-            pool.close().await;
+            tokio::spawn(async move { pool.close().await });
         }
     }
 
@@ -86,4 +110,3 @@ impl Default for ConnectionPoolCache {
 }
 
 pub type SharedConnectionPoolCache = Arc<ConnectionPoolCache>;
-
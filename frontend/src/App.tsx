@@ -13,14 +13,18 @@ import { useQueryExecution } from './hooks/useQueryExecution';
 function App() {
   // Custom hooks for state management
   const { databases, selectedDb, loading, loadDatabases, selectDatabase } = useDatabaseManagement();
-  const { schema, loading: schemaLoading, expandedKeys, loadSchema, setExpandedKeys } = useSchemaLoading();
+  const { schema, loading: schemaLoading, expandedKeys, loadSchema, countRows, setExpandedKeys } = useSchemaLoading();
   const {
     sql,
     setSql,
     queryResult,
     queryLoading,
+    queryError,
+    loadingMore,
     executionTime,
     executeQuery: executeQueryHandler,
+    cancelQuery,
+    loadMore,
     resetQuery,
   } = useQueryExecution(selectedDb?.name || null);
 
@@ -47,7 +51,7 @@ function App() {
 
   const handleRefresh = () => {
     if (selectedDb) {
-      loadSchema(selectedDb.name);
+      loadSchema(selectedDb.name, true);
     }
     loadDatabases();
   };
@@ -67,6 +71,7 @@ function App() {
 
       {/* Second Column - Tables Tree */}
       <SchemaSidebar
+        databases={databases}
         selectedDb={selectedDb}
         schema={schema}
         loading={schemaLoading}
@@ -74,6 +79,7 @@ function App() {
         firstColumnCollapsed={firstColumnCollapsed}
         onRefresh={handleRefresh}
         onExpandedKeysChange={setExpandedKeys}
+        onCountRows={(table) => selectedDb && countRows(selectedDb.name, table)}
       />
 
       {/* Third Column - Statistics and Query Panel */}
@@ -84,18 +90,26 @@ function App() {
           sql={sql}
           queryResult={queryResult}
           queryLoading={queryLoading}
+          queryError={queryError}
+          loadingMore={loadingMore}
           executionTime={executionTime}
           onSqlChange={setSql}
           onExecuteQuery={executeQueryHandler}
+          onCancelQuery={cancelQuery}
+          onLoadMore={loadMore}
         />
       </Layout>
 
       {/* Add Database Modal */}
       {showAddForm && (
         <AddDatabaseForm
-          onSuccess={() => {
+          onSuccess={(name) => {
             setShowAddForm(false);
             loadDatabases();
+            // An updated connection may point at another database or read other schemas
+            if (selectedDb?.name === name) {
+              loadSchema(name);
+            }
           }}
           onCancel={() => setShowAddForm(false)}
         />
@@ -12,3 +12,20 @@ export const executeQuery = async (
   return response.data;
 };
 
+export const cancelQuery = async (
+  dbName: string,
+  queryId: string
+): Promise<void> => {
+  await apiClient.delete(`/dbs/${dbName}/queries/${queryId}`);
+};
+
+export const fetchQueryPage = async (
+  dbName: string,
+  cursor: string
+): Promise<QueryResponse> => {
+  const response = await apiClient.get<QueryResponse>(
+    `/dbs/${dbName}/query/${cursor}`
+  );
+  return response.data;
+};
+
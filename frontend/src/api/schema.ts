@@ -1,8 +1,42 @@
 import { apiClient } from './client';
-import { SchemaMetadata } from '../types/schema';
+import { SchemaMetadata, TableRowCount } from '../types/schema';
+import {
+  ConnectionDiff,
+  ConnectionDiffRequest,
+  SchemaSnapshot,
+  SnapshotDiff,
+  SnapshotDiffRequest,
+} from '../types/schemaDiff';
 
 export const getSchemaMetadata = async (dbName: string): Promise<SchemaMetadata> => {
   const response = await apiClient.get<SchemaMetadata>(`/dbs/${dbName}`);
   return response.data;
 };
 
+export const refreshSchemaMetadata = async (dbName: string): Promise<SchemaMetadata> => {
+  const response = await apiClient.post<SchemaMetadata>(`/dbs/${dbName}/schema/refresh`);
+  return response.data;
+};
+
+export const countTableRows = async (dbName: string, schema: string, table: string): Promise<TableRowCount> => {
+  const response = await apiClient.get<TableRowCount>(
+    `/dbs/${dbName}/tables/${encodeURIComponent(schema)}/${encodeURIComponent(table)}/row-count`
+  );
+  return response.data;
+};
+
+
+export const listSchemaSnapshots = async (dbName: string): Promise<SchemaSnapshot[]> => {
+  const response = await apiClient.get<SchemaSnapshot[]>(`/dbs/${dbName}/schema/snapshots`);
+  return response.data;
+};
+
+export const diffSchemaSnapshots = async (dbName: string, request: SnapshotDiffRequest): Promise<SnapshotDiff> => {
+  const response = await apiClient.get<SnapshotDiff>(`/dbs/${dbName}/schema/diff`, { params: request });
+  return response.data;
+};
+
+export const diffDatabaseSchemas = async (request: ConnectionDiffRequest): Promise<ConnectionDiff> => {
+  const response = await apiClient.get<ConnectionDiff>('/schema-diff', { params: request });
+  return response.data;
+};
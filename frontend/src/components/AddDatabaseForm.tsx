@@ -5,7 +5,7 @@ import { upsertDatabase } from '../api/database';
 import { showError, showSuccess } from '../utils/error';
 
 interface AddDatabaseFormProps {
-  onSuccess?: () => void;
+  onSuccess?: (name: string) => void;
   onCancel?: () => void;
 }
 
@@ -21,7 +21,7 @@ const AddDatabaseForm: React.FC<AddDatabaseFormProps> = ({ onSuccess, onCancel }
       showSuccess(`Database '${values.name}' added successfully`);
       form.resetFields();
       if (onSuccess) {
-        onSuccess();
+        onSuccess(values.name);
       }
     } catch (error: unknown) {
       showError(error, 'Failed to add database');
@@ -61,7 +61,7 @@ const AddDatabaseForm: React.FC<AddDatabaseFormProps> = ({ onSuccess, onCancel }
           rules={[
             { required: true, message: 'Please enter a database URL' },
             {
-              pattern: /^(postgres|postgresql|mysql|sqlite):\/\/.+/, 
+              pattern: /^(postgres|postgresql|mysql|sqlite|file):\/\/.+/, 
               message: 'Invalid database URL format',
             },
           ]}
@@ -1,15 +1,24 @@
 import React from 'react';
-import { Table, Typography, Empty } from 'antd';
-import { QueryResponse, CellValue } from '../types/query';
+import { Table, Typography, Empty, Button } from 'antd';
+import { QueryResponse, CellValue, LogicalType } from '../types/query';
 
 const { Text } = Typography;
 
+const isNumeric = (type: LogicalType) => type === 'integer' || type === 'decimal';
+
 interface QueryResultsProps {
   result: QueryResponse | null;
   loading?: boolean;
+  loadingMore?: boolean;
+  onLoadMore?: () => void;
 }
 
-const QueryResults: React.FC<QueryResultsProps> = ({ result, loading = false }) => {
+const QueryResults: React.FC<QueryResultsProps> = ({
+  result,
+  loading = false,
+  loadingMore = false,
+  onLoadMore,
+}) => {
   if (loading) {
     return (
       <div className="p-4 md:p-6 text-center">
@@ -39,9 +48,10 @@ const QueryResults: React.FC<QueryResultsProps> = ({ result, loading = false })
 
   // Convert columns and rows to Ant Design Table format
   const columns = result.columns.map((col) => ({
-    title: col,
-    dataIndex: col,
-    key: col,
+    title: col.name,
+    dataIndex: col.name,
+    key: col.name,
+    align: isNumeric(col.logicalType) ? ('right' as const) : undefined,
     render: (text: CellValue) => {
       if (text === null || text === undefined) {
         return <Text type="secondary">NULL</Text>;
@@ -56,7 +66,7 @@ const QueryResults: React.FC<QueryResultsProps> = ({ result, loading = false })
   const dataSource = result.rows.map((row, index) => {
     const record: Record<string, CellValue> = { key: index };
     result.columns.forEach((col, colIndex) => {
-      record[col] = row[colIndex];
+      record[col.name] = row[colIndex];
     });
     return record;
   });
@@ -66,7 +76,13 @@ const QueryResults: React.FC<QueryResultsProps> = ({ result, loading = false })
       <div className="mb-4 flex flex-col sm:flex-row sm:items-center gap-2 sm:gap-4">
         <Text strong className="text-sm sm:text-base">
           {result.rowCount} row{result.rowCount !== 1 ? 's' : ''} returned
+          {result.hasMore ? ' (more available)' : ''}
         </Text>
+        {result.truncated && (
+          <Text type="warning" className="text-xs sm:text-sm">
+            Result truncated at the connection's row or size limit
+          </Text>
+        )}
         <Text type="secondary" className="text-xs sm:text-sm">
           Execution time: {result.executionTimeMs}ms
         </Text>
@@ -86,6 +102,13 @@ const QueryResults: React.FC<QueryResultsProps> = ({ result, loading = false })
           className="w-full"
         />
       </div>
+      {result.hasMore && onLoadMore && (
+        <div className="mt-4 text-center">
+          <Button onClick={onLoadMore} loading={loadingMore}>
+            Load more rows
+          </Button>
+        </div>
+      )}
     </div>
   );
 };
@@ -1,9 +1,10 @@
 import React from 'react';
-import { Layout, Typography, Card, Button } from 'antd';
-import { PlayCircleOutlined } from '@ant-design/icons';
+import { Layout, Typography, Card, Button, Space } from 'antd';
+import { PlayCircleOutlined, StopOutlined } from '@ant-design/icons';
 import { DatabaseConnection } from '../types/database';
 import { SchemaMetadata } from '../types/schema';
 import { QueryResponse } from '../types/query';
+import { ErrorResponse } from '../utils/error';
 import SQLEditor from './SQLEditor';
 import QueryResults from './QueryResults';
 
@@ -16,9 +17,13 @@ interface QueryWorkspaceProps {
   sql: string;
   queryResult: QueryResponse | null;
   queryLoading: boolean;
+  queryError: ErrorResponse | null;
+  loadingMore: boolean;
   executionTime: string;
   onSqlChange: (value: string) => void;
   onExecuteQuery: () => void;
+  onCancelQuery: () => void;
+  onLoadMore: () => void;
 }
 
 const QueryWorkspace: React.FC<QueryWorkspaceProps> = ({
@@ -27,9 +32,13 @@ const QueryWorkspace: React.FC<QueryWorkspaceProps> = ({
   sql,
   queryResult,
   queryLoading,
+  queryError,
+  loadingMore,
   executionTime,
   onSqlChange,
   onExecuteQuery,
+  onCancelQuery,
+  onLoadMore,
 }) => {
   return (
     <Content style={{ padding: '12px 24px 12px 24px', background: '#f5f5f5', minHeight: '100vh', display: 'flex', flexDirection: 'column' }}>
@@ -119,23 +128,41 @@ const QueryWorkspace: React.FC<QueryWorkspaceProps> = ({
               </Title>
             }
             extra={
-              <Button
-                type="primary"
-                icon={<PlayCircleOutlined />}
-                onClick={onExecuteQuery}
-                loading={queryLoading}
-                size="large"
-                style={{
-                  height: '40px',
-                  paddingLeft: '20px',
-                  paddingRight: '20px',
-                  borderRadius: '6px',
-                  fontSize: '14px',
-                  fontWeight: 500,
-                }}
-              >
-                EXECUTE
-              </Button>
+              <Space>
+                {queryLoading && (
+                  <Button
+                    danger
+                    icon={<StopOutlined />}
+                    onClick={onCancelQuery}
+                    size="large"
+                    style={{
+                      height: '40px',
+                      borderRadius: '6px',
+                      fontSize: '14px',
+                      fontWeight: 500,
+                    }}
+                  >
+                    CANCEL
+                  </Button>
+                )}
+                <Button
+                  type="primary"
+                  icon={<PlayCircleOutlined />}
+                  onClick={onExecuteQuery}
+                  loading={queryLoading}
+                  size="large"
+                  style={{
+                    height: '40px',
+                    paddingLeft: '20px',
+                    paddingRight: '20px',
+                    borderRadius: '6px',
+                    fontSize: '14px',
+                    fontWeight: 500,
+                  }}
+                >
+                  EXECUTE
+                </Button>
+              </Space>
             }
             style={{
               marginBottom: '10px',
@@ -150,6 +177,7 @@ const QueryWorkspace: React.FC<QueryWorkspaceProps> = ({
                 onChange={(value) => onSqlChange(value || '')}
                 height="280px"
                 onExecute={onExecuteQuery}
+                error={queryError}
               />
             </div>
           </Card>
@@ -168,7 +196,12 @@ const QueryWorkspace: React.FC<QueryWorkspaceProps> = ({
               bodyStyle={{ padding: '20px', flex: 1, display: 'flex', flexDirection: 'column', overflow: 'hidden' }}
             >
               <div style={{ flex: 1, overflow: 'auto' }}>
-                <QueryResults result={queryResult} loading={queryLoading} />
+                <QueryResults
+                  result={queryResult}
+                  loading={queryLoading}
+                  loadingMore={loadingMore}
+                  onLoadMore={onLoadMore}
+                />
               </div>
             </Card>
           )}
@@ -1,19 +1,53 @@
-import React, { useRef } from 'react';
+import React, { useEffect, useRef } from 'react';
 import Editor, { Monaco } from '@monaco-editor/react';
 import { editor as MonacoEditor } from 'monaco-editor';
+import { ErrorResponse } from '../utils/error';
 
 interface SQLEditorProps {
   value: string;
   onChange: (value: string | undefined) => void;
   height?: string;
   onExecute?: () => void;
+  error?: ErrorResponse | null;
 }
 
-const SQLEditor: React.FC<SQLEditorProps> = ({ value, onChange, height = '300px', onExecute }) => {
+const ERROR_MARKER_OWNER = 'query-error';
+
+const SQLEditor: React.FC<SQLEditorProps> = ({ value, onChange, height = '300px', onExecute, error }) => {
   const editorRef = useRef<MonacoEditor.IStandaloneCodeEditor | null>(null);
+  const monacoRef = useRef<Monaco | null>(null);
+
+  // Underline the token a query error points at
+  useEffect(() => {
+    const model = editorRef.current?.getModel();
+    const monaco = monacoRef.current;
+    if (!model || !monaco) {
+      return;
+    }
+
+    if (!error?.line || !error.column) {
+      monaco.editor.setModelMarkers(model, ERROR_MARKER_OWNER, []);
+      return;
+    }
+
+    const word = model.getWordAtPosition({ lineNumber: error.line, column: error.column });
+    const message = [error.error, error.detail, error.hint && `Hint: ${error.hint}`].filter(Boolean).join('\n');
+    monaco.editor.setModelMarkers(model, ERROR_MARKER_OWNER, [
+      {
+        severity: monaco.MarkerSeverity.Error,
+        message,
+        code: error.sqlstate,
+        startLineNumber: error.line,
+        startColumn: error.column,
+        endLineNumber: error.line,
+        endColumn: word ? word.endColumn : error.column + 1,
+      },
+    ]);
+  }, [error]);
 
   const handleEditorDidMount = (editor: MonacoEditor.IStandaloneCodeEditor, monaco: Monaco) => {
     editorRef.current = editor;
+    monacoRef.current = monaco;
 
     // Add Ctrl+Enter / Cmd+Enter shortcut
     editor.addCommand(monaco.KeyMod.CtrlCmd | monaco.KeyCode.Enter, () => {
@@ -0,0 +1,102 @@
+import React, { useEffect, useState } from 'react';
+import { Empty, Modal, Select, Space, Spin, Typography } from 'antd';
+import { diffDatabaseSchemas } from '../api/schema';
+import { DatabaseConnection } from '../types/database';
+import { ConnectionDiff, DiffSide } from '../types/schemaDiff';
+import { showError } from '../utils/error';
+import SchemaDiffView from './SchemaDiffView';
+
+const { Text, Paragraph } = Typography;
+
+interface SchemaCompareModalProps {
+  dbName: string;
+  databases: DatabaseConnection[];
+  open: boolean;
+  onClose: () => void;
+}
+
+// Migration scripts are written for PostgreSQL, from the schemas of both databases
+const isPostgres = (db?: DatabaseConnection) => /^postgres(ql)?:\/\//.test(db?.url ?? '');
+
+const SchemaCompareModal: React.FC<SchemaCompareModalProps> = ({ dbName, databases, open, onClose }) => {
+  const [other, setOther] = useState<string | undefined>();
+  const [migrate, setMigrate] = useState<DiffSide | undefined>();
+  const [diff, setDiff] = useState<ConnectionDiff | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  useEffect(() => {
+    if (!open || !other) {
+      return;
+    }
+    setLoading(true);
+    setDiff(null);
+    diffDatabaseSchemas({ left: dbName, right: other, migrate })
+      .then(setDiff)
+      .catch((error: unknown) => showError(error, 'Failed to compare schemas'))
+      .finally(() => setLoading(false));
+  }, [dbName, other, migrate, open]);
+
+  const others = databases.filter((db) => db.name !== dbName);
+  const canMigrate =
+    isPostgres(databases.find((db) => db.name === dbName)) && isPostgres(others.find((db) => db.name === other));
+
+  // Only ask for a script while both databases are PostgreSQL
+  useEffect(() => {
+    if (!canMigrate) {
+      setMigrate(undefined);
+    }
+  }, [canMigrate]);
+
+  return (
+    <Modal title={`Compare ${dbName} with another database`} open={open} onCancel={onClose} footer={null} width={720}>
+      {others.length === 0 ? (
+        <Empty description="Add another database to compare schemas" />
+      ) : (
+        <Space direction="vertical" style={{ width: '100%' }}>
+          <Space wrap>
+            <Text>Compare with</Text>
+            <Select
+              style={{ width: 200 }}
+              placeholder="Database"
+              options={others.map((db) => ({ value: db.name, label: db.name }))}
+              value={other}
+              onChange={setOther}
+            />
+            <Text>Migration script for</Text>
+            <Select
+              style={{ width: 160 }}
+              allowClear
+              disabled={!canMigrate}
+              placeholder={canMigrate ? 'None' : 'PostgreSQL only'}
+              options={[
+                { value: 'left', label: dbName },
+                ...(other ? [{ value: 'right', label: other }] : []),
+              ]}
+              value={migrate}
+              onChange={setMigrate}
+            />
+          </Space>
+          {loading ? (
+            <Spin style={{ display: 'block', textAlign: 'center', padding: '24px' }} />
+          ) : (
+            diff && (
+              <>
+                <Text type="secondary">
+                  Changes going from {diff.left} to {diff.right}
+                </Text>
+                <SchemaDiffView diff={diff.changes} />
+                {diff.migration && (
+                  <Paragraph copyable={{ text: diff.migration }} style={{ marginTop: '12px' }}>
+                    <pre style={{ maxHeight: '320px', overflow: 'auto', fontSize: '12px' }}>{diff.migration}</pre>
+                  </Paragraph>
+                )}
+              </>
+            )
+          )}
+        </Space>
+      )}
+    </Modal>
+  );
+};
+
+export default SchemaCompareModal;
@@ -0,0 +1,111 @@
+import React from 'react';
+import { Collapse, Empty, Space, Tag, Typography } from 'antd';
+import { SchemaDiff, TableDiff } from '../types/schemaDiff';
+
+const { Text } = Typography;
+
+interface SchemaDiffViewProps {
+  diff: SchemaDiff;
+}
+
+// One line per change of a table, marked added (+), dropped (-) or changed (~)
+const tableChanges = (table: TableDiff): { mark: string; text: string }[] => [
+  ...(table.addedColumns ?? []).map((column) => ({
+    mark: '+',
+    text: `column ${column.name} ${column.dataType}${column.nullable ? '' : ' NOT NULL'}`,
+  })),
+  ...(table.droppedColumns ?? []).map((column) => ({ mark: '-', text: `column ${column}` })),
+  ...(table.retypedColumns ?? []).map((change) => ({
+    mark: '~',
+    text: `column ${change.column}: ${change.from} → ${change.to}`,
+  })),
+  ...(table.nullabilityChanges ?? []).map((change) => ({
+    mark: '~',
+    text: `column ${change.column} is now ${change.nullable ? 'NULL' : 'NOT NULL'}`,
+  })),
+  ...(table.defaultChanges ?? []).map((change) => ({
+    mark: '~',
+    text: change.default ? `column ${change.column} defaults to ${change.default}` : `column ${change.column} has no default`,
+  })),
+  ...(table.primaryKeyChange
+    ? [
+        {
+          mark: '~',
+          text: `primary key (${(table.primaryKeyChange.from ?? []).join(', ')}) → (${(table.primaryKeyChange.to ?? []).join(', ')})`,
+        },
+      ]
+    : []),
+  ...(table.addedIndexes ?? []).map((index) => ({
+    mark: '+',
+    text: `${index.unique ? 'unique ' : ''}index ${index.name} (${index.columns.join(', ')})${
+      index.predicate ? ` WHERE ${index.predicate}` : ''
+    }`,
+  })),
+  ...(table.droppedIndexes ?? []).map((index) => ({ mark: '-', text: `index ${index}` })),
+  ...(table.addedForeignKeys ?? []).map((fk) => ({
+    mark: '+',
+    text: `foreign key ${fk.name} (${fk.columns.join(', ')}) → ${fk.referencedSchema}.${fk.referencedTable} (${fk.referencedColumns.join(', ')})`,
+  })),
+  ...(table.droppedForeignKeys ?? []).map((fk) => ({ mark: '-', text: `foreign key ${fk}` })),
+  ...(table.addedUniqueConstraints ?? []).map((unique) => ({
+    mark: '+',
+    text: `unique ${unique.name} (${unique.columns.join(', ')})`,
+  })),
+  ...(table.droppedUniqueConstraints ?? []).map((unique) => ({ mark: '-', text: `unique ${unique}` })),
+  ...(table.addedCheckConstraints ?? []).map((check) => ({
+    mark: '+',
+    text: `check ${check.name} (${check.expression})`,
+  })),
+  ...(table.droppedCheckConstraints ?? []).map((check) => ({ mark: '-', text: `check ${check}` })),
+];
+
+// Added, dropped and changed tables or views, in that order
+const objectChanges = (added: string[], dropped: string[], changed: string[], kind: string) => [
+  ...added.map((name) => ({ key: `added-${kind}-${name}`, name, tag: <Tag color="green">ADDED {kind}</Tag> })),
+  ...dropped.map((name) => ({ key: `dropped-${kind}-${name}`, name, tag: <Tag color="red">DROPPED {kind}</Tag> })),
+  ...changed.map((name) => ({ key: `changed-${kind}-${name}`, name, tag: <Tag color="gold">CHANGED {kind}</Tag> })),
+];
+
+const markColor: Record<string, string> = { '+': '#389e0d', '-': '#cf1322', '~': '#d48806' };
+
+const SchemaDiffView: React.FC<SchemaDiffViewProps> = ({ diff }) => {
+  const objects = [
+    ...objectChanges(diff.addedTables, diff.droppedTables, [], 'TABLE'),
+    ...objectChanges(diff.addedViews, diff.droppedViews, diff.changedViews, 'VIEW'),
+  ];
+  if (objects.length === 0 && diff.changedTables.length === 0) {
+    return <Empty description="No structural changes" />;
+  }
+
+  return (
+    <Space direction="vertical" style={{ width: '100%' }}>
+      {objects.map(({ key, name, tag }) => (
+        <Space key={key}>
+          {tag}
+          <Text strong>{name}</Text>
+        </Space>
+      ))}
+      {diff.changedTables.length > 0 && (
+        <Collapse
+          defaultActiveKey={diff.changedTables.map((table) => table.table)}
+          items={diff.changedTables.map((table) => ({
+            key: table.table,
+            label: (
+              <Space>
+                <Tag color="gold">CHANGED TABLE</Tag>
+                <Text strong>{table.table}</Text>
+              </Space>
+            ),
+            children: tableChanges(table).map(({ mark, text }) => (
+              <div key={`${mark}${text}`} style={{ fontFamily: 'monospace', fontSize: '12px', padding: '2px 0' }}>
+                <Text style={{ color: markColor[mark], fontWeight: 600 }}>{mark}</Text> {text}
+              </div>
+            )),
+          }))}
+        />
+      )}
+    </Space>
+  );
+};
+
+export default SchemaDiffView;
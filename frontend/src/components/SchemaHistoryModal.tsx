@@ -0,0 +1,77 @@
+import React, { useEffect, useState } from 'react';
+import { Empty, Modal, Select, Space, Spin, Typography } from 'antd';
+import { diffSchemaSnapshots, listSchemaSnapshots } from '../api/schema';
+import { SchemaSnapshot, SnapshotDiff } from '../types/schemaDiff';
+import { showError } from '../utils/error';
+import SchemaDiffView from './SchemaDiffView';
+
+const { Text } = Typography;
+
+interface SchemaHistoryModalProps {
+  dbName: string;
+  open: boolean;
+  onClose: () => void;
+}
+
+const snapshotLabel = (snapshot: SchemaSnapshot) =>
+  `#${snapshot.version} · ${new Date(snapshot.createdAt).toLocaleString()}`;
+
+const SchemaHistoryModal: React.FC<SchemaHistoryModalProps> = ({ dbName, open, onClose }) => {
+  const [snapshots, setSnapshots] = useState<SchemaSnapshot[]>([]);
+  const [from, setFrom] = useState<number | undefined>();
+  const [to, setTo] = useState<number | undefined>();
+  const [diff, setDiff] = useState<SnapshotDiff | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  // Start by comparing the two latest snapshots
+  useEffect(() => {
+    if (!open) {
+      return;
+    }
+    setDiff(null);
+    listSchemaSnapshots(dbName)
+      .then((data) => {
+        setSnapshots(data);
+        setFrom(data.length > 1 ? data[data.length - 2].version : undefined);
+        setTo(data.length > 1 ? data[data.length - 1].version : undefined);
+      })
+      .catch((error: unknown) => showError(error, 'Failed to load schema history'));
+  }, [dbName, open]);
+
+  useEffect(() => {
+    if (!open || from === undefined || to === undefined) {
+      return;
+    }
+    setLoading(true);
+    diffSchemaSnapshots(dbName, { from, to })
+      .then(setDiff)
+      .catch((error: unknown) => showError(error, 'Failed to compare schema snapshots'))
+      .finally(() => setLoading(false));
+  }, [dbName, open, from, to]);
+
+  const options = snapshots.map((snapshot) => ({ value: snapshot.version, label: snapshotLabel(snapshot) }));
+
+  return (
+    <Modal title={`Schema history of ${dbName}`} open={open} onCancel={onClose} footer={null} width={720}>
+      {snapshots.length < 2 ? (
+        <Empty description="The schema has to be refreshed at least twice to compare snapshots" />
+      ) : (
+        <Space direction="vertical" style={{ width: '100%' }}>
+          <Space>
+            <Text>From</Text>
+            <Select style={{ width: 260 }} options={options} value={from} onChange={setFrom} />
+            <Text>to</Text>
+            <Select style={{ width: 260 }} options={options} value={to} onChange={setTo} />
+          </Space>
+          {loading ? (
+            <Spin style={{ display: 'block', textAlign: 'center', padding: '24px' }} />
+          ) : (
+            diff && <SchemaDiffView diff={diff.changes} />
+          )}
+        </Space>
+      )}
+    </Modal>
+  );
+};
+
+export default SchemaHistoryModal;
@@ -1,14 +1,17 @@
-import React from 'react';
-import { Layout, Typography, Button, Spin, Tree } from 'antd';
-import { ReloadOutlined } from '@ant-design/icons';
+import React, { useState } from 'react';
+import { Layout, Typography, Button, Spin, Tree, Tooltip, Space } from 'antd';
+import { HistoryOutlined, ReloadOutlined, SwapOutlined } from '@ant-design/icons';
 import { DatabaseConnection } from '../types/database';
-import { SchemaMetadata } from '../types/schema';
+import { SchemaMetadata, TableInfo } from '../types/schema';
 import { buildSchemaTreeData } from '../utils/schemaTree';
+import SchemaCompareModal from './SchemaCompareModal';
+import SchemaHistoryModal from './SchemaHistoryModal';
 
 const { Sider } = Layout;
 const { Text } = Typography;
 
 interface SchemaSidebarProps {
+  databases: DatabaseConnection[];
   selectedDb: DatabaseConnection | null;
   schema: SchemaMetadata | null;
   loading: boolean;
@@ -16,9 +19,11 @@ interface SchemaSidebarProps {
   firstColumnCollapsed: boolean;
   onRefresh: () => void;
   onExpandedKeysChange: (keys: React.Key[]) => void;
+  onCountRows: (table: TableInfo) => void;
 }
 
 const SchemaSidebar: React.FC<SchemaSidebarProps> = ({
+  databases,
   selectedDb,
   schema,
   loading,
@@ -26,7 +31,11 @@ const SchemaSidebar: React.FC<SchemaSidebarProps> = ({
   firstColumnCollapsed,
   onRefresh,
   onExpandedKeysChange,
+  onCountRows,
 }) => {
+  const [historyOpen, setHistoryOpen] = useState(false);
+  const [compareOpen, setCompareOpen] = useState(false);
+
   return (
     <Sider
       width={256}
@@ -55,20 +64,59 @@ const SchemaSidebar: React.FC<SchemaSidebarProps> = ({
               justifyContent: 'space-between',
             }}
           >
-            <Text strong style={{ color: '#856404', fontSize: '14px' }}>
-              {selectedDb.name.toUpperCase()}
-            </Text>
-            <Button
-              icon={<ReloadOutlined />}
-              onClick={onRefresh}
-              size="small"
-              style={{
-                borderRadius: '4px',
-              }}
+            <Tooltip
+              title={
+                schema &&
+                `Schema read ${new Date(schema.cachedAt).toLocaleString()}${
+                  schema.stale ? ', refreshing it failed' : ''
+                }`
+              }
             >
-              REFRESH
-            </Button>
+              <Text strong style={{ color: '#856404', fontSize: '14px' }}>
+                {selectedDb.name.toUpperCase()}
+                {schema?.stale && <Text type="warning"> (stale)</Text>}
+              </Text>
+            </Tooltip>
+            <Space size={4}>
+              <Tooltip title="Compare schema snapshots">
+                <Button
+                  icon={<HistoryOutlined />}
+                  onClick={() => setHistoryOpen(true)}
+                  size="small"
+                  style={{
+                    borderRadius: '4px',
+                  }}
+                />
+              </Tooltip>
+              <Tooltip title="Compare with another database">
+                <Button
+                  icon={<SwapOutlined />}
+                  onClick={() => setCompareOpen(true)}
+                  size="small"
+                  style={{
+                    borderRadius: '4px',
+                  }}
+                />
+              </Tooltip>
+              <Button
+                icon={<ReloadOutlined />}
+                onClick={onRefresh}
+                size="small"
+                style={{
+                  borderRadius: '4px',
+                }}
+              >
+                REFRESH
+              </Button>
+            </Space>
           </div>
+          <SchemaHistoryModal dbName={selectedDb.name} open={historyOpen} onClose={() => setHistoryOpen(false)} />
+          <SchemaCompareModal
+            dbName={selectedDb.name}
+            databases={databases}
+            open={compareOpen}
+            onClose={() => setCompareOpen(false)}
+          />
 
           {/* Body - Tables Tree */}
           <div style={{ padding: '12px 16px', overflowX: 'hidden', width: '100%', boxSizing: 'border-box' }}>
@@ -77,7 +125,7 @@ const SchemaSidebar: React.FC<SchemaSidebarProps> = ({
             ) : schema ? (
               <div style={{ width: '100%', overflow: 'hidden' }}>
                 <Tree
-                  treeData={buildSchemaTreeData(schema)}
+                  treeData={buildSchemaTreeData(schema, onCountRows)}
                   defaultExpandAll={false}
                   expandedKeys={expandedKeys}
                   onExpand={(keys) => onExpandedKeysChange(keys)}
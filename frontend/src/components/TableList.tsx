@@ -1,6 +1,7 @@
 import React from 'react';
 import { Collapse, Tag, Typography, Space } from 'antd';
 import { TableInfo } from '../types/schema';
+import { qualifiedName } from '../utils/schemaTree';
 
 const { Text } = Typography;
 
@@ -17,11 +18,11 @@ const TableList: React.FC<TableListProps> = ({ tables }) => {
     <Collapse
       ghost
       items={tables.map((table) => ({
-        key: table.name,
+        key: qualifiedName(table),
         label: (
           <div style={{ display: 'flex', alignItems: 'center', justifyContent: 'space-between', width: '100%' }}>
             <Space size="small">
-              <Text strong style={{ fontSize: '13px' }}>{table.name}</Text>
+              <Text strong style={{ fontSize: '13px' }}>{qualifiedName(table)}</Text>
               <Tag color="blue" style={{ margin: 0, fontSize: '11px', padding: '0 6px', height: '20px', lineHeight: '20px' }}>TABLE</Tag>
               <Text type="secondary" style={{ fontSize: '11px' }}>
                 {table.rowCount || 0} ROWS
@@ -1,6 +1,7 @@
 import React from 'react';
 import { Collapse, Tag, Typography, Space } from 'antd';
 import { ViewInfo } from '../types/schema';
+import { qualifiedName } from '../utils/schemaTree';
 
 const { Text } = Typography;
 
@@ -17,11 +18,11 @@ const ViewList: React.FC<ViewListProps> = ({ views }) => {
     <Collapse
       ghost
       items={views.map((view) => ({
-        key: view.name,
+        key: qualifiedName(view),
         label: (
           <div style={{ display: 'flex', alignItems: 'center', justifyContent: 'space-between', width: '100%' }}>
             <Space>
-              <Text strong>{view.name}</Text>
+              <Text strong>{qualifiedName(view)}</Text>
               <Tag color="green">VIEW</Tag>
             </Space>
           </div>
@@ -54,6 +55,11 @@ const ViewList: React.FC<ViewListProps> = ({ views }) => {
                 </Text>
               </div>
             ))}
+            {view.definition && (
+              <pre style={{ margin: '8px 0 0', padding: '8px 12px', background: '#fafafa', borderRadius: '4px', fontSize: '12px', whiteSpace: 'pre-wrap' }}>
+                {view.definition.trim()}
+              </pre>
+            )}
           </div>
         ),
       }))}
@@ -16,10 +16,15 @@ describe('QueryResults', () => {
 
   it('displays empty result set message', () => {
     const emptyResult: QueryResponse = {
-      columns: ['id', 'name'],
+      columns: [
+        { name: 'id', dbType: 'INT4', logicalType: 'integer', nullable: false },
+        { name: 'name', dbType: 'TEXT', logicalType: 'text', nullable: true },
+      ],
       rows: [],
       rowCount: 0,
       executionTimeMs: 10,
+      hasMore: false,
+      truncated: false,
     };
     render(<QueryResults result={emptyResult} loading={false} />);
     expect(screen.getByText(/returned no rows/)).toBeInTheDocument();
@@ -28,13 +33,18 @@ describe('QueryResults', () => {
 
   it('displays query results with data', () => {
     const result: QueryResponse = {
-      columns: ['id', 'name'],
+      columns: [
+        { name: 'id', dbType: 'INT4', logicalType: 'integer', nullable: false },
+        { name: 'name', dbType: 'TEXT', logicalType: 'text', nullable: true },
+      ],
       rows: [
         [1, 'Alice'],
         [2, 'Bob'],
       ],
       rowCount: 2,
       executionTimeMs: 15,
+      hasMore: false,
+      truncated: false,
     };
     render(<QueryResults result={result} loading={false} />);
     expect(screen.getByText('2 rows returned')).toBeInTheDocument();
@@ -45,14 +55,47 @@ describe('QueryResults', () => {
 
   it('handles null values', () => {
     const result: QueryResponse = {
-      columns: ['id', 'name'],
+      columns: [
+        { name: 'id', dbType: 'INT4', logicalType: 'integer', nullable: false },
+        { name: 'name', dbType: 'TEXT', logicalType: 'text', nullable: true },
+      ],
       rows: [[1, null]],
       rowCount: 1,
       executionTimeMs: 5,
+      hasMore: false,
+      truncated: false,
     };
     render(<QueryResults result={result} loading={false} />);
     // NULL values should be displayed
     expect(screen.getByText('1 row returned')).toBeInTheDocument();
   });
-});
 
+  it('offers to load more rows when a cursor is open', () => {
+    const onLoadMore = jest.fn();
+    const result: QueryResponse = {
+      columns: [{ name: 'id', dbType: 'INT4', logicalType: 'integer', nullable: false }],
+      rows: [[1]],
+      rowCount: 1,
+      executionTimeMs: 5,
+      hasMore: true,
+      truncated: false,
+      cursor: 'abc',
+    };
+    render(<QueryResults result={result} loading={false} onLoadMore={onLoadMore} />);
+    screen.getByText('Load more rows').click();
+    expect(onLoadMore).toHaveBeenCalled();
+  });
+
+  it('warns when the result was truncated', () => {
+    const result: QueryResponse = {
+      columns: [{ name: 'id', dbType: 'INT4', logicalType: 'integer', nullable: false }],
+      rows: [[1]],
+      rowCount: 1,
+      executionTimeMs: 5,
+      hasMore: false,
+      truncated: true,
+    };
+    render(<QueryResults result={result} loading={false} />);
+    expect(screen.getByText(/Result truncated/)).toBeInTheDocument();
+  });
+});
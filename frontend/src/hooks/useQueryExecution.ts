@@ -1,39 +1,86 @@
-import { useState, useCallback } from 'react';
+import { useState, useCallback, useEffect } from 'react';
 import { QueryResponse } from '../types/query';
-import { executeQuery } from '../api/query';
-import { showError, showSuccess } from '../utils/error';
+import { cancelQuery, executeQuery, fetchQueryPage } from '../api/query';
+import { ErrorResponse, getErrorResponse, showError, showSuccess } from '../utils/error';
 
 export const useQueryExecution = (dbName: string | null) => {
   const [sql, setSql] = useState<string>('SELECT * FROM');
   const [queryResult, setQueryResult] = useState<QueryResponse | null>(null);
   const [queryLoading, setQueryLoading] = useState(false);
+  const [loadingMore, setLoadingMore] = useState(false);
   const [executionTime, setExecutionTime] = useState<string>('-');
+  const [runningQueryId, setRunningQueryId] = useState<string | null>(null);
+  const [queryError, setQueryError] = useState<ErrorResponse | null>(null);
+
+  // An error location is only meaningful for the SQL that produced it
+  useEffect(() => {
+    setQueryError(null);
+  }, [sql]);
 
   const executeQueryHandler = useCallback(async () => {
     if (!sql.trim() || !dbName) {
       return;
     }
 
+    const queryId = crypto.randomUUID();
     setQueryLoading(true);
     setQueryResult(null);
+    setQueryError(null);
+    setRunningQueryId(queryId);
     const startTime = Date.now();
 
     try {
-      const response = await executeQuery(dbName, { sql });
+      const response = await executeQuery(dbName, { sql, queryId });
       const endTime = Date.now();
       setExecutionTime(`${((endTime - startTime) / 1000).toFixed(2)}s`);
       setQueryResult(response);
       showSuccess('Query executed successfully');
     } catch (err: unknown) {
+      setQueryError(getErrorResponse(err));
       showError(err, 'Failed to execute query');
     } finally {
       setQueryLoading(false);
+      setRunningQueryId(null);
     }
   }, [sql, dbName]);
 
+  const cancelQueryHandler = useCallback(async () => {
+    if (!dbName || !runningQueryId) {
+      return;
+    }
+
+    try {
+      await cancelQuery(dbName, runningQueryId);
+    } catch (err: unknown) {
+      showError(err, 'Failed to cancel query');
+    }
+  }, [dbName, runningQueryId]);
+
+  const loadMoreHandler = useCallback(async () => {
+    if (!dbName || !queryResult?.cursor) {
+      return;
+    }
+
+    setLoadingMore(true);
+
+    try {
+      const page = await fetchQueryPage(dbName, queryResult.cursor);
+      setQueryResult({
+        ...page,
+        rows: [...queryResult.rows, ...page.rows],
+        rowCount: queryResult.rowCount + page.rowCount,
+      });
+    } catch (err: unknown) {
+      showError(err, 'Failed to load more rows');
+    } finally {
+      setLoadingMore(false);
+    }
+  }, [dbName, queryResult]);
+
   const resetQuery = useCallback(() => {
     setSql('SELECT * FROM');
     setQueryResult(null);
+    setQueryError(null);
     setExecutionTime('-');
   }, []);
 
@@ -42,8 +89,12 @@ export const useQueryExecution = (dbName: string | null) => {
     setSql,
     queryResult,
     queryLoading,
+    queryError,
+    loadingMore,
     executionTime,
     executeQuery: executeQueryHandler,
+    cancelQuery: cancelQueryHandler,
+    loadMore: loadMoreHandler,
     resetQuery,
   };
 };
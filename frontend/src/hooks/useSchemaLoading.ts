@@ -1,24 +1,26 @@
 import { useState, useCallback } from 'react';
-import { SchemaMetadata } from '../types/schema';
-import { getSchemaMetadata } from '../api/schema';
+import { SchemaMetadata, TableInfo } from '../types/schema';
+import { countTableRows, getSchemaMetadata, refreshSchemaMetadata } from '../api/schema';
 import { showError } from '../utils/error';
+import { qualifiedName } from '../utils/schemaTree';
 
 export const useSchemaLoading = () => {
   const [schema, setSchema] = useState<SchemaMetadata | null>(null);
   const [loading, setLoading] = useState(false);
   const [expandedKeys, setExpandedKeys] = useState<React.Key[]>([]);
 
-  const loadSchema = useCallback(async (dbName: string) => {
+  // Load the cached schema, or read it from the database again when `refresh` is set
+  const loadSchema = useCallback(async (dbName: string, refresh = false) => {
     setLoading(true);
     setSchema(null); // Clear old schema immediately
     setExpandedKeys([]); // Clear expanded state
 
     try {
-      const data = await getSchemaMetadata(dbName);
+      const data = refresh ? await refreshSchemaMetadata(dbName) : await getSchemaMetadata(dbName);
       setSchema(data);
       // Auto-expand all tables
       if (data.tables.length > 0) {
-        setExpandedKeys(data.tables.map((table) => `table-${table.name}`));
+        setExpandedKeys(data.tables.map((table) => `table-${qualifiedName(table)}`));
       }
     } catch (error: unknown) {
       showError(error, 'Failed to load schema');
@@ -28,6 +30,22 @@ export const useSchemaLoading = () => {
     }
   }, []);
 
+  // Replace a table's estimated row count with an exact count
+  const countRows = useCallback(async (dbName: string, table: TableInfo) => {
+    try {
+      const { rowCount } = await countTableRows(dbName, table.schema, table.name);
+      const update = (tables: TableInfo[]): TableInfo[] =>
+        tables.map((t) =>
+          t.schema === table.schema && t.name === table.name
+            ? { ...t, rowCount, rowCountEstimated: false }
+            : { ...t, partitions: t.partitions && update(t.partitions) }
+        );
+      setSchema((current) => current && { ...current, tables: update(current.tables) });
+    } catch (error: unknown) {
+      showError(error, 'Failed to count rows');
+    }
+  }, []);
+
   const clearSchema = useCallback(() => {
     setSchema(null);
     setExpandedKeys([]);
@@ -38,6 +56,7 @@ export const useSchemaLoading = () => {
     loading,
     expandedKeys,
     loadSchema,
+    countRows,
     clearSchema,
     setExpandedKeys,
   };
@@ -1,11 +1,26 @@
+export interface ConnectionSettings {
+  statementTimeoutMs: number;
+  maxRows: number;
+  maxResponseBytes: number;
+  defaultLimit: number;
+  role?: string;
+  deniedFunctions: string[];
+  includeSchemas: string[];
+  excludeSchemas: string[];
+  rowCounts: 'exact' | 'estimated';
+  schemaCacheTtlSecs: number;
+}
+
 export interface DatabaseConnection {
   name: string;
   url: string;
+  settings: ConnectionSettings;
   createdAt: string;
   updatedAt: string;
 }
 
 export interface CreateDatabaseRequest {
   url: string;
+  settings?: Partial<ConnectionSettings>;
 }
 
@@ -1,4 +1,5 @@
 export interface NaturalLanguageQueryRequest {
   prompt: string;
+  queryId?: string;
 }
 
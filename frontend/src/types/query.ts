@@ -2,12 +2,35 @@ export type CellValue = string | number | boolean | null | Record<string, unknow
 
 export interface QueryRequest {
   sql: string;
+  pageSize?: number;
+  queryId?: string;
+}
+
+export type LogicalType =
+  | 'integer'
+  | 'decimal'
+  | 'text'
+  | 'timestamp'
+  | 'boolean'
+  | 'json'
+  | 'binary'
+  | 'array';
+
+export interface QueryColumn {
+  name: string;
+  dbType: string;
+  logicalType: LogicalType;
+  nullable?: boolean;
 }
 
 export interface QueryResponse {
-  columns: string[];
+  columns: QueryColumn[];
   rows: CellValue[][];
   rowCount: number;
   executionTimeMs: number;
+  hasMore: boolean;
+  truncated: boolean;
+  cursor?: string;
+  queryId?: string;
 }
 
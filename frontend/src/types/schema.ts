@@ -2,19 +2,103 @@ export interface SchemaMetadata {
   dbName: string;
   tables: TableInfo[];
   views: ViewInfo[];
+  materializedViews: ViewInfo[];
+  functions: FunctionInfo[];
+  sequences: SequenceInfo[];
   updatedAt: string;
+  cachedAt: string;
+  stale: boolean;
 }
 
+export type TableKind = 'table' | 'partitioned' | 'foreign';
+
 export interface TableInfo {
+  schema: string;
   name: string;
+  kind: TableKind;
+  description?: string;
   columns: ColumnInfo[];
   primaryKey?: string[];
   rowCount?: number;
+  rowCountEstimated?: boolean;
+  statistics?: TableStatistics;
+  foreignKeys?: ForeignKeyInfo[];
+  indexes?: IndexInfo[];
+  uniqueConstraints?: UniqueConstraintInfo[];
+  checkConstraints?: CheckConstraintInfo[];
+  partitionKey?: string;
+  partitionBound?: string;
+  partitions?: TableInfo[];
+}
+
+export interface TableStatistics {
+  totalBytes: number;
+  tableBytes: number;
+  indexBytes: number;
+  toastBytes: number;
+  deadTuples?: number;
+  lastVacuum?: string;
+  lastAutovacuum?: string;
+  lastAnalyze?: string;
+  lastAutoanalyze?: string;
+}
+
+export interface TableRowCount {
+  schema: string;
+  name: string;
+  rowCount: number;
+}
+
+export interface ForeignKeyInfo {
+  name: string;
+  columns: string[];
+  referencedSchema: string;
+  referencedTable: string;
+  referencedColumns: string[];
+  onDelete: string;
+}
+
+export interface IndexInfo {
+  name: string;
+  columns: string[];
+  unique: boolean;
+  method: string;
+  predicate?: string;
+}
+
+export interface UniqueConstraintInfo {
+  name: string;
+  columns: string[];
+}
+
+export interface CheckConstraintInfo {
+  name: string;
+  expression: string;
 }
 
 export interface ViewInfo {
+  schema: string;
   name: string;
+  description?: string;
   columns: ColumnInfo[];
+  definition?: string;
+}
+
+export interface FunctionInfo {
+  schema: string;
+  name: string;
+  arguments: string;
+  returnType: string;
+  returnsSet: boolean;
+  description?: string;
+}
+
+export interface SequenceInfo {
+  schema: string;
+  name: string;
+  dataType: string;
+  startValue: number;
+  increment: number;
 }
 
 export interface ColumnInfo {
@@ -22,5 +106,8 @@ export interface ColumnInfo {
   dataType: string;
   nullable: boolean;
   defaultValue?: string;
+  description?: string;
+  baseType?: string;
+  enumValues?: string[];
 }
 
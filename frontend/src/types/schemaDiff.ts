@@ -0,0 +1,82 @@
+import { CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, IndexInfo, UniqueConstraintInfo } from './schema';
+
+export interface SchemaSnapshot {
+  version: number;
+  createdAt: string;
+}
+
+export interface SnapshotDiffRequest {
+  from?: number;
+  to?: number;
+  since?: string;
+}
+
+export interface SnapshotDiff {
+  dbName: string;
+  from: SchemaSnapshot;
+  to: SchemaSnapshot;
+  changes: SchemaDiff;
+}
+
+export type DiffSide = 'left' | 'right';
+
+export interface ConnectionDiffRequest {
+  left: string;
+  right: string;
+  migrate?: DiffSide;
+}
+
+export interface ConnectionDiff {
+  left: string;
+  right: string;
+  changes: SchemaDiff;
+  migration?: string;
+}
+
+export interface SchemaDiff {
+  addedTables: string[];
+  droppedTables: string[];
+  changedTables: TableDiff[];
+  addedViews: string[];
+  droppedViews: string[];
+  changedViews: string[];
+}
+
+export interface TableDiff {
+  table: string;
+  addedColumns?: ColumnInfo[];
+  droppedColumns?: string[];
+  retypedColumns?: ColumnTypeChange[];
+  nullabilityChanges?: NullabilityChange[];
+  defaultChanges?: DefaultChange[];
+  primaryKeyChange?: PrimaryKeyChange;
+  addedIndexes?: IndexInfo[];
+  droppedIndexes?: string[];
+  addedForeignKeys?: ForeignKeyInfo[];
+  droppedForeignKeys?: string[];
+  addedUniqueConstraints?: UniqueConstraintInfo[];
+  droppedUniqueConstraints?: string[];
+  addedCheckConstraints?: CheckConstraintInfo[];
+  droppedCheckConstraints?: string[];
+}
+
+export interface ColumnTypeChange {
+  column: string;
+  from: string;
+  to: string;
+}
+
+export interface NullabilityChange {
+  column: string;
+  nullable: boolean;
+}
+
+export interface DefaultChange {
+  column: string;
+  default?: string | null;
+}
+
+export interface PrimaryKeyChange {
+  from?: string[] | null;
+  to?: string[] | null;
+}
@@ -5,6 +5,13 @@ export interface ErrorResponse {
   error: string;
   code?: string;
   details?: any;
+  // Location of a SQL error; lines, columns and position start at 1
+  line?: number;
+  column?: number;
+  position?: number;
+  sqlstate?: string;
+  hint?: string;
+  detail?: string;
 }
 
 /**
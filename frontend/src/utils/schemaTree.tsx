@@ -1,120 +1,338 @@
 import React from 'react';
 import { Typography } from 'antd';
-import { SchemaMetadata } from '../types/schema';
+import type { TreeDataNode } from 'antd';
+import { ColumnInfo, SchemaMetadata, TableInfo, ViewInfo } from '../types/schema';
 
 const { Text } = Typography;
 
-export const buildSchemaTreeData = (schema: SchemaMetadata | null) => {
-  if (!schema) return [];
+export const qualifiedName = (item: { schema: string; name: string }) => `${item.schema}.${item.name}`;
 
-  return [
-    {
+const rootTitle = (label: string, count: number) => (
+  <span style={{ fontWeight: 600, fontSize: '13px' }}>
+    {label} ({count})
+  </span>
+);
+
+const detailNode = (key: string, content: React.ReactNode): TreeDataNode => ({
+  title: (
+    <span style={{ fontSize: '11px', color: '#8c8c8c' }}>
+      {content}
+    </span>
+  ),
+  key,
+  isLeaf: true,
+});
+
+const formatBytes = (bytes: number) => {
+  const units = ['B', 'kB', 'MB', 'GB', 'TB'];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit += 1;
+  }
+  return `${unit === 0 ? value : value.toFixed(1)} ${units[unit]}`;
+};
+
+// Partitions are listed under their parent, with the rows they hold
+const buildTableNode = (table: TableInfo, onCountRows?: (table: TableInfo) => void): TreeDataNode => {
+  const tableName = qualifiedName(table);
+  const partitions = table.partitions ?? [];
+  const children: TreeDataNode[] = table.columns.map((col) => buildColumnNode(tableName, col, table));
+
+  if (table.statistics) {
+    const { totalBytes, tableBytes, indexBytes, toastBytes, deadTuples } = table.statistics;
+    children.push(detailNode(
+      `table-size-${tableName}`,
+      `Size: ${formatBytes(totalBytes)} (table ${formatBytes(tableBytes)}, indexes ${formatBytes(indexBytes)}, TOAST ${formatBytes(toastBytes)})`
+    ));
+    const lastVacuum = [table.statistics.lastVacuum, table.statistics.lastAutovacuum].filter(Boolean).sort().pop();
+    const lastAnalyze = [table.statistics.lastAnalyze, table.statistics.lastAutoanalyze].filter(Boolean).sort().pop();
+    children.push(detailNode(
+      `table-maintenance-${tableName}`,
+      `Dead rows: ${deadTuples ?? 0}, vacuumed: ${lastVacuum ? new Date(lastVacuum).toLocaleString() : 'never'}, analyzed: ${lastAnalyze ? new Date(lastAnalyze).toLocaleString() : 'never'}`
+    ));
+  }
+
+  if (table.partitionKey) {
+    children.push(detailNode(`table-partition-key-${tableName}`, `Partitioned by ${table.partitionKey}`));
+  }
+  if (partitions.length > 0) {
+    children.push({
+      title: rootTitle('Partitions', partitions.length),
+      key: `table-partitions-${tableName}`,
+      children: partitions.map((partition) => buildTableNode(partition, onCountRows)),
+    });
+  }
+
+  return {
+    title: (
+      <div style={{ display: 'flex', alignItems: 'center', gap: '6px', width: '100%', paddingRight: '8px' }}>
+        <Text
+          strong
+          ellipsis
+          title={table.partitionBound ?? table.description}
+          style={{
+            fontSize: '12px',
+            flex: 1,
+            minWidth: 0
+          }}
+        >
+          {tableName}
+        </Text>
+        {table.kind !== 'table' && (
+          <span style={{ fontSize: '10px', color: '#722ed1', flexShrink: 0 }}>
+            {table.kind}
+          </span>
+        )}
+        {table.kind !== 'foreign' && (
+          table.rowCountEstimated && table.kind === 'table' && onCountRows ? (
+            <a
+              title="Estimated from table statistics, click to count exactly"
+              onClick={(event) => {
+                event.stopPropagation();
+                onCountRows(table);
+              }}
+              style={{ fontSize: '10px', flexShrink: 0 }}
+            >
+              (~{table.rowCount || 0} rows)
+            </a>
+          ) : (
+            <span style={{ fontSize: '10px', color: '#8c8c8c', flexShrink: 0 }}>
+              ({table.rowCountEstimated && '~'}{table.rowCount || 0} rows)
+            </span>
+          )
+        )}
+      </div>
+    ),
+    key: `table-${tableName}`,
+    children,
+  };
+};
+
+const buildViewNode = (view: ViewInfo): TreeDataNode => {
+  const viewName = qualifiedName(view);
+  const children: TreeDataNode[] = view.columns.map((col) => buildColumnNode(viewName, col));
+
+  if (view.definition) {
+    children.push(detailNode(
+      `view-definition-${viewName}`,
+      <Text code style={{ fontSize: '11px', whiteSpace: 'pre-wrap' }}>{view.definition.trim()}</Text>
+    ));
+  }
+
+  return {
+    title: (
+      <Text strong ellipsis title={view.description} style={{ fontSize: '12px' }}>
+        {viewName}
+      </Text>
+    ),
+    key: `view-${viewName}`,
+    children,
+  };
+};
+
+const buildColumnNode = (tableName: string, col: ColumnInfo, table?: TableInfo): TreeDataNode => {
+  const constraints: TreeDataNode[] = [];
+
+  if (table?.primaryKey?.includes(col.name)) {
+    constraints.push({
       title: (
-        <span style={{ fontWeight: 600, fontSize: '13px' }}>
-          Tables ({schema.tables.length})
+        <span style={{ fontSize: '11px', color: '#1890ff' }}>
+          PK (Primary Key)
         </span>
       ),
-      key: 'tables-root',
-      children: schema.tables.map((table) => ({
+      key: `constraint-pk-${tableName}-${col.name}`,
+      isLeaf: true,
+    });
+  }
+
+  table?.foreignKeys
+    ?.filter((fk) => fk.columns.includes(col.name))
+    .forEach((fk) => {
+      const referenced = fk.referencedColumns[fk.columns.indexOf(col.name)];
+      constraints.push({
         title: (
-          <div style={{ display: 'flex', alignItems: 'center', gap: '6px', width: '100%', paddingRight: '8px' }}>
-            <Text
-              strong
-              ellipsis
-              style={{
-                fontSize: '12px',
-                flex: 1,
-                minWidth: 0
-              }}
-            >
-              {table.name}
+          <span style={{ fontSize: '11px', color: '#722ed1' }}>
+            FK → {fk.referencedSchema}.{fk.referencedTable}.{referenced}
+          </span>
+        ),
+        key: `constraint-fk-${tableName}-${fk.name}-${col.name}`,
+        isLeaf: true,
+      });
+    });
+
+  if (table?.uniqueConstraints?.some((unique) => unique.columns.includes(col.name))) {
+    constraints.push({
+      title: (
+        <span style={{ fontSize: '11px', color: '#fa8c16' }}>
+          UNIQUE
+        </span>
+      ),
+      key: `constraint-unique-${tableName}-${col.name}`,
+      isLeaf: true,
+    });
+  }
+
+  if (!col.nullable) {
+    constraints.push({
+      title: (
+        <span style={{ fontSize: '11px', color: '#ff4d4f' }}>
+          NOT NULL
+        </span>
+      ),
+      key: `constraint-null-${tableName}-${col.name}`,
+      isLeaf: true,
+    });
+  }
+
+  constraints.push({
+    title: (
+      <span style={{ fontSize: '11px', color: '#8c8c8c' }}>
+        Type: {col.dataType.toUpperCase()}
+        {col.baseType && ` (${col.baseType.toUpperCase()})`}
+      </span>
+    ),
+    key: `constraint-type-${tableName}-${col.name}`,
+    isLeaf: true,
+  });
+
+  if (col.enumValues) {
+    constraints.push({
+      title: (
+        <span style={{ fontSize: '11px', color: '#8c8c8c' }}>
+          Values: {col.enumValues.join(', ')}
+        </span>
+      ),
+      key: `constraint-values-${tableName}-${col.name}`,
+      isLeaf: true,
+    });
+  }
+
+  if (col.description) {
+    constraints.push({
+      title: (
+        <span style={{ fontSize: '11px', color: '#8c8c8c' }}>
+          {col.description}
+        </span>
+      ),
+      key: `constraint-description-${tableName}-${col.name}`,
+      isLeaf: true,
+    });
+  }
+
+  if (col.defaultValue) {
+    constraints.push({
+      title: (
+        <span style={{ fontSize: '11px', color: '#8c8c8c' }}>
+          Default: {col.defaultValue}
+        </span>
+      ),
+      key: `constraint-default-${tableName}-${col.name}`,
+      isLeaf: true,
+    });
+  }
+
+  return {
+    title: (
+      <div style={{
+        display: 'flex',
+        alignItems: 'center',
+        width: '100%',
+        paddingRight: '8px',
+        boxSizing: 'border-box'
+      }}>
+        <Text
+          code
+          ellipsis
+          style={{
+            fontSize: '12px',
+            background: 'transparent',
+            padding: 0,
+            border: 'none',
+            flex: 1,
+            minWidth: 0
+          }}
+        >
+          {col.name}
+        </Text>
+      </div>
+    ),
+    key: `field-${tableName}-${col.name}`,
+    children: constraints.length > 0 ? constraints : undefined,
+    isLeaf: constraints.length === 0,
+  };
+};
+
+export const buildSchemaTreeData = (
+  schema: SchemaMetadata | null,
+  onCountRows?: (table: TableInfo) => void
+) => {
+  if (!schema) return [];
+
+  const roots: TreeDataNode[] = [
+    {
+      title: rootTitle('Tables', schema.tables.length),
+      key: 'tables-root',
+      children: schema.tables.map((table) => buildTableNode(table, onCountRows)),
+    },
+  ];
+
+  if (schema.views.length > 0) {
+    roots.push({
+      title: rootTitle('Views', schema.views.length),
+      key: 'views-root',
+      children: schema.views.map(buildViewNode),
+    });
+  }
+
+  const { materializedViews, functions, sequences } = schema;
+
+  if (materializedViews.length > 0) {
+    roots.push({
+      title: rootTitle('Materialized views', materializedViews.length),
+      key: 'materialized-views-root',
+      children: materializedViews.map(buildViewNode),
+    });
+  }
+
+  if (functions.length > 0) {
+    roots.push({
+      title: rootTitle('Functions', functions.length),
+      key: 'functions-root',
+      children: functions.map((fn) => {
+        const signature = `${qualifiedName(fn)}(${fn.arguments})`;
+        return {
+          title: (
+            <Text ellipsis title={fn.description} style={{ fontSize: '12px' }}>
+              {signature} → {fn.returnType}
             </Text>
-            <span style={{ fontSize: '10px', color: '#8c8c8c', flexShrink: 0 }}>
-              ({table.rowCount || 0} rows)
+          ),
+          key: `function-${signature}`,
+          isLeaf: true,
+        };
+      }),
+    });
+  }
+
+  if (sequences.length > 0) {
+    roots.push({
+      title: rootTitle('Sequences', sequences.length),
+      key: 'sequences-root',
+      children: sequences.map((sequence) => ({
+        title: (
+          <Text style={{ fontSize: '12px' }}>
+            {qualifiedName(sequence)}
+            <span style={{ fontSize: '10px', color: '#8c8c8c' }}>
+              {' '}({sequence.dataType}, start {sequence.startValue}, step {sequence.increment})
             </span>
-          </div>
+          </Text>
         ),
-        key: `table-${table.name}`,
-        children: table.columns.map((col) => {
-          const constraints = [];
-
-          if (table.primaryKey?.includes(col.name)) {
-            constraints.push({
-              title: (
-                <span style={{ fontSize: '11px', color: '#1890ff' }}>
-                  PK (Primary Key)
-                </span>
-              ),
-              key: `constraint-pk-${table.name}-${col.name}`,
-              isLeaf: true,
-            });
-          }
-
-          if (!col.nullable) {
-            constraints.push({
-              title: (
-                <span style={{ fontSize: '11px', color: '#ff4d4f' }}>
-                  NOT NULL
-                </span>
-              ),
-              key: `constraint-null-${table.name}-${col.name}`,
-              isLeaf: true,
-            });
-          }
-
-          constraints.push({
-            title: (
-              <span style={{ fontSize: '11px', color: '#8c8c8c' }}>
-                Type: {col.dataType.toUpperCase()}
-              </span>
-            ),
-            key: `constraint-type-${table.name}-${col.name}`,
-            isLeaf: true,
-          });
-
-          if (col.defaultValue) {
-            constraints.push({
-              title: (
-                <span style={{ fontSize: '11px', color: '#8c8c8c' }}>
-                  Default: {col.defaultValue}
-                </span>
-              ),
-              key: `constraint-default-${table.name}-${col.name}`,
-              isLeaf: true,
-            });
-          }
-
-          return {
-            title: (
-              <div style={{
-                display: 'flex',
-                alignItems: 'center',
-                width: '100%',
-                paddingRight: '8px',
-                boxSizing: 'border-box'
-              }}>
-                <Text
-                  code
-                  ellipsis
-                  style={{
-                    fontSize: '12px',
-                    background: 'transparent',
-                    padding: 0,
-                    border: 'none',
-                    flex: 1,
-                    minWidth: 0
-                  }}
-                >
-                  {col.name}
-                </Text>
-              </div>
-            ),
-            key: `field-${table.name}-${col.name}`,
-            children: constraints.length > 0 ? constraints : undefined,
-            isLeaf: constraints.length === 0,
-          };
-        }),
+        key: `sequence-${qualifiedName(sequence)}`,
+        isLeaf: true,
       })),
-    },
-  ];
+    });
+  }
+
+  return roots;
 };